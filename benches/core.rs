@@ -0,0 +1,97 @@
+#![allow(clippy::needless_return)]
+
+// how fast the core runs, for measuring changes to it. the cycle benches run whole frames of
+// the roms in benches/roms and report instructions a second, and the rest time decoding and
+// drawing on their own
+
+use std::path::Path;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use chip8_rust::core::{Chip8, Quirks};
+
+const ROMS: [&str; 3] = ["arithmetic", "sprites", "memory"];
+
+fn load(rom: &str) -> Chip8 {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("benches/roms").join(format!("{}.asm", rom));
+
+    let mut machine = Chip8::new();
+    machine.load_rom(&path).unwrap_or_else(|e| panic!("couldn't load {}: {}", path.display(), e));
+    machine.seed_random(0);
+
+    return machine;
+}
+
+fn cycle(c: &mut Criterion) {
+    let mut group = c.benchmark_group("cycle");
+
+    // the plain interpreter, with the decode cache, and translating blocks, to see what each
+    // is worth
+    let engines = [("uncached", false, false), ("cached", true, false), ("blocks", false, true)];
+    for rom in ROMS.iter() {
+        for (engine, decode_cache, translate_blocks) in engines.iter() {
+            let mut machine = load(rom);
+            machine.decode_cache = *decode_cache;
+            machine.translate_blocks = *translate_blocks;
+            group.throughput(Throughput::Elements(machine.tick_rate as u64));
+
+            group.bench_function(BenchmarkId::new(*rom, engine), |b| b.iter(|| machine.cycle().unwrap()));
+        }
+    }
+
+    group.finish();
+}
+
+// one of each instruction, apart from the ones that would wander off or wait: the stack ones,
+// the jumps, drawing and waiting for a key
+const DECODE_MIX: [u16; 28] = [
+    0x00E0, 0x3012, 0x4012, 0x5120, 0x6012, 0x7012, 0x8120, 0x8121, 0x8122, 0x8123, 0x8124,
+    0x8125, 0x8126, 0x8127, 0x812E, 0x9120, 0xA300, 0xC0FF, 0xE09E, 0xE0A1, 0xF007, 0xF015,
+    0xF018, 0xF01E, 0xF029, 0xF033, 0xF355, 0xF365
+];
+
+fn decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode");
+    group.throughput(Throughput::Elements(DECODE_MIX.len() as u64));
+
+    // pc and I are put back each time so the skips and stores stay where they started
+    let mut machine = Chip8::new();
+    group.bench_function("mix", |b| b.iter(|| {
+        machine.execute(0x1200).unwrap();
+        machine.execute(0xA300).unwrap();
+        for opcode in DECODE_MIX.iter() {
+            machine.execute(black_box(*opcode)).unwrap();
+        }
+    }));
+
+    // the cheapest instruction there is, so this is nearly all the decode
+    let mut machine = Chip8::new();
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("ld", |b| b.iter(|| machine.execute(black_box(0x6012)).unwrap()));
+
+    group.finish();
+}
+
+fn dxyn(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dxyn");
+
+    // I at the font's 0, drawn somewhere in the middle, over the edges, and at full height
+    let cases = [("5 rows", 0xD125, 10, 10), ("wrapped", 0xD125, 60, 30), ("15 rows", 0xD12F, 10, 10)];
+    for clip in [false, true].iter() {
+        for (name, opcode, x, y) in cases.iter() {
+            let mut machine = Chip8::new();
+            machine.quirks = Quirks { clip: *clip, ..Quirks::default() };
+            machine.execute(0xA050).unwrap();
+            machine.execute(0x6100 | x).unwrap();
+            machine.execute(0x6200 | y).unwrap();
+
+            let id = format!("{}{}", name, if *clip { ", clipped" } else { "" });
+            group.bench_function(id, |b| b.iter(|| machine.execute(black_box(*opcode)).unwrap()));
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, cycle, decode, dxyn);
+criterion_main!(benches);
@@ -0,0 +1,14 @@
+; counts through every pair of bytes doing sums on them, like the loops that move things about
+; in most games. it never draws anything, so it's just registers and jumps
+start:  LD V0, 0
+        LD V1, 0
+loop:   ADD V0, 1
+        LD V2, V0
+        ADD V2, V1
+        SUB V2, V1
+        SHR V2
+        XOR V3, V2
+        SE V0, 0
+        JP loop
+        ADD V1, 1
+        JP loop
@@ -0,0 +1,19 @@
+; turns a counter into decimal and copies the registers in and out of memory, the way score
+; keeping and save data get done, with a subroutine call on each pass
+start:  LD V5, 0
+loop:   ADD V5, 1
+        CALL score
+        LD I, save
+        LD [I], V5
+        LD I, save
+        LD V5, [I]
+        JP loop
+
+score:  LD I, digits
+        LD B, V5
+        LD V2, [I]
+        LD F, V2
+        RET
+
+digits: DB 0, 0, 0
+save:   DB 0, 0, 0, 0, 0, 0
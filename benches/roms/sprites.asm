@@ -0,0 +1,19 @@
+; fills the screen with the font a digit at a time, clearing it whenever it gets back to the
+; top left, so most of the time goes on drawing
+start:  CLS
+        LD V0, 0        ; x
+        LD V1, 0        ; y
+        LD V2, 0        ; digit
+row:    LD F, V2
+        DRW V0, V1, 5
+        ADD V2, 1
+        LD V3, #F
+        AND V2, V3
+        ADD V0, 5
+        SE V0, 60
+        JP row
+        LD V0, 0
+        ADD V1, 6
+        SE V1, 30
+        JP row
+        JP start
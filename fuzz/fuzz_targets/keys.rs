@@ -0,0 +1,41 @@
+#![no_main]
+
+// like the rom target, but with keys going up and down between frames, so the programs that
+// sit waiting on fx0a or checking keys with ex9e/exa1 get somewhere
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+
+use chip8_rust::core::{Chip8, Quirks};
+
+const MAX_ROM_SIZE: usize = 4096 - 0x200;
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    quirks: [bool; 5],
+    translate_blocks: bool,
+    program: Vec<u8>,
+    keys: Vec<u16> // which keys are held down during each frame, a bit each
+}
+
+fuzz_target!(|input: Input| {
+    let program = &input.program[..input.program.len().min(MAX_ROM_SIZE)];
+
+    let mut machine = Chip8::new();
+    machine.load_program(program).unwrap();
+    machine.quirks = Quirks {
+        shift: input.quirks[0],
+        load_store: input.quirks[1],
+        jump: input.quirks[2],
+        vf_reset: input.quirks[3],
+        clip: input.quirks[4]
+    };
+    machine.translate_blocks = input.translate_blocks;
+    machine.seed_random(0);
+
+    for held in input.keys.iter() {
+        for (key, state) in machine.keypad.iter_mut().enumerate() {
+            *state = (held >> key & 1) as u8;
+        }
+        let _ = machine.cycle();
+    }
+});
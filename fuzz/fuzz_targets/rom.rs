@@ -0,0 +1,34 @@
+#![no_main]
+
+// runs whatever bytes it's given as a rom for a while, which should never panic however broken
+// the program is. execution errors are fine, the platform just pauses on them, and carrying on
+// past them is what resuming does
+use libfuzzer_sys::fuzz_target;
+
+use chip8_rust::core::{Chip8, Quirks};
+
+const MAX_ROM_SIZE: usize = 4096 - 0x200;
+const FRAMES: usize = 60;
+
+fuzz_target!(|data: &[u8]| {
+    let (settings, program) = match data.split_first() {
+        Some((first, rest)) => (*first, &rest[..rest.len().min(MAX_ROM_SIZE)]),
+        None => return,
+    };
+
+    let mut machine = Chip8::new();
+    machine.load_program(program).unwrap();
+    machine.quirks = Quirks {
+        shift: settings & 1 != 0,
+        load_store: settings & 2 != 0,
+        jump: settings & 4 != 0,
+        vf_reset: settings & 8 != 0,
+        clip: settings & 16 != 0
+    };
+    machine.translate_blocks = settings & 32 != 0;
+    machine.seed_random(0);
+
+    for _ in 0..FRAMES {
+        let _ = machine.cycle();
+    }
+});
@@ -1,9 +1,66 @@
 #version 150 core
 
-in vec4 v_Colour;
+// post processing for the chip-8 screen. this needs to stay in step with Effects::shade in
+// src/postfx.rs, which does the same thing in software for headless output
+
+const float PI = 3.14159265;
+
+uniform sampler2D t_Screen;
+uniform vec2 u_ScreenSize;
+uniform vec2 u_OutputSize;
+uniform float u_Curvature;
+uniform float u_SharpBilinear;
+uniform float u_Bloom;
+uniform float u_Scanlines;
+uniform float u_PixelGrid;
+
+in vec2 v_Uv;
 
 out vec4 Target0;
 
 void main() {
-    Target0 = v_Colour;
-}
\ No newline at end of file
+    vec2 uv = v_Uv;
+
+    // barrel distortion
+    if (u_Curvature > 0.0) {
+        vec2 centred = uv * 2.0 - 1.0;
+        centred *= 1.0 + u_Curvature * dot(centred, centred);
+        uv = (centred + 1.0) / 2.0;
+        if (any(lessThan(uv, vec2(0.0))) || any(greaterThan(uv, vec2(1.0)))) {
+            Target0 = vec4(0.0, 0.0, 0.0, 1.0);
+            return;
+        }
+    }
+
+    // nearest neighbour, or sharp bilinear which only blends across the edges of each pixel
+    vec2 scale = u_OutputSize / u_ScreenSize;
+    vec2 position = uv * u_ScreenSize - 0.5;
+    vec2 cell = floor(position);
+    vec2 offset = position - cell;
+    if (u_SharpBilinear > 0.5) {
+        offset = clamp((offset - 0.5) * scale + 0.5, 0.0, 1.0);
+    } else {
+        offset = floor(offset + 0.5);
+    }
+    vec4 colour = texture(t_Screen, (cell + 0.5 + offset) / u_ScreenSize);
+
+    // bloom
+    if (u_Bloom > 0.0) {
+        vec3 glow = vec3(0.0);
+        for (int dy = -1; dy <= 1; dy++) {
+            for (int dx = -1; dx <= 1; dx++) {
+                float weight = (2.0 - abs(float(dx))) * (2.0 - abs(float(dy))) / 16.0;
+                glow += texture(t_Screen, uv + vec2(dx, dy) / u_ScreenSize).rgb * weight;
+            }
+        }
+        colour.rgb += glow * u_Bloom;
+    }
+
+    // scanlines and pixel grid
+    vec2 inside = fract(uv * u_ScreenSize);
+    float brightness = 1.0;
+    brightness *= 1.0 - u_Scanlines * (1.0 - sin(PI * inside.y));
+    brightness *= 1.0 - u_PixelGrid * (1.0 - sqrt(sin(PI * inside.y) * sin(PI * inside.x)));
+
+    Target0 = vec4(colour.rgb * brightness, colour.a);
+}
@@ -0,0 +1,13 @@
+#version 150 core
+
+// the hud, which has already been drawn in software and only needs blending over the screen
+
+uniform sampler2D t_Overlay;
+
+in vec2 v_Uv;
+
+out vec4 Target0;
+
+void main() {
+    Target0 = texture(t_Overlay, v_Uv);
+}
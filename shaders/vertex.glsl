@@ -1,11 +1,11 @@
 #version 150 core
 
 in vec2 a_Pos;
-in vec3 a_Colour;
+in vec2 a_Uv;
 
-out vec4 v_Colour;
+out vec2 v_Uv;
 
 void main() {
-    v_Colour = vec4(a_Colour, 1.0);
+    v_Uv = a_Uv;
     gl_Position = vec4(a_Pos, 0.0, 1.0);
-}
\ No newline at end of file
+}
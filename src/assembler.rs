@@ -0,0 +1,331 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+// a small assembler for the mnemonics from cowgod's chip-8 technical reference, so programs can
+// be worked on as source and picked up by the watcher without a separate build step. it looks
+// like:
+//
+// ; draws a 5 in the top left
+// start:  LD V0, 5
+//         LD F, V0
+//         DRW V1, V1, 5     ; V1 starts out as 0
+// loop:   JP loop
+// data:   DB #F0, $80, 0x80, %11110000, 0b10000000
+//
+// mnemonics and registers aren't case sensitive, numbers can be decimal, hex (#, $ or 0x) or
+// binary (% or 0b), and labels can be used anywhere an address or byte is expected. I, DT,
+// ST, K, F, B and V0 to VF are reserved, so they can't be used as labels
+
+// files with these extensions get assembled, anything else is loaded as it is
+pub const SOURCE_EXTENSIONS: [&str; 3] = ["asm", "s", "src"];
+
+const PROGRAM_START_ADDRESS: u16 = 0x200;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AssemblyError {
+    pub line: usize, // starting from 1
+    pub message: String
+}
+
+impl fmt::Display for AssemblyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+pub fn is_source(path: &Path) -> bool {
+    return path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| SOURCE_EXTENSIONS.iter().any(|source| extension.eq_ignore_ascii_case(source)));
+}
+
+// the program in the file at path, assembling it first if it's source. any assembler errors
+// come back one per line
+pub fn read_program(path: &Path) -> Result<Vec<u8>, String> {
+    if !is_source(path) {
+        return fs::read(path).map_err(|e| e.to_string());
+    }
+
+    let source = fs::read_to_string(path).map_err(|e| e.to_string())?;
+
+    return assemble(&source).map_err(|errors| {
+        let lines: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+        lines.join("\n")
+    });
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Operand {
+    Register(u8),
+    I,
+    IndirectI, // [I]
+    DelayTimer,
+    SoundTimer,
+    Key,
+    Font,
+    Bcd,
+    Number(u32),
+    Label(String)
+}
+
+// a line that's been parsed but not encoded yet, since labels further on aren't known until
+// the whole file has been read
+struct Statement {
+    line: usize,
+    mnemonic: String,
+    operands: Vec<Operand>
+}
+
+pub fn assemble(source: &str) -> Result<Vec<u8>, Vec<AssemblyError>> {
+    let mut errors = vec![];
+    let mut labels = HashMap::new();
+    let mut statements = vec![];
+    let mut address = PROGRAM_START_ADDRESS as u32;
+
+    // first pass: find out where every label is
+    for (i, text) in source.lines().enumerate() {
+        let line = i + 1;
+        let mut text = text.split(';').next().unwrap_or("").trim();
+
+        if let Some(colon) = text.find(':') {
+            let label = text[..colon].trim();
+            if !is_identifier(label) || parse_operand(label).is_ok_and(|operand| !matches!(operand, Operand::Label(_))) {
+                errors.push(AssemblyError { line, message: format!("invalid label: {}", label) });
+            } else if labels.insert(label.to_ascii_lowercase(), address).is_some() {
+                errors.push(AssemblyError { line, message: format!("{} is defined more than once", label) });
+            }
+            text = text[colon + 1..].trim();
+        }
+
+        if text.is_empty() {
+            continue;
+        }
+
+        let (mnemonic, rest) = match text.find(char::is_whitespace) {
+            Some(space) => (&text[..space], text[space..].trim()),
+            None => (text, ""),
+        };
+        let mnemonic = mnemonic.to_ascii_uppercase();
+
+        let operands: Result<Vec<Operand>, String> = if rest.is_empty() {
+            Ok(vec![])
+        } else {
+            rest.split(',').map(|operand| parse_operand(operand.trim())).collect()
+        };
+
+        let operands = match operands {
+            Ok(operands) => operands,
+            Err(message) => {
+                errors.push(AssemblyError { line, message });
+                continue;
+            },
+        };
+
+        address += match mnemonic.as_str() {
+            "DB" => operands.len() as u32,
+            "DW" => operands.len() as u32 * 2,
+            _ => 2,
+        };
+
+        statements.push(Statement { line, mnemonic, operands });
+    }
+
+    // second pass: now everything can be encoded
+    let mut program = vec![];
+    for statement in statements.iter() {
+        match encode(statement, &labels) {
+            Ok(bytes) => program.extend(bytes),
+            Err(message) => errors.push(AssemblyError { line: statement.line, message }),
+        }
+    }
+
+    if !errors.is_empty() {
+        errors.sort_by_key(|error| error.line);
+        return Err(errors);
+    }
+
+    return Ok(program);
+}
+
+fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+
+    return chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+}
+
+fn parse_operand(text: &str) -> Result<Operand, String> {
+    let upper = text.to_ascii_uppercase();
+
+    let operand = match upper.as_str() {
+        "I" => Operand::I,
+        "[I]" => Operand::IndirectI,
+        "DT" => Operand::DelayTimer,
+        "ST" => Operand::SoundTimer,
+        "K" => Operand::Key,
+        "F" => Operand::Font,
+        "B" => Operand::Bcd,
+        _ if upper.len() == 2 && upper.starts_with('V') => {
+            let register = u8::from_str_radix(&upper[1..], 16).map_err(|_| format!("invalid register: {}", text))?;
+            Operand::Register(register)
+        },
+        _ if is_identifier(text) => Operand::Label(text.to_ascii_lowercase()),
+        _ => Operand::Number(parse_number(&upper).ok_or_else(|| format!("invalid operand: {}", text))?),
+    };
+
+    return Ok(operand);
+}
+
+fn parse_number(text: &str) -> Option<u32> {
+    let (digits, radix) = if let Some(hex) = text.strip_prefix('#').or_else(|| text.strip_prefix('$')).or_else(|| text.strip_prefix("0X")) {
+        (hex, 16)
+    } else if let Some(binary) = text.strip_prefix('%').or_else(|| text.strip_prefix("0B")) {
+        (binary, 2)
+    } else {
+        (text, 10)
+    };
+
+    return u32::from_str_radix(digits, radix).ok();
+}
+
+fn encode(statement: &Statement, labels: &HashMap<String, u32>) -> Result<Vec<u8>, String> {
+    use Operand::*;
+
+    // numbers and labels, checked to fit in however many bits they're going into
+    let value = |operand: &Operand, max: u32| -> Result<u16, String> {
+        let value = match operand {
+            Number(value) => *value,
+            Label(label) => *labels.get(label).ok_or_else(|| format!("unknown label: {}", label))?,
+            _ => return Err(format!("expected a number or label for {}", statement.mnemonic)),
+        };
+
+        if value > max {
+            return Err(format!("{} is too big, it needs to be at most {}", value, max));
+        }
+
+        return Ok(value as u16);
+    };
+
+    let operands = statement.operands.as_slice();
+    let mnemonic = statement.mnemonic.as_str();
+
+    // data directives come out as they are rather than as a single instruction
+    if mnemonic == "DB" || mnemonic == "DW" {
+        if operands.is_empty() {
+            return Err(format!("{} needs at least one value", mnemonic));
+        }
+
+        let mut bytes = vec![];
+        for operand in operands {
+            if mnemonic == "DB" {
+                bytes.push(value(operand, 0xFF)? as u8);
+            } else {
+                bytes.extend(value(operand, 0xFFFF)?.to_be_bytes().iter());
+            }
+        }
+
+        return Ok(bytes);
+    }
+
+    let xy = |x: u8, y: u8| ((x as u16) << 8) | ((y as u16) << 4);
+
+    let opcode = match (mnemonic, operands) {
+        ("CLS", []) => 0x00E0,
+        ("RET", []) => 0x00EE,
+        ("SYS", [address]) => value(address, 0xFFF)?,
+        ("JP", [Register(0), address]) => 0xB000 | value(address, 0xFFF)?,
+        ("JP", [address]) => 0x1000 | value(address, 0xFFF)?,
+        ("CALL", [address]) => 0x2000 | value(address, 0xFFF)?,
+        ("SE", [Register(x), Register(y)]) => 0x5000 | xy(*x, *y),
+        ("SE", [Register(x), byte]) => 0x3000 | xy(*x, 0) | value(byte, 0xFF)?,
+        ("SNE", [Register(x), Register(y)]) => 0x9000 | xy(*x, *y),
+        ("SNE", [Register(x), byte]) => 0x4000 | xy(*x, 0) | value(byte, 0xFF)?,
+        ("LD", [Register(x), Register(y)]) => 0x8000 | xy(*x, *y),
+        ("LD", [Register(x), DelayTimer]) => 0xF007 | xy(*x, 0),
+        ("LD", [Register(x), Key]) => 0xF00A | xy(*x, 0),
+        ("LD", [Register(x), IndirectI]) => 0xF065 | xy(*x, 0),
+        ("LD", [Register(x), byte]) => 0x6000 | xy(*x, 0) | value(byte, 0xFF)?,
+        ("LD", [I, address]) => 0xA000 | value(address, 0xFFF)?,
+        ("LD", [DelayTimer, Register(x)]) => 0xF015 | xy(*x, 0),
+        ("LD", [SoundTimer, Register(x)]) => 0xF018 | xy(*x, 0),
+        ("LD", [Font, Register(x)]) => 0xF029 | xy(*x, 0),
+        ("LD", [Bcd, Register(x)]) => 0xF033 | xy(*x, 0),
+        ("LD", [IndirectI, Register(x)]) => 0xF055 | xy(*x, 0),
+        ("ADD", [I, Register(x)]) => 0xF01E | xy(*x, 0),
+        ("ADD", [Register(x), Register(y)]) => 0x8004 | xy(*x, *y),
+        ("ADD", [Register(x), byte]) => 0x7000 | xy(*x, 0) | value(byte, 0xFF)?,
+        ("OR", [Register(x), Register(y)]) => 0x8001 | xy(*x, *y),
+        ("AND", [Register(x), Register(y)]) => 0x8002 | xy(*x, *y),
+        ("XOR", [Register(x), Register(y)]) => 0x8003 | xy(*x, *y),
+        ("SUB", [Register(x), Register(y)]) => 0x8005 | xy(*x, *y),
+        ("SHR", [Register(x)]) => 0x8006 | xy(*x, 0),
+        ("SHR", [Register(x), Register(y)]) => 0x8006 | xy(*x, *y),
+        ("SUBN", [Register(x), Register(y)]) => 0x8007 | xy(*x, *y),
+        ("SHL", [Register(x)]) => 0x800E | xy(*x, 0),
+        ("SHL", [Register(x), Register(y)]) => 0x800E | xy(*x, *y),
+        ("RND", [Register(x), byte]) => 0xC000 | xy(*x, 0) | value(byte, 0xFF)?,
+        ("DRW", [Register(x), Register(y), n]) => 0xD000 | xy(*x, *y) | value(n, 0xF)?,
+        ("SKP", [Register(x)]) => 0xE09E | xy(*x, 0),
+        ("SKNP", [Register(x)]) => 0xE0A1 | xy(*x, 0),
+        (mnemonic, _) if !MNEMONICS.contains(&mnemonic) => return Err(format!("unknown instruction: {}", mnemonic)),
+        (mnemonic, _) => return Err(format!("invalid operands for {}", mnemonic)),
+    };
+
+    return Ok(opcode.to_be_bytes().to_vec());
+}
+
+// turns an instruction back into source, the opposite of encode. anything that isn't an
+// instruction comes out as a DW so that it still assembles to the same thing
+pub fn disassemble(opcode: u16) -> String {
+    let x = (opcode >> 8) & 0xF;
+    let y = (opcode >> 4) & 0xF;
+    let n = opcode & 0xF;
+    let kk = opcode & 0xFF;
+    let nnn = opcode & 0xFFF;
+
+    return match (opcode >> 12, x, y, n) {
+        (0x0, 0x0, 0xE, 0x0) => "CLS".to_string(),
+        (0x0, 0x0, 0xE, 0xE) => "RET".to_string(),
+        (0x0, _, _, _) => format!("SYS #{:03X}", nnn),
+        (0x1, _, _, _) => format!("JP #{:03X}", nnn),
+        (0x2, _, _, _) => format!("CALL #{:03X}", nnn),
+        (0x3, _, _, _) => format!("SE V{:X}, #{:02X}", x, kk),
+        (0x4, _, _, _) => format!("SNE V{:X}, #{:02X}", x, kk),
+        (0x5, _, _, 0x0) => format!("SE V{:X}, V{:X}", x, y),
+        (0x6, _, _, _) => format!("LD V{:X}, #{:02X}", x, kk),
+        (0x7, _, _, _) => format!("ADD V{:X}, #{:02X}", x, kk),
+        (0x8, _, _, 0x0) => format!("LD V{:X}, V{:X}", x, y),
+        (0x8, _, _, 0x1) => format!("OR V{:X}, V{:X}", x, y),
+        (0x8, _, _, 0x2) => format!("AND V{:X}, V{:X}", x, y),
+        (0x8, _, _, 0x3) => format!("XOR V{:X}, V{:X}", x, y),
+        (0x8, _, _, 0x4) => format!("ADD V{:X}, V{:X}", x, y),
+        (0x8, _, _, 0x5) => format!("SUB V{:X}, V{:X}", x, y),
+        (0x8, _, _, 0x6) => format!("SHR V{:X}, V{:X}", x, y),
+        (0x8, _, _, 0x7) => format!("SUBN V{:X}, V{:X}", x, y),
+        (0x8, _, _, 0xE) => format!("SHL V{:X}, V{:X}", x, y),
+        (0x9, _, _, 0x0) => format!("SNE V{:X}, V{:X}", x, y),
+        (0xA, _, _, _) => format!("LD I, #{:03X}", nnn),
+        (0xB, _, _, _) => format!("JP V0, #{:03X}", nnn),
+        (0xC, _, _, _) => format!("RND V{:X}, #{:02X}", x, kk),
+        (0xD, _, _, _) => format!("DRW V{:X}, V{:X}, {}", x, y, n),
+        (0xE, _, 0x9, 0xE) => format!("SKP V{:X}", x),
+        (0xE, _, 0xA, 0x1) => format!("SKNP V{:X}", x),
+        (0xF, _, 0x0, 0x7) => format!("LD V{:X}, DT", x),
+        (0xF, _, 0x0, 0xA) => format!("LD V{:X}, K", x),
+        (0xF, _, 0x1, 0x5) => format!("LD DT, V{:X}", x),
+        (0xF, _, 0x1, 0x8) => format!("LD ST, V{:X}", x),
+        (0xF, _, 0x1, 0xE) => format!("ADD I, V{:X}", x),
+        (0xF, _, 0x2, 0x9) => format!("LD F, V{:X}", x),
+        (0xF, _, 0x3, 0x3) => format!("LD B, V{:X}", x),
+        (0xF, _, 0x5, 0x5) => format!("LD [I], V{:X}", x),
+        (0xF, _, 0x6, 0x5) => format!("LD V{:X}, [I]", x),
+        _ => format!("DW #{:04X}", opcode),
+    };
+}
+
+const MNEMONICS: [&str; 22] = [
+    "CLS", "RET", "SYS", "JP", "CALL", "SE", "SNE", "LD", "ADD", "OR", "AND", "XOR", "SUB",
+    "SHR", "SUBN", "SHL", "RND", "DRW", "SKP", "SKNP", "DB", "DW"
+];
@@ -0,0 +1,468 @@
+#![allow(clippy::needless_return)]
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use serde::Serialize;
+
+use chip8_rust::config::{Config, Overrides};
+use chip8_rust::core::{Chip8, ExecutionError, Quirks};
+use chip8_rust::romdb::RomDatabase;
+use chip8_rust::screenshot;
+use chip8_rust::script::Script;
+
+// runs a whole library of roms headlessly, several at once, and writes up how each one got on.
+// it's meant for checking changes to the interpreter against every rom there is, e.g. every
+// night, where anything that starts hitting errors or ends up on a different screen stands out
+const USAGE: &str = "usage: batch-run [options] <rom dir>
+
+runs every rom under the directory headlessly and writes a report of how each one got on: any
+errors and unknown instructions it hit, the quirks it ran with and a screenshot of where it
+ended up. roms get their settings the same way as in chip8-rust, from the rom database and the
+config file
+
+options:
+    --out <dir>         where report.json, report.html and the screenshots go, batch-report
+                        by default
+    --frames <n>        how long to run each rom for, 600 frames by default
+    --threads <n>       how many roms to run at once, one per cpu by default
+    --input <path>      keys to press, the same for every rom. a <rom>.input file next to a
+                        rom is used for that rom instead
+    --config <path>     config file to use instead of the default one
+    --profile <name>    quirk profile for every rom, rather than what the database says
+    --scale <n>         size of each chip-8 pixel in the screenshots, 4 by default
+    --seed <n>          seed for RND, 0 by default
+    --blocks            run with block translation, which gives the same results faster
+
+input files have a line for each change to the keys held down: the frame it happens on, then
+the keys held from then on as hex digits or - for none, e.g.
+
+    60 5
+    65 -
+    120 46
+
+exits with 1 if any rom hit an error or couldn't be run, and 2 if the batch couldn't be run";
+
+const DEFAULT_OUT_DIR: &str = "batch-report";
+const DEFAULT_FRAMES: u64 = 600;
+const DEFAULT_SCALE: u32 = 4;
+const INPUT_EXTENSION: &str = "input";
+const MAX_ERRORS: usize = 20; // kept for each rom, past which they're only counted
+
+struct Args {
+    dir: PathBuf,
+    out: PathBuf,
+    frames: u64,
+    threads: usize,
+    input: Option<Script>,
+    config_path: Option<PathBuf>,
+    profile: Option<String>,
+    scale: u32,
+    seed: u64,
+    blocks: bool
+}
+
+#[derive(Serialize)]
+struct Report {
+    frames: u64,
+    seed: u64,
+    blocks: bool,
+    roms: Vec<RomReport>
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Ok,     // ran the whole time without any errors
+    Errors, // hit at least one execution error, but kept going past them like resuming does
+    Failed  // couldn't be run at all, see failure
+}
+
+#[derive(Serialize)]
+struct RomReport {
+    path: String, // relative to the rom dir
+    title: Option<String>,
+    sha1: Option<String>,
+    status: Status,
+    failure: Option<String>,
+    profile: Option<String>, // the quirk profile its quirks match, if they match one
+    quirks: Option<Quirks>,
+    tick_rate: Option<u32>,
+    error_count: usize,
+    errors: Vec<ErrorReport>,
+    unknown_opcodes: Vec<UnknownOpcode>,
+    screenshot: Option<String> // relative to the out dir
+}
+
+#[derive(Serialize)]
+struct ErrorReport {
+    frame: u64,
+    message: String
+}
+
+// each different opcode that wasn't an instruction, where it was first hit and how many times
+#[derive(Serialize)]
+struct UnknownOpcode {
+    opcode: String,
+    pc: String,
+    count: u64
+}
+
+fn main() {
+    let args = match parse_args(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            println!("error: {}\n\n{}", e, USAGE);
+            process::exit(2);
+        },
+    };
+
+    match run(&args) {
+        Ok(true) => (),
+        Ok(false) => process::exit(1),
+        Err(e) => {
+            println!("error: {}", e);
+            process::exit(2);
+        },
+    }
+}
+
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Args, String> {
+    let mut dir = None;
+    let mut parsed = Args {
+        dir: PathBuf::new(),
+        out: PathBuf::from(DEFAULT_OUT_DIR),
+        frames: DEFAULT_FRAMES,
+        threads: thread::available_parallelism().map(|threads| threads.get()).unwrap_or(1),
+        input: None,
+        config_path: None,
+        profile: None,
+        scale: DEFAULT_SCALE,
+        seed: 0,
+        blocks: false
+    };
+
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            if dir.is_some() {
+                return Err("expected just the one rom dir".to_string());
+            }
+            dir = Some(PathBuf::from(arg));
+            continue;
+        }
+
+        if arg == "--blocks" {
+            parsed.blocks = true;
+            continue;
+        }
+
+        let value = args.next().ok_or_else(|| format!("{} needs a value", arg))?;
+        let number = || value.parse::<u64>().map_err(|_| format!("{} expects a number, got {}", arg, value));
+        match arg.as_str() {
+            "--out" => parsed.out = PathBuf::from(&value),
+            "--frames" => parsed.frames = number()?,
+            "--threads" => parsed.threads = number()?.max(1) as usize,
+            "--input" => parsed.input = Some(Script::load(Path::new(&value))?),
+            "--config" => parsed.config_path = Some(PathBuf::from(&value)),
+            "--profile" => {
+                Quirks::from_profile(&value).ok_or_else(|| format!("unknown quirk profile: {}", value))?;
+                parsed.profile = Some(value.clone());
+            },
+            "--scale" => parsed.scale = number()?.max(1) as u32,
+            "--seed" => parsed.seed = number()?,
+            _ => return Err(format!("unknown option: {}", arg)),
+        }
+    }
+
+    parsed.dir = dir.ok_or_else(|| "expected a rom dir".to_string())?;
+
+    return Ok(parsed);
+}
+
+// returns whether every rom ran without any trouble
+fn run(args: &Args) -> Result<bool, String> {
+    let config = match args.config_path.clone().or_else(Config::default_path) {
+        Some(path) => Config::load(&path).map_err(|e| e.to_string())?,
+        None => Config::default(),
+    };
+    let database = match RomDatabase::default_dir() {
+        Some(dir) if dir.exists() => RomDatabase::load(&dir).map_err(|e| format!("couldn't load the rom database: {}", e))?,
+        _ => RomDatabase::embedded(),
+    };
+
+    let screenshot_dir = args.out.join("screenshots");
+    fs::create_dir_all(&screenshot_dir).map_err(|e| format!("couldn't create {}: {}", screenshot_dir.display(), e))?;
+
+    let mut roms = vec![];
+    find_roms(&args.dir, &args.out, &mut roms).map_err(|e| format!("couldn't read {}: {}", args.dir.display(), e))?;
+    roms.sort();
+    if roms.is_empty() {
+        return Err(format!("there aren't any roms in {}", args.dir.display()));
+    }
+
+    // a simple pool: each thread takes the next rom nobody has started on until they're all done
+    let next = AtomicUsize::new(0);
+    let finished = AtomicUsize::new(0);
+    let reports: Mutex<Vec<Option<RomReport>>> = Mutex::new((0..roms.len()).map(|_| None).collect());
+
+    thread::scope(|scope| {
+        for _ in 0..args.threads.min(roms.len()) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::SeqCst);
+                let path = match roms.get(i) {
+                    Some(path) => path,
+                    None => break,
+                };
+
+                let report = run_rom(args, &config, &database, path, i);
+                let done = finished.fetch_add(1, Ordering::SeqCst) + 1;
+                println!("[{}/{}] {}: {}", done, roms.len(), report.path, summary(&report));
+                reports.lock().unwrap()[i] = Some(report);
+            });
+        }
+    });
+
+    let report = Report {
+        frames: args.frames,
+        seed: args.seed,
+        blocks: args.blocks,
+        roms: reports.into_inner().unwrap().into_iter().flatten().collect()
+    };
+
+    let json_path = args.out.join("report.json");
+    let json = serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?;
+    fs::write(&json_path, json).map_err(|e| format!("couldn't write {}: {}", json_path.display(), e))?;
+
+    let html_path = args.out.join("report.html");
+    fs::write(&html_path, html_report(&report)).map_err(|e| format!("couldn't write {}: {}", html_path.display(), e))?;
+
+    let count = |status: Status| report.roms.iter().filter(|rom| rom.status == status).count();
+    println!(
+        "\n{} roms: {} ok, {} with errors, {} failed. report in {}",
+        report.roms.len(), count(Status::Ok), count(Status::Errors), count(Status::Failed), args.out.display()
+    );
+
+    return Ok(report.roms.iter().all(|rom| rom.status == Status::Ok));
+}
+
+// every file under dir, apart from hidden ones, input scripts and whatever's in the out dir
+fn find_roms(dir: &Path, out: &Path, roms: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    let out = out.canonicalize().ok();
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+        let script = path.extension().is_some_and(|extension| extension == INPUT_EXTENSION);
+        if hidden || script || (out.is_some() && path.canonicalize().ok() == out) {
+            continue;
+        }
+
+        if entry.file_type()?.is_dir() {
+            find_roms(&path, out.as_deref().unwrap_or(Path::new("")), roms)?;
+        } else {
+            roms.push(path);
+        }
+    }
+
+    return Ok(());
+}
+
+fn run_rom(args: &Args, config: &Config, database: &RomDatabase, path: &Path, index: usize) -> RomReport {
+    let mut report = RomReport {
+        path: path.strip_prefix(&args.dir).unwrap_or(path).display().to_string(),
+        title: None,
+        sha1: None,
+        status: Status::Ok,
+        failure: None,
+        profile: None,
+        quirks: None,
+        tick_rate: None,
+        error_count: 0,
+        errors: vec![],
+        unknown_opcodes: vec![],
+        screenshot: None
+    };
+
+    let mut machine = Chip8::new();
+    if let Err(e) = machine.load_rom(path) {
+        report.status = Status::Failed;
+        report.failure = Some(format!("couldn't load it: {}", e));
+        return report;
+    }
+
+    // settings are worked out just like they are for a single rom, with the profile given here
+    // standing in for the command line
+    let info = database.lookup(machine.rom_hash());
+    let overrides = Overrides { profile: args.profile.clone(), ..Overrides::default() };
+    let settings = match config.resolve(machine.rom_hash(), info.as_ref().map(|info| &info.overrides), &overrides) {
+        Ok(settings) => settings,
+        Err(e) => {
+            report.status = Status::Failed;
+            report.failure = Some(e);
+            return report;
+        },
+    };
+
+    report.title = info.map(|info| info.title.to_string());
+    report.sha1 = Some(machine.rom_hash().to_string());
+    report.profile = settings.quirks.profile_name().map(|name| name.to_string());
+    report.quirks = Some(settings.quirks);
+    report.tick_rate = Some(settings.tick_rate);
+
+    let script = match path.with_extension(INPUT_EXTENSION) {
+        own if own.exists() => match Script::load(&own) {
+            Ok(script) => Some(script),
+            Err(e) => {
+                report.status = Status::Failed;
+                report.failure = Some(e);
+                return report;
+            },
+        },
+        _ => args.input.clone(),
+    };
+
+    machine.quirks = settings.quirks;
+    machine.tick_rate = settings.tick_rate;
+    machine.translate_blocks = args.blocks;
+    machine.seed_random(args.seed);
+
+    let mut unknown: BTreeMap<u16, (u16, u64)> = BTreeMap::new(); // opcode to where it was first hit, and how often
+    for frame in 0..args.frames {
+        if let Some(script) = &script {
+            let held = script.keys_at(frame);
+            for (key, state) in machine.keypad.iter_mut().enumerate() {
+                *state = (held >> key & 1) as u8;
+            }
+        }
+
+        // like the platform being resumed after each one, it carries on from past the
+        // instruction that went wrong
+        let e = match machine.cycle() {
+            Ok(()) => continue,
+            Err(e) => e,
+        };
+
+        report.status = Status::Errors;
+        report.error_count += 1;
+        if report.errors.len() < MAX_ERRORS {
+            report.errors.push(ErrorReport { frame, message: e.to_string() });
+        }
+        if let ExecutionError::UnknownOpcode { pc, opcode } = e {
+            unknown.entry(opcode).or_insert((pc, 0)).1 += 1;
+        }
+    }
+
+    report.unknown_opcodes = unknown.into_iter()
+        .map(|(opcode, (pc, count))| UnknownOpcode { opcode: format!("{:04X}", opcode), pc: format!("{:03X}", pc), count })
+        .collect();
+
+    // numbered so two roms with the same name in different directories don't clash
+    let name = format!("{:04}-{}.png", index, screenshot::safe_name(&report.path));
+    let (pixels, width, height) = screenshot::render(&settings.palette.colourise(&machine.video), args.scale, None);
+    match screenshot::write_png(&args.out.join("screenshots").join(&name), &pixels, width, height) {
+        Ok(()) => report.screenshot = Some(format!("screenshots/{}", name)),
+        Err(e) => println!("warning: couldn't save the screenshot of {}: {}", report.path, e),
+    }
+
+    return report;
+}
+
+fn summary(report: &RomReport) -> String {
+    match report.status {
+        Status::Ok => "ok".to_string(),
+        Status::Errors => format!("{} errors, the first {}", report.error_count, report.errors[0].message),
+        Status::Failed => format!("failed, {}", report.failure.as_deref().unwrap_or("")),
+    }
+}
+
+fn html_report(report: &Report) -> String {
+    let mut rows = String::new();
+    for rom in report.roms.iter() {
+        let quirks = match &rom.quirks {
+            Some(quirks) => {
+                let enabled = [
+                    ("shift", quirks.shift), ("load_store", quirks.load_store), ("jump", quirks.jump),
+                    ("vf_reset", quirks.vf_reset), ("clip", quirks.clip)
+                ];
+                let names: Vec<&str> = enabled.iter().filter(|(_, on)| *on).map(|(name, _)| *name).collect();
+                format!("{}<br><small>{}</small>", rom.profile.as_deref().unwrap_or("custom"), names.join(", "))
+            },
+            None => String::new(),
+        };
+
+        let mut problems: Vec<String> = rom.failure.iter().cloned().collect();
+        problems.extend(rom.errors.iter().map(|error| format!("frame {}: {}", error.frame, error.message)));
+        if rom.error_count > rom.errors.len() {
+            problems.push(format!("and {} more", rom.error_count - rom.errors.len()));
+        }
+
+        let unknown: Vec<String> = rom.unknown_opcodes.iter()
+            .map(|unknown| format!("{} at {} ({}x)", unknown.opcode, unknown.pc, unknown.count))
+            .collect();
+
+        let screenshot = match &rom.screenshot {
+            Some(path) => format!("<img src=\"{}\" alt=\"\">", escape(path)),
+            None => String::new(),
+        };
+
+        rows.push_str(&format!(
+            "<tr class=\"{status}\"><td>{path}<br><small>{title}</small></td><td>{status}</td><td>{quirks}</td>\
+             <td>{tick_rate}</td><td>{problems}</td><td>{unknown}</td><td>{screenshot}</td></tr>\n",
+            status = match rom.status { Status::Ok => "ok", Status::Errors => "errors", Status::Failed => "failed" },
+            path = escape(&rom.path),
+            title = escape(rom.title.as_deref().unwrap_or("")),
+            quirks = quirks,
+            tick_rate = rom.tick_rate.map(|tick_rate| tick_rate.to_string()).unwrap_or_default(),
+            problems = problems.iter().map(|problem| escape(problem)).collect::<Vec<String>>().join("<br>"),
+            unknown = unknown.join("<br>"),
+            screenshot = screenshot
+        ));
+    }
+
+    let count = |status: Status| report.roms.iter().filter(|rom| rom.status == status).count();
+
+    return format!(
+        "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>chip-8 batch report</title>
+<style>
+body {{ font-family: sans-serif; }}
+table {{ border-collapse: collapse; }}
+td, th {{ border: 1px solid #ccc; padding: 4px 8px; text-align: left; vertical-align: top; }}
+tr.errors {{ background: #fff4d6; }}
+tr.failed {{ background: #ffdcdc; }}
+img {{ image-rendering: pixelated; }}
+</style>
+</head>
+<body>
+<h1>chip-8 batch report</h1>
+<p>{total} roms run for {frames} frames{blocks}, seed {seed}: {ok} ok, {errors} with errors, {failed} failed</p>
+<table>
+<tr><th>rom</th><th>status</th><th>quirks</th><th>tick rate</th><th>errors</th><th>unknown opcodes</th><th>last frame</th></tr>
+{rows}</table>
+</body>
+</html>
+",
+        total = report.roms.len(),
+        frames = report.frames,
+        blocks = if report.blocks { " with block translation" } else { "" },
+        seed = report.seed,
+        ok = count(Status::Ok),
+        errors = count(Status::Errors),
+        failed = count(Status::Failed),
+        rows = rows
+    );
+}
+
+fn escape(text: &str) -> String {
+    return text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;");
+}
@@ -0,0 +1,263 @@
+#![allow(clippy::needless_return)]
+
+use std::collections::VecDeque;
+use std::env;
+use std::path::PathBuf;
+use std::process;
+
+use chip8_rust::config::{self, Overrides, Settings};
+use chip8_rust::core::{Chip8, ExecutionError, MACHINE_SCREEN_WIDTH, MACHINE_SCREEN_HEIGHT};
+use chip8_rust::trace::{self, Entry};
+
+// finds where two runs of a program part ways, e.g. to see exactly which instruction a quirk
+// change breaks a game at. it either compares two trace files written with --trace, or runs
+// the rom itself under two configurations in lockstep, which also catches differences on the
+// screen that a trace can't show
+const USAGE: &str = "usage: trace-diff [options] <a trace> <b trace>
+       trace-diff [options] --rom <rom> --a <config> --b <config>
+
+shows the first instruction where two traces, or two runs of a rom, differ. configs are comma
+separated settings from profile=<name>, tick_rate=<n> and <quirk>=<bool>, e.g.
+--a profile=chip8 --b profile=chip8,shift=true
+
+options:
+    --context <n>   how many instructions leading up to the difference to show, 10 by default
+    --frames <n>    how long to run the rom for, 600 frames by default
+    --seed <n>      seed for RND, the same for both runs, 0 by default";
+
+const DEFAULT_CONTEXT: usize = 10;
+const DEFAULT_FRAMES: u64 = 600;
+
+struct Args {
+    traces: Vec<PathBuf>,
+    rom: Option<PathBuf>,
+    configs: [Option<Overrides>; 2],
+    context: usize,
+    frames: u64,
+    seed: u64
+}
+
+fn main() {
+    let args = match parse_args(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            println!("error: {}\n\n{}", e, USAGE);
+            process::exit(2);
+        },
+    };
+
+    let result = match &args.rom {
+        Some(rom) => run_rom(&args, rom),
+        None => compare_traces(&args),
+    };
+
+    // like diff, 1 means they differ and 2 means something went wrong
+    match result {
+        Ok(true) => (),
+        Ok(false) => process::exit(1),
+        Err(e) => {
+            println!("error: {}", e);
+            process::exit(2);
+        },
+    }
+}
+
+fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Args, String> {
+    let mut parsed = Args {
+        traces: vec![],
+        rom: None,
+        configs: [None, None],
+        context: DEFAULT_CONTEXT,
+        frames: DEFAULT_FRAMES,
+        seed: 0
+    };
+
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") {
+            parsed.traces.push(PathBuf::from(arg));
+            continue;
+        }
+
+        let value = args.next().ok_or_else(|| format!("{} needs a value", arg))?;
+        let number = || value.parse::<u64>().map_err(|_| format!("{} expects a number, got {}", arg, value));
+        match arg.as_str() {
+            "--rom" => parsed.rom = Some(PathBuf::from(&value)),
+            "--a" => parsed.configs[0] = Some(parse_config(&value)?),
+            "--b" => parsed.configs[1] = Some(parse_config(&value)?),
+            "--context" => parsed.context = number()? as usize,
+            "--frames" => parsed.frames = number()?,
+            "--seed" => parsed.seed = number()?,
+            _ => return Err(format!("unknown option: {}", arg)),
+        }
+    }
+
+    match &parsed.rom {
+        Some(_) if !parsed.traces.is_empty() => return Err("give either two traces or a rom, not both".to_string()),
+        Some(_) if parsed.configs.iter().any(|config| config.is_none()) => return Err("--rom needs both --a and --b".to_string()),
+        None if parsed.traces.len() != 2 => return Err("expected two traces to compare".to_string()),
+        _ => (),
+    }
+
+    return Ok(parsed);
+}
+
+fn parse_config(value: &str) -> Result<Overrides, String> {
+    let mut overrides = Overrides::default();
+
+    for setting in value.split(',').filter(|setting| !setting.is_empty()) {
+        let mut parts = setting.splitn(2, '=');
+        let name = parts.next().unwrap_or("");
+        let value = parts.next().ok_or_else(|| format!("expected <name>=<value>, got {}", setting))?;
+
+        match name {
+            "profile" => overrides.profile = Some(value.to_string()),
+            "tick_rate" => overrides.tick_rate = Some(value.parse().map_err(|_| format!("tick_rate expects a number, got {}", value))?),
+            _ => overrides.quirks.set(name, config::parse_bool(name, value)?)?,
+        }
+    }
+
+    return Ok(overrides);
+}
+
+// returns whether they're the same
+fn compare_traces(args: &Args) -> Result<bool, String> {
+    let a = trace::read_trace(&args.traces[0])?;
+    let b = trace::read_trace(&args.traces[1])?;
+
+    let same = a.iter().zip(b.iter()).position(|(a, b)| a != b);
+    let first = match same {
+        Some(i) => i,
+        None if a.len() == b.len() => {
+            println!("no differences in {} instructions", a.len());
+            return Ok(true);
+        },
+        None => a.len().min(b.len()),
+    };
+
+    println!("first difference at entry {}:", first);
+    for (name, entries) in [("a", &a), ("b", &b)].iter() {
+        match entries.get(first) {
+            Some(entry) => println!("{}: {}", name, entry.to_text()),
+            None => println!("{}: (trace ends here)", name),
+        }
+    }
+
+    print_context(&a[first.saturating_sub(args.context)..first]);
+
+    return Ok(false);
+}
+
+// runs both machines an instruction at a time, stopping at the first one that leaves them in
+// different states
+fn run_rom(args: &Args, rom: &PathBuf) -> Result<bool, String> {
+    let mut machines = vec![];
+    for config in args.configs.iter() {
+        let mut settings = Settings::default();
+        settings.apply(config.as_ref().unwrap())?;
+
+        let mut machine = Chip8::new();
+        machine.load_rom(rom).map_err(|e| format!("couldn't load {}: {}", rom.display(), e))?;
+        machine.quirks = settings.quirks;
+        machine.tick_rate = settings.tick_rate;
+        machine.seed_random(args.seed);
+        machines.push(machine);
+    }
+
+    // different tick rates mean a different number of instructions a frame, so the timers
+    // count down at their own pace
+    let tick_rates = [machines[0].tick_rate.max(1) as u64, machines[1].tick_rate.max(1) as u64];
+    let instructions = args.frames * tick_rates[0].max(tick_rates[1]);
+    let mut recent: VecDeque<Entry> = VecDeque::new();
+
+    for cycle in 0..instructions {
+        let (entry_a, result_a) = Entry::step(&mut machines[0], cycle);
+        let (entry_b, result_b) = Entry::step(&mut machines[1], cycle);
+
+        for (machine, tick_rate) in machines.iter_mut().zip(tick_rates.iter()) {
+            if (cycle + 1) % tick_rate == 0 {
+                machine.tick_timers();
+            }
+        }
+
+        let differences = state_differences(&machines[0], &machines[1]);
+        if !differences.is_empty() || result_a != result_b {
+            println!("first difference after {} instructions (frame {}):", cycle, cycle / tick_rates[0]);
+            println!("a: {}", describe(&entry_a, result_a));
+            println!("b: {}", describe(&entry_b, result_b));
+            println!();
+            for difference in differences.iter() {
+                println!("  {}", difference);
+            }
+            print_context(recent.make_contiguous());
+            return Ok(false);
+        }
+
+        if let Err(e) = result_a {
+            println!("no differences in {} instructions, when both stopped: {}", cycle + 1, e);
+            return Ok(true);
+        }
+
+        if recent.len() == args.context {
+            recent.pop_front();
+        }
+        if args.context > 0 {
+            recent.push_back(entry_a);
+        }
+    }
+
+    println!("no differences in {} instructions ({} frames)", instructions, args.frames);
+
+    return Ok(true);
+}
+
+fn describe(entry: &Entry, result: Result<(), ExecutionError>) -> String {
+    match result {
+        Ok(()) => entry.to_text(),
+        Err(e) => format!("{}\t({})", entry.to_text(), e),
+    }
+}
+
+// everything that's different between the two machines, as a line each with a's value first
+fn state_differences(a: &Chip8, b: &Chip8) -> Vec<String> {
+    let mut differences = vec![];
+
+    if a.pc() != b.pc() {
+        differences.push(format!("PC: {:03X} / {:03X}", a.pc(), b.pc()));
+    }
+
+    for (register, (a, b)) in a.registers().iter().zip(b.registers().iter()).enumerate() {
+        if a != b {
+            differences.push(format!("V{:X}: {:02X} / {:02X}", register, a, b));
+        }
+    }
+
+    if a.index() != b.index() {
+        differences.push(format!("I: {:03X} / {:03X}", a.index(), b.index()));
+    }
+
+    if a.stack_depth() != b.stack_depth() {
+        differences.push(format!("SP: {} / {}", a.stack_depth(), b.stack_depth()));
+    }
+
+    let screen = MACHINE_SCREEN_WIDTH * MACHINE_SCREEN_HEIGHT;
+    let pixels: Vec<usize> = (0..screen).filter(|i| a.video[*i] != b.video[*i]).collect();
+    if let Some(first) = pixels.first() {
+        differences.push(format!(
+            "video: {} pixels differ, the first at {}, {}",
+            pixels.len(), first % MACHINE_SCREEN_WIDTH, first / MACHINE_SCREEN_WIDTH
+        ));
+    }
+
+    return differences;
+}
+
+fn print_context(entries: &[Entry]) {
+    if entries.is_empty() {
+        return;
+    }
+
+    println!("\nleading up to it:");
+    for entry in entries {
+        println!("   {}", entry.to_text());
+    }
+}
@@ -0,0 +1,196 @@
+use std::path::PathBuf;
+
+use chip8_rust::colour::Colour;
+use chip8_rust::config::{parse_bool, Overrides};
+use chip8_rust::keymap::Keymap;
+use chip8_rust::palette::Palette;
+use chip8_rust::persistence::Persistence;
+use chip8_rust::postfx::Effects;
+use chip8_rust::recording::RecordingFormat;
+use chip8_rust::trace::{AddressRange, TraceFormat, TraceOptions};
+use chip8_rust::watcher::WatchMode;
+
+pub const USAGE: &str = "usage: chip8-rust [options] [rom]
+
+without a rom, the window opens on the menu to pick one (Esc brings it up later on too).
+roms ending in .asm, .s or .src are assembled first
+
+options:
+    --config <path>         config file to use instead of the default one
+    --frontend <name>       gpu (the default), minifb for a window drawn without the gpu,
+                            terminal for running over ssh and the like, or headless to run
+                            as fast as possible without showing anything
+    --keymap <layout>       keys to use for the chip-8's keypad: qwerty (the default), qwertz,
+                            azerty, colemak, or the 16 keys of the 4x4 block reading across
+                            then down, e.g. 1234QWERASDFZXCV
+    --rom-dir <dir>         where the menu looks for roms
+    --key-timeout <ms>      terminal only: how long a key stays held after it was last seen
+    --hud <bool>            show fps, speed and the machine's state over the game
+    --watch <mode>          reload the rom whenever it changes on disk: off (the default),
+                            reset to start it again, or preserve to keep the registers,
+                            screen and memory outside the program
+    --frames <n>            headless only: how many frames to run for, 600 by default
+    --screenshot <path>     save the last frame as a png on the way out
+    --screenshot-dir <dir>  where screenshots taken with F12 go
+    --screenshot-scale <n>  size of each chip-8 pixel in screenshots, 1 by default
+    --screenshot-effects <bool>
+                            whether screenshots include post processing
+    --record <path>         record the whole session, as a gif if the path ends in .gif or
+                            otherwise into a directory of pngs with a wav of the buzzer
+    --record-format <name>  what F11 records: gif (the default) or frames
+    --record-scale <n>      size of each chip-8 pixel in recordings, 4 by default
+    --trace <path>          log every instruction run, with the registers it changed
+                            (trace-diff compares two of these)
+    --trace-format <name>   text (the default) or binary
+    --trace-range <range>   only trace instructions at these addresses, as comma separated
+                            hex addresses or ranges, e.g. 200-2FF,340
+    --trace-last <n>        only keep the last n instructions, and write them out if the
+                            program hits an error
+    --seed <n>              seed for RND, so runs can be repeated exactly
+    --scale <n>             size of each chip-8 pixel on screen
+    --tick-rate <n>         instructions executed per frame
+    --palette <palette>     theme name or comma separated list of 2, 4 or 16 colours
+                            themes: classic, green, amber, gameboy, high-contrast
+    --fg <colour>           foreground colour, as #RRGGBB, #RRGGBBAA, a css name or r,g,b
+    --bg <colour>           background colour, as #RRGGBB, #RRGGBBAA, a css name or r,g,b
+    --effects <effects>     post processing preset or comma separated list of effects, each
+                            optionally with a strength from 0 to 1, e.g. scanlines=0.3,bloom
+                            presets: none, sharp, scanlines, crt, lcd
+                            effects: curvature, sharp, bloom, scanlines, grid
+    --persistence <mode>    flicker reduction: off, or (merge the last two frames), decay or
+                            decay=<brightness kept each frame, from 0 up to 1>
+    --profile <name>        quirk profile: default, chip8 or schip
+    --quirk <name>=<bool>   set a single quirk: shift, load_store, jump, vf_reset or clip";
+
+pub enum FrontendKind {
+    Gpu,
+    Minifb,
+    Terminal,
+    Headless
+}
+
+const DEFAULT_HEADLESS_FRAMES: u64 = 600;
+
+pub struct Args {
+    pub rom_path: Option<PathBuf>,
+    pub config_path: Option<PathBuf>,
+    pub frontend: FrontendKind,
+    pub frames: u64,
+    pub screenshot_path: Option<PathBuf>,
+    pub record_path: Option<PathBuf>,
+    pub trace_path: Option<PathBuf>,
+    pub trace_options: TraceOptions,
+    pub seed: Option<u64>,
+    pub overrides: Overrides
+}
+
+impl Args {
+    pub fn parse<I: Iterator<Item = String>>(mut args: I) -> Result<Args, String> {
+        let mut rom_path = None;
+        let mut config_path = None;
+        let mut frontend = FrontendKind::Gpu;
+        let mut frames = DEFAULT_HEADLESS_FRAMES;
+        let mut screenshot_path = None;
+        let mut record_path = None;
+        let mut trace_path = None;
+        let mut trace_options = TraceOptions::default();
+        let mut seed = None;
+        let mut overrides = Overrides::default();
+
+        while let Some(arg) = args.next() {
+            if !arg.starts_with("--") {
+                if rom_path.is_some() {
+                    return Err(format!("unexpected argument: {}", arg));
+                }
+                rom_path = Some(PathBuf::from(arg));
+                continue;
+            }
+
+            let value = args.next().ok_or_else(|| format!("{} needs a value", arg))?;
+            match arg.as_str() {
+                "--config" => config_path = Some(PathBuf::from(value)),
+                "--frontend" => frontend = match value.as_str() {
+                    "gpu" => FrontendKind::Gpu,
+                    "minifb" => FrontendKind::Minifb,
+                    "terminal" => FrontendKind::Terminal,
+                    "headless" => FrontendKind::Headless,
+                    _ => return Err(format!("unknown frontend: {}", value)),
+                },
+                "--keymap" => overrides.keymap = Some(Keymap::parse(&value)?),
+                "--rom-dir" => overrides.rom_dir = Some(PathBuf::from(value)),
+                "--key-timeout" => overrides.key_timeout = Some(parse_number(&arg, &value)?.into()),
+                "--hud" => overrides.hud = Some(parse_bool(&arg, &value)?),
+                "--watch" => overrides.watch = Some(WatchMode::parse(&value)?),
+                "--frames" => frames = parse_number(&arg, &value)?.into(),
+                "--screenshot" => screenshot_path = Some(PathBuf::from(value)),
+                "--screenshot-dir" => overrides.screenshot_dir = Some(PathBuf::from(value)),
+                "--screenshot-scale" => overrides.screenshot_scale = Some(parse_number(&arg, &value)?),
+                "--screenshot-effects" => overrides.screenshot_effects = Some(parse_bool(&arg, &value)?),
+                "--record" => record_path = Some(PathBuf::from(value)),
+                "--record-format" => overrides.record_format = Some(RecordingFormat::parse(&value)?),
+                "--record-scale" => overrides.record_scale = Some(parse_number(&arg, &value)?),
+                "--trace" => trace_path = Some(PathBuf::from(value)),
+                "--trace-format" => trace_options.format = TraceFormat::parse(&value)?,
+                "--trace-range" => {
+                    for range in value.split(',') {
+                        trace_options.ranges.push(AddressRange::parse(range)?);
+                    }
+                },
+                "--trace-last" => trace_options.last = Some(parse_number(&arg, &value)? as usize),
+                "--seed" => seed = Some(value.parse().map_err(|_| format!("{} expects a number, got {}", arg, value))?),
+                "--scale" => overrides.scale = Some(parse_number(&arg, &value)?),
+                "--tick-rate" => overrides.tick_rate = Some(parse_number(&arg, &value)?),
+                "--palette" => overrides.palette = Some(Palette::parse(&value)?),
+                "--effects" => overrides.effects = Some(Effects::parse(&value)?),
+                "--persistence" => overrides.persistence = Some(Persistence::parse(&value)?),
+                "--fg" => overrides.foreground = Some(parse_colour(&value)?),
+                "--bg" => overrides.background = Some(parse_colour(&value)?),
+                "--profile" => overrides.profile = Some(value),
+                "--quirk" => {
+                    let mut parts = value.splitn(2, '=');
+                    let name = parts.next().unwrap_or("");
+                    let enabled = parts.next()
+                        .and_then(|enabled| parse_bool(&arg, enabled).ok())
+                        .ok_or_else(|| format!("expected <name>=<bool> for --quirk, got {}", value))?;
+                    overrides.quirks.set(name, enabled)?;
+                },
+                _ => return Err(format!("unknown option: {}", arg)),
+            }
+        }
+
+        return Ok(Args {
+            rom_path,
+            config_path,
+            frontend,
+            frames,
+            screenshot_path,
+            record_path,
+            trace_path,
+            trace_options,
+            seed,
+            overrides
+        });
+    }
+}
+
+fn parse_number(arg: &str, value: &str) -> Result<u32, String> {
+    return value.parse().map_err(|_| format!("{} expects a number, got {}", arg, value));
+}
+
+fn parse_colour(value: &str) -> Result<Colour, String> {
+    if let Some(colour) = Colour::parse(value) {
+        return Ok(colour);
+    }
+
+    let parts: Vec<&str> = value.split(',').map(|part| part.trim()).collect();
+    if parts.len() != 3 {
+        return Err(format!("expected a colour as #RRGGBB, #RRGGBBAA, a css name or r,g,b, got {}", value));
+    }
+
+    let mut rgb = [0u8; 3];
+    for (channel, part) in rgb.iter_mut().zip(parts) {
+        *channel = part.parse().map_err(|_| format!("invalid colour channel: {}", part))?;
+    }
+
+    return Ok(Colour::from(rgb));
+}
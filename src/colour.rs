@@ -1,23 +1,354 @@
+use std::convert::TryFrom;
+use serde::Deserialize;
+
+// colours can be written as either [r, g, b] or a string that Colour::parse understands in
+// config files
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ColourRepr {
+    Rgb([u8; 3]),
+    Text(String)
+}
+
+// an srgb colour, which is what hex codes and css names describe. the gpu target is srgb too,
+// so anything handed to the shaders needs to go through to_linear_array first, as the gpu
+// converts back to srgb when it writes out to the window
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(try_from = "ColourRepr")]
 pub struct Colour {
     red: u8,
     green: u8,
-    blue: u8
+    blue: u8,
+    alpha: u8
 }
 
 impl Colour {
     pub fn new(red: u8, green: u8, blue: u8) -> Self {
+        Colour::with_alpha(red, green, blue, 255)
+    }
+
+    pub fn with_alpha(red: u8, green: u8, blue: u8, alpha: u8) -> Self {
         Colour {
             red,
             green,
-            blue
+            blue,
+            alpha
+        }
+    }
+
+    // accepts #RRGGBB, #RRGGBBAA or a css colour name
+    pub fn parse(text: &str) -> Option<Self> {
+        return Colour::from_hex(text).or_else(|| Colour::from_name(text));
+    }
+
+    // parses #RRGGBB or #RRGGBBAA, with or without the leading #
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        if (hex.len() != 6 && hex.len() != 8) || !hex.is_ascii() {
+            return None;
+        }
+
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        let alpha = if hex.len() == 8 { channel(6)? } else { 255 };
+
+        return Some(Colour::with_alpha(channel(0)?, channel(2)?, channel(4)?, alpha));
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        if name == "transparent" {
+            return Some(Colour::with_alpha(0, 0, 0, 0));
+        }
+
+        let index = CSS_COLOURS.binary_search_by(|(css_name, _)| css_name.cmp(&name.as_str())).ok()?;
+
+        return Some(Colour::from(CSS_COLOURS[index].1));
+    }
+
+    // 0xAARRGGBB
+    pub fn from_argb(argb: u32) -> Self {
+        let [alpha, red, green, blue] = argb.to_be_bytes();
+
+        return Colour::with_alpha(red, green, blue, alpha);
+    }
+
+    pub fn to_argb(self) -> u32 {
+        return u32::from_be_bytes([self.alpha, self.red, self.green, self.blue]);
+    }
+
+    pub fn to_rgba(self) -> [u8; 4] {
+        return [self.red, self.green, self.blue, self.alpha];
+    }
+
+    pub fn to_hex(self) -> String {
+        if self.alpha == 255 {
+            return format!("#{:02X}{:02X}{:02X}", self.red, self.green, self.blue);
         }
+
+        return format!("#{:02X}{:02X}{:02X}{:02X}", self.red, self.green, self.blue, self.alpha);
+    }
+
+    pub fn red(self) -> u8 {
+        return self.red;
+    }
+
+    pub fn green(self) -> u8 {
+        return self.green;
+    }
+
+    pub fn blue(self) -> u8 {
+        return self.blue;
+    }
+
+    pub fn alpha(self) -> u8 {
+        return self.alpha;
     }
 
-    pub fn to_array(&self) -> [f32; 3] {
-        let r = self.red as f32 / 256.0;
-        let g = self.green as f32 / 256.0;
-        let b = self.blue as f32 / 256.0;
+    // normalised srgb channels
+    pub fn to_array(self) -> [f32; 4] {
+        let r = self.red as f32 / 255.0;
+        let g = self.green as f32 / 255.0;
+        let b = self.blue as f32 / 255.0;
+        let a = self.alpha as f32 / 255.0;
 
-        return [r, g, b];
+        return [r, g, b, a];
+    }
+
+    // normalised linear channels, alpha is always linear so it's left alone
+    pub fn to_linear_array(self) -> [f32; 4] {
+        let [r, g, b, a] = self.to_array();
+
+        return [srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a];
+    }
+
+    pub fn from_linear_array([r, g, b, a]: [f32; 4]) -> Self {
+        return Colour::with_alpha(
+            to_u8(linear_to_srgb(r)),
+            to_u8(linear_to_srgb(g)),
+            to_u8(linear_to_srgb(b)),
+            to_u8(a)
+        );
+    }
+
+    // interpolates between self at t = 0 and other at t = 1. this is done in linear space so
+    // fades don't dip in brightness part way through like they do when mixing srgb values
+    pub fn lerp(self, other: Colour, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let from = self.to_linear_array();
+        let to = other.to_linear_array();
+
+        let mut mixed = [0.0; 4];
+        for i in 0..4 {
+            mixed[i] = from[i] + (to[i] - from[i]) * t;
+        }
+
+        return Colour::from_linear_array(mixed);
+    }
+
+    // standard "over" compositing of self on top of background
+    pub fn blend_over(self, background: Colour) -> Self {
+        let front = self.to_linear_array();
+        let back = background.to_linear_array();
+        let alpha = front[3] + back[3] * (1.0 - front[3]);
+        if alpha <= 0.0 {
+            return Colour::with_alpha(0, 0, 0, 0);
+        }
+
+        let mut blended = [0.0; 4];
+        for i in 0..3 {
+            blended[i] = (front[i] * front[3] + back[i] * back[3] * (1.0 - front[3])) / alpha;
+        }
+        blended[3] = alpha;
+
+        return Colour::from_linear_array(blended);
     }
 }
+
+impl From<[u8; 3]> for Colour {
+    fn from([red, green, blue]: [u8; 3]) -> Self {
+        Colour::new(red, green, blue)
+    }
+}
+
+impl TryFrom<ColourRepr> for Colour {
+    type Error = String;
+
+    fn try_from(repr: ColourRepr) -> Result<Self, Self::Error> {
+        match repr {
+            ColourRepr::Rgb(rgb) => Ok(Colour::from(rgb)),
+            ColourRepr::Text(text) => Colour::parse(&text).ok_or_else(|| format!("invalid colour: {}", text)),
+        }
+    }
+}
+
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        return value / 12.92;
+    }
+
+    return ((value + 0.055) / 1.055).powf(2.4);
+}
+
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        return value * 12.92;
+    }
+
+    return 1.055 * value.powf(1.0 / 2.4) - 0.055;
+}
+
+fn to_u8(value: f32) -> u8 {
+    return (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+}
+
+// css colour module level 4 named colours, sorted by name so they can be binary searched
+const CSS_COLOURS: [(&str, [u8; 3]); 148] = [
+    ("aliceblue", [240, 248, 255]),
+    ("antiquewhite", [250, 235, 215]),
+    ("aqua", [0, 255, 255]),
+    ("aquamarine", [127, 255, 212]),
+    ("azure", [240, 255, 255]),
+    ("beige", [245, 245, 220]),
+    ("bisque", [255, 228, 196]),
+    ("black", [0, 0, 0]),
+    ("blanchedalmond", [255, 235, 205]),
+    ("blue", [0, 0, 255]),
+    ("blueviolet", [138, 43, 226]),
+    ("brown", [165, 42, 42]),
+    ("burlywood", [222, 184, 135]),
+    ("cadetblue", [95, 158, 160]),
+    ("chartreuse", [127, 255, 0]),
+    ("chocolate", [210, 105, 30]),
+    ("coral", [255, 127, 80]),
+    ("cornflowerblue", [100, 149, 237]),
+    ("cornsilk", [255, 248, 220]),
+    ("crimson", [220, 20, 60]),
+    ("cyan", [0, 255, 255]),
+    ("darkblue", [0, 0, 139]),
+    ("darkcyan", [0, 139, 139]),
+    ("darkgoldenrod", [184, 134, 11]),
+    ("darkgray", [169, 169, 169]),
+    ("darkgreen", [0, 100, 0]),
+    ("darkgrey", [169, 169, 169]),
+    ("darkkhaki", [189, 183, 107]),
+    ("darkmagenta", [139, 0, 139]),
+    ("darkolivegreen", [85, 107, 47]),
+    ("darkorange", [255, 140, 0]),
+    ("darkorchid", [153, 50, 204]),
+    ("darkred", [139, 0, 0]),
+    ("darksalmon", [233, 150, 122]),
+    ("darkseagreen", [143, 188, 143]),
+    ("darkslateblue", [72, 61, 139]),
+    ("darkslategray", [47, 79, 79]),
+    ("darkslategrey", [47, 79, 79]),
+    ("darkturquoise", [0, 206, 209]),
+    ("darkviolet", [148, 0, 211]),
+    ("deeppink", [255, 20, 147]),
+    ("deepskyblue", [0, 191, 255]),
+    ("dimgray", [105, 105, 105]),
+    ("dimgrey", [105, 105, 105]),
+    ("dodgerblue", [30, 144, 255]),
+    ("firebrick", [178, 34, 34]),
+    ("floralwhite", [255, 250, 240]),
+    ("forestgreen", [34, 139, 34]),
+    ("fuchsia", [255, 0, 255]),
+    ("gainsboro", [220, 220, 220]),
+    ("ghostwhite", [248, 248, 255]),
+    ("gold", [255, 215, 0]),
+    ("goldenrod", [218, 165, 32]),
+    ("gray", [128, 128, 128]),
+    ("green", [0, 128, 0]),
+    ("greenyellow", [173, 255, 47]),
+    ("grey", [128, 128, 128]),
+    ("honeydew", [240, 255, 240]),
+    ("hotpink", [255, 105, 180]),
+    ("indianred", [205, 92, 92]),
+    ("indigo", [75, 0, 130]),
+    ("ivory", [255, 255, 240]),
+    ("khaki", [240, 230, 140]),
+    ("lavender", [230, 230, 250]),
+    ("lavenderblush", [255, 240, 245]),
+    ("lawngreen", [124, 252, 0]),
+    ("lemonchiffon", [255, 250, 205]),
+    ("lightblue", [173, 216, 230]),
+    ("lightcoral", [240, 128, 128]),
+    ("lightcyan", [224, 255, 255]),
+    ("lightgoldenrodyellow", [250, 250, 210]),
+    ("lightgray", [211, 211, 211]),
+    ("lightgreen", [144, 238, 144]),
+    ("lightgrey", [211, 211, 211]),
+    ("lightpink", [255, 182, 193]),
+    ("lightsalmon", [255, 160, 122]),
+    ("lightseagreen", [32, 178, 170]),
+    ("lightskyblue", [135, 206, 250]),
+    ("lightslategray", [119, 136, 153]),
+    ("lightslategrey", [119, 136, 153]),
+    ("lightsteelblue", [176, 196, 222]),
+    ("lightyellow", [255, 255, 224]),
+    ("lime", [0, 255, 0]),
+    ("limegreen", [50, 205, 50]),
+    ("linen", [250, 240, 230]),
+    ("magenta", [255, 0, 255]),
+    ("maroon", [128, 0, 0]),
+    ("mediumaquamarine", [102, 205, 170]),
+    ("mediumblue", [0, 0, 205]),
+    ("mediumorchid", [186, 85, 211]),
+    ("mediumpurple", [147, 112, 219]),
+    ("mediumseagreen", [60, 179, 113]),
+    ("mediumslateblue", [123, 104, 238]),
+    ("mediumspringgreen", [0, 250, 154]),
+    ("mediumturquoise", [72, 209, 204]),
+    ("mediumvioletred", [199, 21, 133]),
+    ("midnightblue", [25, 25, 112]),
+    ("mintcream", [245, 255, 250]),
+    ("mistyrose", [255, 228, 225]),
+    ("moccasin", [255, 228, 181]),
+    ("navajowhite", [255, 222, 173]),
+    ("navy", [0, 0, 128]),
+    ("oldlace", [253, 245, 230]),
+    ("olive", [128, 128, 0]),
+    ("olivedrab", [107, 142, 35]),
+    ("orange", [255, 165, 0]),
+    ("orangered", [255, 69, 0]),
+    ("orchid", [218, 112, 214]),
+    ("palegoldenrod", [238, 232, 170]),
+    ("palegreen", [152, 251, 152]),
+    ("paleturquoise", [175, 238, 238]),
+    ("palevioletred", [219, 112, 147]),
+    ("papayawhip", [255, 239, 213]),
+    ("peachpuff", [255, 218, 185]),
+    ("peru", [205, 133, 63]),
+    ("pink", [255, 192, 203]),
+    ("plum", [221, 160, 221]),
+    ("powderblue", [176, 224, 230]),
+    ("purple", [128, 0, 128]),
+    ("rebeccapurple", [102, 51, 153]),
+    ("red", [255, 0, 0]),
+    ("rosybrown", [188, 143, 143]),
+    ("royalblue", [65, 105, 225]),
+    ("saddlebrown", [139, 69, 19]),
+    ("salmon", [250, 128, 114]),
+    ("sandybrown", [244, 164, 96]),
+    ("seagreen", [46, 139, 87]),
+    ("seashell", [255, 245, 238]),
+    ("sienna", [160, 82, 45]),
+    ("silver", [192, 192, 192]),
+    ("skyblue", [135, 206, 235]),
+    ("slateblue", [106, 90, 205]),
+    ("slategray", [112, 128, 144]),
+    ("slategrey", [112, 128, 144]),
+    ("snow", [255, 250, 250]),
+    ("springgreen", [0, 255, 127]),
+    ("steelblue", [70, 130, 180]),
+    ("tan", [210, 180, 140]),
+    ("teal", [0, 128, 128]),
+    ("thistle", [216, 191, 216]),
+    ("tomato", [255, 99, 71]),
+    ("turquoise", [64, 224, 208]),
+    ("violet", [238, 130, 238]),
+    ("wheat", [245, 222, 179]),
+    ("white", [255, 255, 255]),
+    ("whitesmoke", [245, 245, 245]),
+    ("yellow", [255, 255, 0]),
+    ("yellowgreen", [154, 205, 50])
+];
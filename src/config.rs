@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use serde::Deserialize;
+
+use crate::colour::Colour;
+use crate::core::{Quirks, DEFAULT_TICK_RATE};
+use crate::keymap::Keymap;
+use crate::palette::Palette;
+use crate::persistence::Persistence;
+use crate::postfx::Effects;
+use crate::recording::RecordingFormat;
+use crate::watcher::WatchMode;
+
+// the config file lives at <config dir>/chip8-rust/config.toml and looks something like:
+//
+// [defaults]
+// scale = 12
+// palette = "amber" # or a list of colours, like ["#101010", [255, 176, 0]]
+// foreground = "#FFC040"
+// effects = "scanlines=0.3,bloom"
+// persistence = "decay=0.5"
+// keymap = "azerty"
+// rom_dir = "roms"
+// key_timeout = 150
+// hud = true
+// watch = "reset"
+// screenshot_dir = "screenshots"
+// screenshot_scale = 4
+// screenshot_effects = true
+// record_format = "frames"
+// record_scale = 2
+//
+// [roms.5a2b3c...] # sha-1 of the rom
+// name = "PONG"
+// profile = "chip8"
+// tick_rate = 15
+//
+// [roms.5a2b3c....quirks]
+// clip = false
+
+const CONFIG_DIR_NAME: &str = "chip8-rust";
+const CONFIG_FILE_NAME: &str = "config.toml";
+const DEFAULT_SCALE: u32 = 10;
+const DEFAULT_KEY_TIMEOUT: u64 = 200;
+
+// everything here is optional so that settings can be layered. the order of precedence from
+// lowest to highest is: built in defaults, [defaults] in the config file, the rom database,
+// the [roms.<sha-1>] section for the loaded rom, and finally the command line
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Overrides {
+    pub name: Option<String>, // shown in the window title
+    pub palette: Option<Palette>, // a theme name or a list of colours
+    pub foreground: Option<Colour>,
+    pub background: Option<Colour>,
+    pub effects: Option<Effects>, // a preset name or a list of effects
+    pub persistence: Option<Persistence>,
+    pub scale: Option<u32>,
+    pub tick_rate: Option<u32>,
+    pub keymap: Option<Keymap>, // a layout name or the 16 keys to use, see keymap.rs
+    pub rom_dir: Option<PathBuf>, // where the menu's rom browser starts
+    pub key_timeout: Option<u64>, // terminal only, see frontend/terminal.rs
+    pub hud: Option<bool>, // show the hud from the start, rather than waiting for F1
+    pub watch: Option<WatchMode>, // "off", "reset" or "preserve", for reloading the rom when it changes
+    pub screenshot_dir: Option<PathBuf>,
+    pub screenshot_scale: Option<u32>,
+    pub screenshot_effects: Option<bool>, // include post processing in screenshots
+    pub record_format: Option<RecordingFormat>, // "gif" or "frames", saved to screenshot_dir
+    pub record_scale: Option<u32>,
+    pub profile: Option<String>,
+    pub quirks: QuirkOverrides
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QuirkOverrides {
+    pub shift: Option<bool>,
+    pub load_store: Option<bool>,
+    pub jump: Option<bool>,
+    pub vf_reset: Option<bool>,
+    pub clip: Option<bool>
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub defaults: Overrides,
+    pub roms: HashMap<String, Overrides>
+}
+
+// the final settings the interpreter runs with
+pub struct Settings {
+    pub title: Option<String>,
+    pub palette: Palette,
+    pub effects: Effects,
+    pub persistence: Persistence,
+    pub scale: u32,
+    pub tick_rate: u32,
+    pub keymap: Keymap,
+    pub rom_dir: Option<PathBuf>,
+    pub key_timeout: u64, // in milliseconds
+    pub hud: bool,
+    pub watch: WatchMode,
+    pub screenshot_dir: PathBuf,
+    pub screenshot_scale: u32,
+    pub screenshot_effects: bool,
+    pub record_format: RecordingFormat,
+    pub record_scale: u32,
+    pub quirks: Quirks
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            title: None,
+            palette: Palette::default(),
+            effects: Effects::default(),
+            persistence: Persistence::default(),
+            scale: DEFAULT_SCALE,
+            tick_rate: DEFAULT_TICK_RATE,
+            keymap: Keymap::default(),
+            rom_dir: None,
+            key_timeout: DEFAULT_KEY_TIMEOUT,
+            hud: false,
+            watch: WatchMode::Off,
+            screenshot_dir: PathBuf::from("."),
+            screenshot_scale: 1,
+            screenshot_effects: false,
+            record_format: RecordingFormat::Gif,
+            record_scale: 4,
+            quirks: Quirks::default()
+        }
+    }
+}
+
+impl Settings {
+    pub fn apply(&mut self, overrides: &Overrides) -> Result<(), String> {
+        if let Some(name) = &overrides.name {
+            self.title = Some(name.clone());
+        }
+
+        // the palette goes first so foreground and background can tweak it
+        if let Some(palette) = &overrides.palette {
+            self.palette = palette.clone();
+        }
+
+        if let Some(foreground) = overrides.foreground {
+            self.palette.set_foreground(foreground);
+        }
+
+        if let Some(background) = overrides.background {
+            self.palette.set_background(background);
+        }
+
+        if let Some(effects) = overrides.effects {
+            self.effects = effects;
+        }
+
+        if let Some(persistence) = overrides.persistence {
+            self.persistence = persistence;
+        }
+
+        if let Some(scale) = overrides.scale {
+            if scale == 0 {
+                return Err("scale must be at least 1".to_string());
+            }
+            self.scale = scale;
+        }
+
+        if let Some(tick_rate) = overrides.tick_rate {
+            self.tick_rate = tick_rate;
+        }
+
+        if let Some(keymap) = overrides.keymap {
+            self.keymap = keymap;
+        }
+
+        if let Some(dir) = &overrides.rom_dir {
+            self.rom_dir = Some(dir.clone());
+        }
+
+        if let Some(key_timeout) = overrides.key_timeout {
+            self.key_timeout = key_timeout;
+        }
+
+        if let Some(hud) = overrides.hud {
+            self.hud = hud;
+        }
+
+        if let Some(watch) = overrides.watch {
+            self.watch = watch;
+        }
+
+        if let Some(dir) = &overrides.screenshot_dir {
+            self.screenshot_dir = dir.clone();
+        }
+
+        if let Some(scale) = overrides.screenshot_scale {
+            if scale == 0 {
+                return Err("screenshot scale must be at least 1".to_string());
+            }
+            self.screenshot_scale = scale;
+        }
+
+        if let Some(effects) = overrides.screenshot_effects {
+            self.screenshot_effects = effects;
+        }
+
+        if let Some(format) = overrides.record_format {
+            self.record_format = format;
+        }
+
+        if let Some(scale) = overrides.record_scale {
+            if scale == 0 {
+                return Err("recording scale must be at least 1".to_string());
+            }
+            self.record_scale = scale;
+        }
+
+        // a profile replaces all of the quirks, so it has to go before the individual ones
+        if let Some(profile) = &overrides.profile {
+            self.quirks = Quirks::from_profile(profile)
+                .ok_or_else(|| format!("unknown quirk profile: {}", profile))?;
+        }
+
+        let quirks = &overrides.quirks;
+        let current = &mut self.quirks;
+        current.shift = quirks.shift.unwrap_or(current.shift);
+        current.load_store = quirks.load_store.unwrap_or(current.load_store);
+        current.jump = quirks.jump.unwrap_or(current.jump);
+        current.vf_reset = quirks.vf_reset.unwrap_or(current.vf_reset);
+        current.clip = quirks.clip.unwrap_or(current.clip);
+
+        Ok(())
+    }
+}
+
+impl QuirkOverrides {
+    pub fn set(&mut self, name: &str, value: bool) -> Result<(), String> {
+        let quirk = match name {
+            "shift" => &mut self.shift,
+            "load_store" => &mut self.load_store,
+            "jump" => &mut self.jump,
+            "vf_reset" => &mut self.vf_reset,
+            "clip" => &mut self.clip,
+            _ => return Err(format!("unknown quirk: {}", name)),
+        };
+
+        *quirk = Some(value);
+
+        Ok(())
+    }
+}
+
+impl Config {
+    pub fn default_path() -> Option<PathBuf> {
+        return dirs::config_dir().map(|dir| dir.join(CONFIG_DIR_NAME).join(CONFIG_FILE_NAME));
+    }
+
+    // a missing file is fine and just means nothing has been configured yet
+    pub fn load(path: &PathBuf) -> io::Result<Config> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(e) => return Err(e),
+        };
+
+        return toml::from_str(&contents).map_err(|e| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), e))
+        });
+    }
+
+    // database holds whatever the rom database recommends for this rom. it sits above the
+    // global defaults, since it's specific to the rom, but anything set for the rom by hand wins
+    pub fn resolve(&self, rom_hash: &str, database: Option<&Overrides>, cli: &Overrides) -> Result<Settings, String> {
+        let mut settings = Settings::default();
+        settings.apply(&self.defaults)?;
+
+        if let Some(overrides) = database {
+            settings.apply(overrides)?;
+        }
+
+        // hashes are looked up case insensitively in case they were pasted in from elsewhere
+        let rom = self.roms.iter()
+            .find(|(hash, _)| hash.eq_ignore_ascii_case(rom_hash))
+            .map(|(_, overrides)| overrides);
+        if let Some(overrides) = rom {
+            settings.apply(overrides)?;
+        }
+
+        settings.apply(cli)?;
+
+        return Ok(settings);
+    }
+}
+
+// how true and false are written on the command line. name is the option or setting the value
+// was given for, to say in the error
+pub fn parse_bool(name: &str, value: &str) -> Result<bool, String> {
+    match value {
+        "true" | "on" | "1" => Ok(true),
+        "false" | "off" | "0" => Ok(false),
+        _ => Err(format!("{} expects true or false, got {}", name, value)),
+    }
+}
@@ -1,12 +1,21 @@
-use std::fs;
+use std::fmt;
 use std::io;
-use std::io::Read;
-use rand::Rng;
+use std::path::Path;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+
+use crate::assembler;
+
+mod blocks;
 
 // program consts
 const PROGRAM_START_ADDRESS: usize = 0x200;
 pub const MACHINE_SCREEN_WIDTH: usize = 64;
 pub const MACHINE_SCREEN_HEIGHT: usize = 32;
+pub const PIXEL_ON: u32 = 1; // video holds a bit per plane, and only the first is drawn to
+pub const DEFAULT_TICK_RATE: u32 = 50;
+const MAX_ROM_SIZE: usize = 4096 - PROGRAM_START_ADDRESS;
 const FONT_SET_SIZE: usize = 80;
 const FONT_SET_START_ADDRESS: usize = 0x50;
 const FONT_SET: [u8; FONT_SET_SIZE] = [
@@ -28,6 +37,104 @@ const FONT_SET: [u8; FONT_SET_SIZE] = [
     0xF0, 0x80, 0xF0, 0x80, 0x80  // F
 ];
 
+// every quirk profile, by the name it goes by and then any others it can be given as
+type Profile = (&'static [&'static str], fn() -> Quirks);
+const PROFILES: [Profile; 3] = [
+    (&["default"], Quirks::default),
+    (&["chip8", "chip-8"], Quirks::chip8),
+    (&["schip", "superchip"], Quirks::schip)
+];
+
+// behaviours which differ between the original cosmac vip interpreter and later ones like
+// schip. roms tend to quietly rely on one or the other, so these need to be picked per rom
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Quirks {
+    pub shift: bool,      // 8xy6/8xye shift Vx in place rather than loading Vy first
+    pub load_store: bool, // fx55/fx65 leave I pointing past the last register touched
+    pub jump: bool,       // bnnn jumps to xnn + Vx rather than nnn + V0
+    pub vf_reset: bool,   // 8xy1/8xy2/8xy3 clear VF
+    pub clip: bool        // sprites are clipped at the screen edges rather than wrapped
+}
+
+impl Quirks {
+    pub fn chip8() -> Self {
+        Quirks {
+            shift: false,
+            load_store: true,
+            jump: false,
+            vf_reset: true,
+            clip: true
+        }
+    }
+
+    pub fn schip() -> Self {
+        Quirks {
+            shift: true,
+            load_store: false,
+            jump: true,
+            vf_reset: false,
+            clip: true
+        }
+    }
+
+    pub fn from_profile(name: &str) -> Option<Self> {
+        return PROFILES.iter()
+            .find(|(names, _)| names.contains(&name))
+            .map(|(_, quirks)| quirks());
+    }
+
+    // the name of the profile with exactly these quirks, if there is one
+    pub fn profile_name(self) -> Option<&'static str> {
+        return PROFILES.iter()
+            .find(|(_, quirks)| quirks() == self)
+            .map(|(names, _)| names[0]);
+    }
+}
+
+impl Default for Quirks {
+    // what this interpreter has always done
+    fn default() -> Self {
+        Quirks {
+            shift: true,
+            load_store: false,
+            jump: false,
+            vf_reset: false,
+            clip: true
+        }
+    }
+}
+
+// something the program did that the machine can't carry on from sensibly. pc is the address of
+// the instruction that caused it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExecutionError {
+    UnknownOpcode { pc: u16, opcode: u16 },
+    StackOverflow { pc: u16 },  // a CALL with all 16 levels of the stack in use
+    StackUnderflow { pc: u16 }, // a RET with nothing on the stack
+    MemoryOutOfBounds { pc: u16, address: usize } // the first address it wanted past the end of memory
+}
+
+impl fmt::Display for ExecutionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExecutionError::UnknownOpcode { pc, opcode } => write!(f, "unknown instruction {:04X} at {:03X}", opcode, pc),
+            ExecutionError::StackOverflow { pc } => write!(f, "stack overflow at {:03X}", pc),
+            ExecutionError::StackUnderflow { pc } => write!(f, "return with an empty stack at {:03X}", pc),
+            ExecutionError::MemoryOutOfBounds { pc, address } => write!(f, "access to {:04X}, past the end of memory, at {:03X}", address, pc),
+        }
+    }
+}
+
+// runs an instruction, with the opcode already in Chip8::opcode and pc past it
+type Handler = fn(&mut Chip8) -> Result<(), ExecutionError>;
+
+// an instruction that's been fetched and decoded already, so running it again can skip both
+#[derive(Clone, Copy)]
+struct Decoded {
+    opcode: u16,
+    handler: Handler
+}
+
 pub struct Chip8 {
     registers: [u8; 16],
     memory: [u8; 4096],
@@ -39,7 +146,17 @@ pub struct Chip8 {
     sound_timer: u8,
     pub keypad: [u8; 16],
     pub video: [u32; (MACHINE_SCREEN_WIDTH * MACHINE_SCREEN_HEIGHT) + 1],
-    opcode: u16
+    pub quirks: Quirks,
+    pub tick_rate: u32,
+    pub decode_cache: bool,          // keep instructions decoded once they've been run
+    decoded: Vec<Option<Decoded>>,   // by address, cleared wherever memory is written to
+    pub translate_blocks: bool,      // have cycle run translated blocks rather than a step at a time
+    blocks: blocks::Blocks,
+    opcode: u16,
+    rom_hash: String,
+    rom: Vec<u8>, // the program as it was loaded, for resetting back to
+    rng: StdRng, // for RND, seeded randomly unless it's been asked to be repeatable
+    seed: Option<u64> // what rng was seeded with, if it was, so a reset can start it over
 }
 
 impl Chip8 {
@@ -55,35 +172,160 @@ impl Chip8 {
             sound_timer: 0,
             keypad: [0; 16],
             video: [0; (MACHINE_SCREEN_WIDTH * MACHINE_SCREEN_HEIGHT) + 1],
-            opcode: 0
+            quirks: Quirks::default(),
+            tick_rate: DEFAULT_TICK_RATE,
+            decode_cache: true,
+            decoded: vec![None; 4096],
+            translate_blocks: false,
+            blocks: blocks::Blocks::new(4096),
+            opcode: 0,
+            rom_hash: String::new(),
+            rom: vec![],
+            rng: StdRng::from_entropy(),
+            seed: None
         };
 
         // load fonts into memory
-        for i in 0..FONT_SET_SIZE {
-            machine.memory[FONT_SET_START_ADDRESS + i] = FONT_SET[i];
-        }
+        machine.memory[FONT_SET_START_ADDRESS..FONT_SET_START_ADDRESS + FONT_SET_SIZE]
+            .copy_from_slice(&FONT_SET);
 
         return machine;
     }
 
     // general utilities
-    pub fn load_rom(&mut self, file_name: &str) -> io::Result<()> {
-        // read in file
-        let mut file = fs::File::open(file_name)?;
-        let file_length = file.metadata()?.len();
-        let mut buffer = vec![0u8; file_length as usize];
-        file.read(&mut buffer)?;
+    pub fn load_rom<P: AsRef<Path>>(&mut self, file_name: P) -> io::Result<()> {
+        // read in file, assembling it first if it's source
+        let buffer = assembler::read_program(file_name.as_ref())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        return self.load_program(&buffer);
+    }
+
+    // puts a program into memory without touching anything else, so loading over a running
+    // program swaps it out while keeping the registers, timers, screen and the memory around it.
+    // whatever was left of the previous program past the end of this one is cleared
+    pub fn load_program(&mut self, program: &[u8]) -> io::Result<()> {
+        if program.len() > MAX_ROM_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("rom is {} bytes, but only {} will fit in memory", program.len(), MAX_ROM_SIZE)
+            ));
+        }
 
         // store rom in memory
-        for i in 0..buffer.len() {
-            self.memory[i + PROGRAM_START_ADDRESS] = buffer[i];
+        let end = PROGRAM_START_ADDRESS + program.len().max(self.rom.len());
+        for byte in &mut self.memory[PROGRAM_START_ADDRESS..end] {
+            *byte = 0;
         }
+        self.memory[PROGRAM_START_ADDRESS..PROGRAM_START_ADDRESS + program.len()].copy_from_slice(program);
+        self.rom = program.to_vec();
+        self.memory_written(PROGRAM_START_ADDRESS..end);
+
+        // used to look up per rom settings
+        self.rom_hash = sha1_smol::Sha1::from(program).digest().to_string();
 
         Ok(())
     }
 
-    fn get_random_number() -> u8 {
-        return rand::thread_rng().gen_range(0, 256) as u8
+    // puts the machine back how it was just after the rom was loaded: everything cleared, the
+    // font reloaded and the rom restored over anything the program wrote on top of it. the
+    // quirks and tick rate are settings rather than machine state, so they stay as they are. a
+    // seeded RND starts over from the seed, so a reset run goes the same as a fresh one
+    pub fn reset(&mut self) {
+        self.soft_reset();
+        if let Some(seed) = self.seed {
+            self.rng = StdRng::seed_from_u64(seed);
+        }
+        self.video = [0; (MACHINE_SCREEN_WIDTH * MACHINE_SCREEN_HEIGHT) + 1];
+
+        self.memory = [0; 4096];
+        self.memory[FONT_SET_START_ADDRESS..FONT_SET_START_ADDRESS + FONT_SET_SIZE]
+            .copy_from_slice(&FONT_SET);
+        self.memory[PROGRAM_START_ADDRESS..PROGRAM_START_ADDRESS + self.rom.len()].copy_from_slice(&self.rom);
+        self.memory_written(0..self.memory.len());
+    }
+
+    // starts the program again from the top without touching memory or the screen, so anything
+    // it's saved (high scores, say) is still there
+    pub fn soft_reset(&mut self) {
+        self.registers = [0; 16];
+        self.index = 0;
+        self.pc = PROGRAM_START_ADDRESS as u16;
+        self.stack = [0; 16];
+        self.sp = 0;
+        self.delay_timer = 0;
+        self.sound_timer = 0;
+        self.opcode = 0;
+    }
+
+    // sha-1 of the loaded rom as lowercase hex, or empty if nothing has been loaded
+    pub fn rom_hash(&self) -> &str {
+        return &self.rom_hash;
+    }
+
+    // read only views of the machine state, for status displays and debugging
+    pub fn registers(&self) -> &[u8; 16] {
+        return &self.registers;
+    }
+
+    pub fn index(&self) -> u16 {
+        return self.index;
+    }
+
+    pub fn pc(&self) -> u16 {
+        return self.pc;
+    }
+
+    pub fn stack_depth(&self) -> u8 {
+        return self.sp;
+    }
+
+    pub fn memory(&self) -> &[u8; 4096] {
+        return &self.memory;
+    }
+
+    pub fn delay_timer(&self) -> u8 {
+        return self.delay_timer;
+    }
+
+    pub fn sound_timer(&self) -> u8 {
+        return self.sound_timer;
+    }
+
+    // makes RND give the same numbers every run, so two machines can be compared
+    pub fn seed_random(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+        self.seed = Some(seed);
+    }
+
+    // where the instruction being run was fetched from, as pc has already moved past it. it wraps
+    // rather than panicking when execute is called straight away with pc below 2
+    fn instruction_pc(&self) -> u16 {
+        return self.pc.wrapping_sub(2);
+    }
+
+    // the range of memory an instruction wants to touch, or an error if it runs off the end
+    fn memory_range(&self, start: u16, length: usize) -> Result<std::ops::Range<usize>, ExecutionError> {
+        let range = start as usize..start as usize + length;
+        if range.end > self.memory.len() {
+            return Err(ExecutionError::MemoryOutOfBounds { pc: self.instruction_pc(), address: range.start.max(self.memory.len()) });
+        }
+
+        return Ok(range);
+    }
+
+    // forgets the decoded instructions and blocks that overlap the range, including an
+    // instruction starting the byte before it
+    fn memory_written(&mut self, range: std::ops::Range<usize>) {
+        for decoded in &mut self.decoded[range.start.saturating_sub(1)..range.end] {
+            *decoded = None;
+        }
+
+        self.blocks.invalidate(range);
+    }
+
+    fn get_random_number(&mut self) -> u8 {
+        return self.rng.gen_range(0, 256) as u8
     }
 
     fn get_addr(&self) -> u16 {
@@ -118,28 +360,58 @@ impl Chip8 {
         return (x, y, n)
     }
 
-    fn get_nibbles(&self) -> (u8, u8, u8, u8) {
-        let n1 = ((self.opcode & 0xF000) >> 12) as u8;
-        let n2 = ((self.opcode & 0x0F00) >> 8) as u8;
-        let n3 = ((self.opcode & 0x00F0) >> 4) as u8;
-        let n4 = (self.opcode & 0x000F) as u8;
+    // runs a frame's worth of instructions and then counts the timers down. on an error the
+    // rest of the frame is skipped, with pc already past the instruction that caused it
+    pub fn cycle(&mut self) -> Result<(), ExecutionError> {
+        if self.translate_blocks {
+            self.run_blocks(self.tick_rate as usize)?;
+        } else {
+            for _ in 0..self.tick_rate {
+                self.step()?;
+            }
+        }
 
-        return (n1, n2, n3, n4)
-    }
+        self.tick_timers();
 
-    pub fn cycle(&mut self) {
-        for _ in 0..50 {
-            // load next instruction from memory
-            let first_byte = (self.memory[self.pc as usize] as u16) << 8;
-            let second_byte = self.memory[(self.pc + 1) as usize] as u16;
-            self.opcode = first_byte | second_byte;
+        Ok(())
+    }
 
-            // instruction execution time
-            self.pc += 2;
-            self.execute_instruction();
+    // runs a single instruction, without touching the timers
+    pub fn step(&mut self) -> Result<(), ExecutionError> {
+        // load next instruction from memory, if there's a whole one there
+        if self.pc as usize + 1 >= self.memory.len() {
+            return Err(ExecutionError::MemoryOutOfBounds { pc: self.pc, address: self.pc as usize + 1 });
         }
+        let decoded = match self.decoded[self.pc as usize] {
+            Some(decoded) if self.decode_cache => decoded,
+            _ => {
+                let first_byte = (self.memory[self.pc as usize] as u16) << 8;
+                let second_byte = self.memory[(self.pc + 1) as usize] as u16;
+                let opcode = first_byte | second_byte;
+
+                let decoded = Decoded { opcode, handler: decode(opcode) };
+                if self.decode_cache {
+                    self.decoded[self.pc as usize] = Some(decoded);
+                }
+                decoded
+            },
+        };
 
-        // decrement the timers
+        // instruction execution time
+        self.opcode = decoded.opcode;
+        self.pc += 2;
+        return (decoded.handler)(self);
+    }
+
+    // runs an opcode as if it had just been fetched from pc, which is already past it. it's for
+    // timing the decode and the instructions without the fetch
+    pub fn execute(&mut self, opcode: u16) -> Result<(), ExecutionError> {
+        self.opcode = opcode;
+        return self.execute_instruction();
+    }
+
+    // counts the timers down by one, which happens 60 times a second
+    pub fn tick_timers(&mut self) {
         if self.delay_timer > 0 {
             self.delay_timer -= 1;
         }
@@ -149,300 +421,405 @@ impl Chip8 {
         }
     }
 
-    fn execute_instruction(&mut self) {
-        let (n1, n2, n3, n4) = self.get_nibbles();
-        match (n1, n2, n3, n4) {
-            (0x0, 0x0, 0xE, 0x0) => self.op_00e0(),
-            (0x0, 0x0, 0xE, 0xE) => self.op_00ee(),
-            (0x1,   _,   _,   _) => self.op_1nnn(),
-            (0x2,   _,   _,   _) => self.op_2nnn(),
-            (0x3,   _,   _,   _) => self.op_3xkk(),
-            (0x4,   _,   _,   _) => self.op_4xkk(),
-            (0x5,   _,   _, 0x0) => self.op_5xy0(),
-            (0x6,   _,   _,   _) => self.op_6xkk(),
-            (0x7,   _,   _,   _) => self.op_7xkk(),
-            (0x8,   _,   _, 0x0) => self.op_8xy0(),
-            (0x8,   _,   _, 0x1) => self.op_8xy1(),
-            (0x8,   _,   _, 0x2) => self.op_8xy2(),
-            (0x8,   _,   _, 0x3) => self.op_8xy3(),
-            (0x8,   _,   _, 0x4) => self.op_8xy4(),
-            (0x8,   _,   _, 0x5) => self.op_8xy5(),
-            (0x8,   _,   _, 0x6) => self.op_8xy6(),
-            (0x8,   _,   _, 0x7) => self.op_8xy7(),
-            (0x8,   _,   _, 0xE) => self.op_8xye(),
-            (0x9,   _,   _, 0x0) => self.op_9xy0(),
-            (0xA,   _,   _,   _) => self.op_annn(),
-            (0xB,   _,   _,   _) => self.op_bnnn(),
-            (0xC,   _,   _,   _) => self.op_cxkk(),
-            (0xD,   _,   _,   _) => self.op_dxyn(),
-            (0xE,   _, 0x9, 0xE) => self.op_ex9e(),
-            (0xE,   _, 0xA, 0x1) => self.op_exa1(),
-            (0xF,   _, 0x0, 0x7) => self.op_fx07(),
-            (0xF,   _, 0x0, 0xA) => self.op_fx0a(),
-            (0xF,   _, 0x1, 0x5) => self.op_fx15(),
-            (0xF,   _, 0x1, 0x8) => self.op_fx18(),
-            (0xF,   _, 0x1, 0xE) => self.op_fx1e(),
-            (0xF,   _, 0x2, 0x9) => self.op_fx29(),
-            (0xF,   _, 0x3, 0x3) => self.op_fx33(),
-            (0xF,   _, 0x5, 0x5) => self.op_fx55(),
-            (0xF,   _, 0x6, 0x5) => self.op_fx65(),
-            (  _,   _,   _,   _) => println!("instruction not recognised: {}", self.opcode),
-        }
+    fn execute_instruction(&mut self) -> Result<(), ExecutionError> {
+        return decode(self.opcode)(self);
+    }
+}
+
+// which op_ function runs an opcode
+fn decode(opcode: u16) -> Handler {
+    let n1 = ((opcode & 0xF000) >> 12) as u8;
+    let n2 = ((opcode & 0x0F00) >> 8) as u8;
+    let n3 = ((opcode & 0x00F0) >> 4) as u8;
+    let n4 = (opcode & 0x000F) as u8;
+
+    return match (n1, n2, n3, n4) {
+        (0x0, 0x0, 0xE, 0x0) => Chip8::op_00e0,
+        (0x0, 0x0, 0xE, 0xE) => Chip8::op_00ee,
+        (0x1,   _,   _,   _) => Chip8::op_1nnn,
+        (0x2,   _,   _,   _) => Chip8::op_2nnn,
+        (0x3,   _,   _,   _) => Chip8::op_3xkk,
+        (0x4,   _,   _,   _) => Chip8::op_4xkk,
+        (0x5,   _,   _, 0x0) => Chip8::op_5xy0,
+        (0x6,   _,   _,   _) => Chip8::op_6xkk,
+        (0x7,   _,   _,   _) => Chip8::op_7xkk,
+        (0x8,   _,   _, 0x0) => Chip8::op_8xy0,
+        (0x8,   _,   _, 0x1) => Chip8::op_8xy1,
+        (0x8,   _,   _, 0x2) => Chip8::op_8xy2,
+        (0x8,   _,   _, 0x3) => Chip8::op_8xy3,
+        (0x8,   _,   _, 0x4) => Chip8::op_8xy4,
+        (0x8,   _,   _, 0x5) => Chip8::op_8xy5,
+        (0x8,   _,   _, 0x6) => Chip8::op_8xy6,
+        (0x8,   _,   _, 0x7) => Chip8::op_8xy7,
+        (0x8,   _,   _, 0xE) => Chip8::op_8xye,
+        (0x9,   _,   _, 0x0) => Chip8::op_9xy0,
+        (0xA,   _,   _,   _) => Chip8::op_annn,
+        (0xB,   _,   _,   _) => Chip8::op_bnnn,
+        (0xC,   _,   _,   _) => Chip8::op_cxkk,
+        (0xD,   _,   _,   _) => Chip8::op_dxyn,
+        (0xE,   _, 0x9, 0xE) => Chip8::op_ex9e,
+        (0xE,   _, 0xA, 0x1) => Chip8::op_exa1,
+        (0xF,   _, 0x0, 0x7) => Chip8::op_fx07,
+        (0xF,   _, 0x0, 0xA) => Chip8::op_fx0a,
+        (0xF,   _, 0x1, 0x5) => Chip8::op_fx15,
+        (0xF,   _, 0x1, 0x8) => Chip8::op_fx18,
+        (0xF,   _, 0x1, 0xE) => Chip8::op_fx1e,
+        (0xF,   _, 0x2, 0x9) => Chip8::op_fx29,
+        (0xF,   _, 0x3, 0x3) => Chip8::op_fx33,
+        (0xF,   _, 0x5, 0x5) => Chip8::op_fx55,
+        (0xF,   _, 0x6, 0x5) => Chip8::op_fx65,
+        (  _,   _,   _,   _) => Chip8::op_unknown,
+    };
+}
+
+impl Default for Chip8 {
+    fn default() -> Self {
+        Chip8::new()
     }
 }
 
 // opcode implementation
 impl Chip8 {
+    // anything that isn't one of the instructions below
+    fn op_unknown(&mut self) -> Result<(), ExecutionError> {
+        return Err(ExecutionError::UnknownOpcode { pc: self.instruction_pc(), opcode: self.opcode });
+    }
+
     // CLS
     // clear screen
-    fn op_00e0(&mut self) {
+    fn op_00e0(&mut self) -> Result<(), ExecutionError> {
         self.video = [0; (MACHINE_SCREEN_WIDTH * MACHINE_SCREEN_HEIGHT) + 1];
+
+        Ok(())
     }
 
     // RET
     // pop address off stack and return to it
-    fn op_00ee(&mut self) {
+    fn op_00ee(&mut self) -> Result<(), ExecutionError> {
+        if self.sp == 0 {
+            return Err(ExecutionError::StackUnderflow { pc: self.instruction_pc() });
+        }
+
         self.sp -= 1;
         self.pc = self.stack[self.sp as usize];
+
+        Ok(())
     }
 
     // JP addr
     // jump to addr
-    fn op_1nnn(&mut self) {
+    fn op_1nnn(&mut self) -> Result<(), ExecutionError> {
         self.pc = self.get_addr();
+
+        Ok(())
     }
 
     // CALL addr
     // store next pc on stack and jump to addr
-    fn op_2nnn(&mut self) {
+    fn op_2nnn(&mut self) -> Result<(), ExecutionError> {
+        if self.sp as usize == self.stack.len() {
+            return Err(ExecutionError::StackOverflow { pc: self.instruction_pc() });
+        }
+
         self.stack[self.sp as usize] = self.pc;
         self.sp += 1;
         self.pc = self.get_addr();
+
+        Ok(())
     }
 
     // SE Vx, kk
     // skip next instruction if Vx == kk
-    fn op_3xkk(&mut self) {
+    fn op_3xkk(&mut self) -> Result<(), ExecutionError> {
         let (vx, kk) = self.get_x_kk();
         if self.registers[vx as usize] == kk {
             self.pc += 2;
         }
+
+        Ok(())
     }
 
     // SNE Vx, kk
     // skip next instruction if Vx != kk
-    fn op_4xkk(&mut self) {
+    fn op_4xkk(&mut self) -> Result<(), ExecutionError> {
         let (vx, kk) = self.get_x_kk();
         if self.registers[vx as usize] != kk {
             self.pc += 2;
         }
+
+        Ok(())
     }
 
     // SE Vx, Vy
     // skip next instruction if Vx == Vy
-    fn op_5xy0(&mut self) {
+    fn op_5xy0(&mut self) -> Result<(), ExecutionError> {
         let (vx, vy) = self.get_x_y();
         if self.registers[vx as usize] == self.registers[vy as usize] {
             self.pc += 2;
         }
+
+        Ok(())
     }
 
     // LD Vx, kk
     // load kk into Vx
-    fn op_6xkk(&mut self) {
+    fn op_6xkk(&mut self) -> Result<(), ExecutionError> {
         let (vx, kk) = self.get_x_kk();
         self.registers[vx as usize] = kk;
+
+        Ok(())
     }
 
     // ADD Vx, kk
     // add kk to Vx
-    fn op_7xkk(&mut self) {
+    fn op_7xkk(&mut self) -> Result<(), ExecutionError> {
         let (vx, kk) = self.get_x_kk();
         self.registers[vx as usize] = (self.registers[vx as usize] as u16 + kk as u16) as u8;
+
+        Ok(())
     }
 
     // LD Vx, Vy
     // load Vy into Vx
-    fn op_8xy0(&mut self) {
+    fn op_8xy0(&mut self) -> Result<(), ExecutionError> {
         let (vx, vy) = self.get_x_y();
         self.registers[vx as usize] = self.registers[vy as usize];
+
+        Ok(())
     }
 
     // OR Vx, Vy
     // Vx = Vx OR Vy
-    fn op_8xy1(&mut self) {
+    fn op_8xy1(&mut self) -> Result<(), ExecutionError> {
         let (vx, vy) = self.get_x_y();
         self.registers[vx as usize] |= self.registers[vy as usize];
+        if self.quirks.vf_reset {
+            self.registers[0xF] = 0;
+        }
+
+        Ok(())
     }
 
     // AND Vx, Vy
     // Vx = Vx AND Vy
-    fn op_8xy2(&mut self) {
+    fn op_8xy2(&mut self) -> Result<(), ExecutionError> {
         let (vx, vy) = self.get_x_y();
         self.registers[vx as usize] &= self.registers[vy as usize];
+        if self.quirks.vf_reset {
+            self.registers[0xF] = 0;
+        }
+
+        Ok(())
     }
 
     // XOR Vx, Vy
     // Vx = Vx XOR Vy
-    fn op_8xy3(&mut self) {
+    fn op_8xy3(&mut self) -> Result<(), ExecutionError> {
         let (vx, vy) = self.get_x_y();
         self.registers[vx as usize] ^= self.registers[vy as usize];
+        if self.quirks.vf_reset {
+            self.registers[0xF] = 0;
+        }
+
+        Ok(())
     }
 
+    // the arithmetic below always sets VF after the result, so that when Vx is VF it ends up
+    // holding the flag rather than the result
+
     // ADD Vx, Vy
     // add Vy to Vx, set VF to carry
-    fn op_8xy4(&mut self) {
+    fn op_8xy4(&mut self) -> Result<(), ExecutionError> {
         let (vx, vy) = self.get_x_y();
-        let result = self.registers[vx as usize] as u16 + self.registers[vy as usize] as u16;
+        let (result, carry) = self.registers[vx as usize].overflowing_add(self.registers[vy as usize]);
 
-        if result > 0xFF { // if there is overflow...
-            self.registers[0xF] = 1; // ...set carry bit to 1...
-        } else {
-            self.registers[0xF] = 0; // ...else set to 0
-        }
+        self.registers[vx as usize] = result;
+        self.registers[0xF] = carry as u8;
 
-        self.registers[vx as usize] = (result & 0xFF) as u8;
+        Ok(())
     }
 
     // SUB Vx, Vy
     // subtract Vy from Vx, set VF to NOT borrow
-    fn op_8xy5(&mut self) {
+    fn op_8xy5(&mut self) -> Result<(), ExecutionError> {
         let (vx, vy) = self.get_x_y();
+        let (result, borrow) = self.registers[vx as usize].overflowing_sub(self.registers[vy as usize]);
 
-        if self.registers[vx as usize] > self.registers[vy as usize] { // if there is no borrowing...
-            self.registers[0xF] = 1; // ...set VF to 1...
-        } else {
-            self.registers[0xF] = 0; // ...else set to 0
-        }
+        self.registers[vx as usize] = result;
+        self.registers[0xF] = !borrow as u8;
 
-        // TODO: clean
-        //self.registers[vx as usize] -= self.registers[vy as usize];
-        self.registers[vx as usize] = self.registers[vx as usize].wrapping_sub(self.registers[vy as usize]);
+        Ok(())
     }
 
-    // SHR Vx
+    // SHR Vx {, Vy}
     // shift Vx right one bit. store overflow in VF
-    fn op_8xy6(&mut self) {
-        let vx = self.get_x();
-        self.registers[0xF] = self.registers[vx as usize] & 0b00000001;
-        self.registers[vx as usize] >>= 1;
+    fn op_8xy6(&mut self) -> Result<(), ExecutionError> {
+        let (vx, vy) = self.get_x_y();
+        let value = if self.quirks.shift { self.registers[vx as usize] } else { self.registers[vy as usize] };
+
+        self.registers[vx as usize] = value >> 1;
+        self.registers[0xF] = value & 0b00000001;
+
+        Ok(())
     }
 
     // SUBN Vx, Vy
-    // subtract Vy from Vx, set VF to NOT borrow
-    fn op_8xy7(&mut self) {
+    // set Vx to Vy - Vx, set VF to NOT borrow
+    fn op_8xy7(&mut self) -> Result<(), ExecutionError> {
         let (vx, vy) = self.get_x_y();
+        let (result, borrow) = self.registers[vy as usize].overflowing_sub(self.registers[vx as usize]);
 
-        if self.registers[vy as usize] > self.registers[vx as usize] { // if there is no borrowing...
-            self.registers[0xF] = 1; // ...set VF to 1...
-        } else {
-            self.registers[0xF] = 0; // ...else set to 0
-        }
+        self.registers[vx as usize] = result;
+        self.registers[0xF] = !borrow as u8;
 
-        // TODO: clean
-        //self.registers[vx as usize] = self.registers[vy as usize] - self.registers[vx as usize];
-        self.registers[vx as usize] = self.registers[vy as usize].wrapping_sub(self.registers[vx as usize]);
+        Ok(())
     }
 
     // SHL Vx {, Vy}
     // shift Vx left one bit. store overflow in VF
-    fn op_8xye(&mut self) {
-        let vx = self.get_x();
-        self.registers[0xF] = (self.registers[vx as usize] & 0b10000000) >> 7;
-        self.registers[vx as usize] <<= 1;
+    fn op_8xye(&mut self) -> Result<(), ExecutionError> {
+        let (vx, vy) = self.get_x_y();
+        let value = if self.quirks.shift { self.registers[vx as usize] } else { self.registers[vy as usize] };
+
+        self.registers[vx as usize] = value << 1;
+        self.registers[0xF] = (value & 0b10000000) >> 7;
+
+        Ok(())
     }
 
     // SNE Vx, Vy
     // skip next instruction if Vx != Vy
-    fn op_9xy0(&mut self) {
+    fn op_9xy0(&mut self) -> Result<(), ExecutionError> {
         let (vx, vy) = self.get_x_y();
         if self.registers[vx as usize] != self.registers[vy as usize] {
             self.pc += 2;
         }
+
+        Ok(())
     }
 
     // LD I, addr
     // load addr into I
-    fn op_annn(&mut self) {
+    fn op_annn(&mut self) -> Result<(), ExecutionError> {
         self.index = self.get_addr();
+
+        Ok(())
     }
 
     // JP V0, addr
-    // jump to addr + V0
-    fn op_bnnn(&mut self) {
-        self.pc = self.get_addr() + self.registers[0x0] as u16;
+    // jump to addr + V0, or addr + Vx with the jump quirk
+    fn op_bnnn(&mut self) -> Result<(), ExecutionError> {
+        let offset = if self.quirks.jump {
+            self.registers[self.get_x() as usize]
+        } else {
+            self.registers[0x0]
+        };
+
+        self.pc = self.get_addr() + offset as u16;
+
+        Ok(())
     }
 
     // RND Vx, kk
     // set Vx to random byte AND kk
-    fn op_cxkk(&mut self) {
+    fn op_cxkk(&mut self) -> Result<(), ExecutionError> {
         let (vx, kk) = self.get_x_kk();
-        self.registers[vx as usize] = Chip8::get_random_number() & kk;
+        self.registers[vx as usize] = self.get_random_number() & kk;
+
+        Ok(())
     }
 
     // DRW Vx, Vy, n
     // get n bytes from memory starting at address I and display as sprite at Vx, Vy. sprite is
     // XOR'd onto screen, and if it causes any pixels to be set to 0 then VF is set to 1, otherwise
     // VF is set to 0
-    fn op_dxyn(&mut self) {
+    fn op_dxyn(&mut self) -> Result<(), ExecutionError> {
         let (vx, vy, n) = self.get_x_y_n();
 
         // wrap around screen
         let x = self.registers[vx as usize] % MACHINE_SCREEN_WIDTH as u8;
         let y = self.registers[vy as usize] % MACHINE_SCREEN_HEIGHT as u8;
 
+        // the whole sprite has to be in memory, even the rows that end up clipped
+        let sprite = self.memory_range(self.index, n as usize)?;
+
         // set VF to 0
         self.registers[0xF] = 0;
 
         // actually draw the sprite
         for row in 0..n {
-            let sprite_byte = self.memory[(self.index + row as u16) as usize];
+            let mut py = (y + row) as usize;
+            if py >= MACHINE_SCREEN_HEIGHT {
+                if self.quirks.clip {
+                    break;
+                }
+                py %= MACHINE_SCREEN_HEIGHT;
+            }
+
+            let sprite_byte = self.memory[sprite.start + row as usize];
             for col in 0..8 {
+                let mut px = (x + col) as usize;
+                if px >= MACHINE_SCREEN_WIDTH {
+                    if self.quirks.clip {
+                        break;
+                    }
+                    px %= MACHINE_SCREEN_WIDTH;
+                }
+
                 let sprite_pixel = sprite_byte & (0b10000000 >> col);
-                let screen_pixel = &mut self.video[(y + row) as usize * MACHINE_SCREEN_WIDTH + (x + col) as usize];
+                let screen_pixel = &mut self.video[py * MACHINE_SCREEN_WIDTH + px];
                 if sprite_pixel > 0 {
                     // collision detection
-                    if *screen_pixel == 0xFFFFFFFF {
+                    if *screen_pixel & PIXEL_ON != 0 {
                         self.registers[0xF] = 1;
                     }
 
                     // XOR the pixels
-                    *screen_pixel ^= 0xFFFFFFFF;
+                    *screen_pixel ^= PIXEL_ON;
                 }
             }
         }
+
+        Ok(())
     }
 
     // SKP Vx
     // skip next instruction if key code stored in Vx is pressed
-    fn op_ex9e(&mut self) {
+    fn op_ex9e(&mut self) -> Result<(), ExecutionError> {
         let vx = self.get_x();
-        let key = self.registers[vx as usize] as usize;
+        // there are only 16 keys, so just the low nibble picks one
+        let key = (self.registers[vx as usize] & 0xF) as usize;
         if self.keypad[key] == 1 {
             self.pc += 2;
         }
+
+        Ok(())
     }
 
     // SKNP Vx
     // skip next instruction if key code stored in Vx is not pressed
-    fn op_exa1(&mut self) {
+    fn op_exa1(&mut self) -> Result<(), ExecutionError> {
         let vx = self.get_x();
-        let key = self.registers[vx as usize] as usize;
+        let key = (self.registers[vx as usize] & 0xF) as usize;
         if self.keypad[key] != 1 {
             self.pc += 2;
         }
+
+        Ok(())
     }
 
     // LD Vx, DT
     // set Vx to delay timer value
-    fn op_fx07(&mut self) {
+    fn op_fx07(&mut self) -> Result<(), ExecutionError> {
         let vx = self.get_x();
         self.registers[vx as usize] = self.delay_timer;
+
+        Ok(())
     }
 
     // LD Vx, K
     // wait for key press, store key code in Vx
-    fn op_fx0a(&mut self) {
+    fn op_fx0a(&mut self) -> Result<(), ExecutionError> {
         let vx = self.get_x();
         let val = &mut self.registers[vx as usize];
 
@@ -482,72 +859,98 @@ impl Chip8 {
         } else {
             self.pc -= 2; // loop back to same instruction if no key was pressed
         }
+
+        Ok(())
     }
 
     // LD DT, Vx
     // set delay timer = Vx
-    fn op_fx15(&mut self) {
+    fn op_fx15(&mut self) -> Result<(), ExecutionError> {
         let vx = self.get_x();
         self.delay_timer = self.registers[vx as usize];
+
+        Ok(())
     }
 
     // LD ST, Vx
     // set sound timer = Vx
-    fn op_fx18(&mut self) {
+    fn op_fx18(&mut self) -> Result<(), ExecutionError> {
         let vx = self.get_x();
         self.sound_timer = self.registers[vx as usize];
+
+        Ok(())
     }
 
     // LD ADD I, Vx
     // add Vx to I
-    fn op_fx1e(&mut self) {
+    fn op_fx1e(&mut self) -> Result<(), ExecutionError> {
         let vx = self.get_x();
-        self.index += self.registers[vx as usize] as u16;
+        self.index = self.index.wrapping_add(self.registers[vx as usize] as u16);
+
+        Ok(())
     }
 
     // LD F, Vx
     // set I = location of sprite for digit Vx
-    fn op_fx29(&mut self) {
+    fn op_fx29(&mut self) -> Result<(), ExecutionError> {
         let vx = self.get_x();
-        let digit = self.registers[vx as usize];
+        // only the low nibble means anything, there are just 16 digits
+        let digit = self.registers[vx as usize] & 0xF;
+
+        self.index = FONT_SET_START_ADDRESS as u16 + 5 * digit as u16;
 
-        self.index = FONT_SET_START_ADDRESS as u16 + (5 * digit) as u16;
+        Ok(())
     }
 
     // LD B, Vx
     // store BCD representation of Vx in I, I + 1, and I + 2
-    fn op_fx33(&mut self) {
+    fn op_fx33(&mut self) -> Result<(), ExecutionError> {
         let vx = self.get_x();
         let mut value = self.registers[vx as usize];
+        let digits = self.memory_range(self.index, 3)?;
 
         // ones place
-        self.memory[(self.index + 2) as usize] = value % 10;
+        self.memory[digits.start + 2] = value % 10;
         value /= 10;
 
         // tens place
-        self.memory[(self.index + 1) as usize] = value % 10;
+        self.memory[digits.start + 1] = value % 10;
         value /= 10;
 
         // hundreds place
-        self.memory[self.index as usize] = value % 10;
+        self.memory[digits.start] = value % 10;
+        self.memory_written(digits);
+
+        Ok(())
     }
 
     // LD [I], Vx
     // store registers V0 -> Vx in memory starting at index
-    fn op_fx55(&mut self) {
+    fn op_fx55(&mut self) -> Result<(), ExecutionError> {
         let vx = self.get_x();
-        for i in 0..vx + 1 {
-            self.memory[(self.index + i as u16) as usize] = self.registers[i as usize];
+        let range = self.memory_range(self.index, vx as usize + 1)?;
+        self.memory[range.clone()].copy_from_slice(&self.registers[..=vx as usize]);
+        self.memory_written(range);
+
+        if self.quirks.load_store {
+            self.index = self.index.wrapping_add(vx as u16 + 1);
         }
+
+        Ok(())
     }
 
     // LD Vx, [I]
     // read in values to V0 -> Vx starting a index in memory
-    fn op_fx65(&mut self) {
+    fn op_fx65(&mut self) -> Result<(), ExecutionError> {
         let vx = self.get_x();
-        for i in 0..vx + 1 {
-            self.registers[i as usize] = self.memory[(self.index + i as u16) as usize];
+        let range = self.memory_range(self.index, vx as usize + 1)?;
+        self.registers[..=vx as usize].copy_from_slice(&self.memory[range]);
+
+        if self.quirks.load_store {
+            self.index = self.index.wrapping_add(vx as u16 + 1);
         }
+
+        Ok(())
     }
 }
 
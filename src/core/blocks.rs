@@ -0,0 +1,198 @@
+use std::ops::Range;
+
+use super::{decode, Chip8, ExecutionError};
+
+// translates runs of straight-line instructions into a chain of closures, so a block can be run
+// again without fetching or decoding any of it. a block ends at the first instruction that could
+// go anywhere but the next one, or that writes to memory, so a program changing its own code is
+// always caught before anything stale runs: writes throw away the blocks they land in, and the
+// next time pc gets there it's translated again. anything that can't be made into a block, like
+// pc at the very end of memory, is left to Chip8::step
+
+// longest a block gets, so translating one doesn't take forever on a rom that's mostly zeroes
+const MAX_BLOCK_LENGTH: usize = 64;
+
+// an instruction ready to run, which moves pc on past itself first like Chip8::step does
+type Step = Box<dyn Fn(&mut Chip8) -> Result<(), ExecutionError> + Send>;
+
+struct Block {
+    start: usize,
+    end: usize, // the address after the last instruction
+    steps: Vec<Step>
+}
+
+impl Block {
+    fn translate(memory: &[u8], start: u16) -> Option<Block> {
+        let start = start as usize;
+        let mut address = start;
+        let mut steps = vec![];
+
+        while address + 1 < memory.len() && steps.len() < MAX_BLOCK_LENGTH {
+            let opcode = u16::from_be_bytes([memory[address], memory[address + 1]]);
+            address += 2;
+            steps.push(translate_instruction(opcode, address as u16));
+
+            if ends_block(opcode) {
+                break;
+            }
+        }
+
+        if steps.is_empty() {
+            return None;
+        }
+
+        return Some(Block { start, end: address, steps });
+    }
+
+    // runs up to count instructions, returning how many ran. an error stops it straight away,
+    // the same as it would have stopped Chip8::cycle
+    fn run(&self, machine: &mut Chip8, count: usize) -> (usize, Result<(), ExecutionError>) {
+        let steps = &self.steps[..self.steps.len().min(count)];
+        for (ran, step) in steps.iter().enumerate() {
+            if let Err(e) = step(machine) {
+                return (ran + 1, Err(e));
+            }
+        }
+
+        return (steps.len(), Ok(()));
+    }
+}
+
+// the blocks that have been translated so far, by the address they start at. blocks can
+// overlap when something jumps into the middle of one, so covering counts how many blocks each
+// address is part of, to tell quickly whether a write touches any code at all
+pub(super) struct Blocks {
+    starts: Vec<Option<Box<Block>>>,
+    covering: Vec<u16>,
+    written: Option<Range<usize>> // only used by a stand-in, see Chip8::run_blocks
+}
+
+impl Blocks {
+    pub(super) fn new(size: usize) -> Self {
+        Blocks {
+            starts: (0..size).map(|_| None).collect(),
+            covering: vec![0; size],
+            written: None
+        }
+    }
+
+    // takes the place of the real table while it's out, keeping track of what gets written
+    fn stand_in() -> Self {
+        Blocks {
+            starts: vec![],
+            covering: vec![],
+            written: None
+        }
+    }
+
+    fn get(&self, address: u16) -> Option<&Block> {
+        return self.starts.get(address as usize)?.as_deref();
+    }
+
+    fn insert(&mut self, block: Block) {
+        for count in &mut self.covering[block.start..block.end] {
+            *count += 1;
+        }
+
+        let start = block.start;
+        self.starts[start] = Some(Box::new(block));
+    }
+
+    // throws away every block with code in the range
+    pub(super) fn invalidate(&mut self, range: Range<usize>) {
+        if self.starts.is_empty() {
+            self.written = Some(match self.written.take() {
+                Some(written) => written.start.min(range.start)..written.end.max(range.end),
+                None => range,
+            });
+            return;
+        }
+
+        if self.covering[range.clone()].iter().all(|count| *count == 0) {
+            return;
+        }
+
+        // a block is at most MAX_BLOCK_LENGTH instructions long, so any that start further back
+        // than that end before the range does
+        let first = range.start.saturating_sub(MAX_BLOCK_LENGTH * 2 - 1);
+        for start in first..range.end.min(self.starts.len()) {
+            let written = match &self.starts[start] {
+                Some(block) => block.start < range.end && range.start < block.end,
+                None => false,
+            };
+            if written {
+                let block = self.starts[start].take().unwrap();
+                for count in &mut self.covering[block.start..block.end] {
+                    *count -= 1;
+                }
+            }
+        }
+    }
+}
+
+impl Chip8 {
+    // runs count instructions a block at a time, stopping at the first error. the blocks can't
+    // be borrowed from the machine while they change it, so the table is swapped out for a
+    // stand-in that notes down any writes, which are then applied to the real one after each
+    // block. only the last instruction in a block can write, so that's soon enough
+    pub(super) fn run_blocks(&mut self, count: usize) -> Result<(), ExecutionError> {
+        let mut blocks = std::mem::replace(&mut self.blocks, Blocks::stand_in());
+        let result = self.run_blocks_from(&mut blocks, count);
+        self.blocks = blocks;
+
+        return result;
+    }
+
+    fn run_blocks_from(&mut self, blocks: &mut Blocks, mut count: usize) -> Result<(), ExecutionError> {
+        while count > 0 {
+            let pc = self.pc;
+            if blocks.get(pc).is_none() {
+                match Block::translate(&self.memory, pc) {
+                    Some(block) => blocks.insert(block),
+                    None => {
+                        let result = self.step();
+                        count -= 1;
+                        self.apply_writes(blocks);
+                        result?;
+                        continue;
+                    },
+                }
+            }
+
+            let (ran, result) = blocks.get(pc).unwrap().run(self, count);
+            count -= ran;
+            self.apply_writes(blocks);
+            result?;
+        }
+
+        Ok(())
+    }
+
+    fn apply_writes(&mut self, blocks: &mut Blocks) {
+        if let Some(range) = self.blocks.written.take() {
+            blocks.invalidate(range);
+        }
+    }
+}
+
+// anything that could jump, skip, call, return or wait, and the stores, which could write over
+// code in this block or any other
+fn ends_block(opcode: u16) -> bool {
+    return match opcode >> 12 {
+        0x0 => opcode == 0x00EE,
+        0x1 | 0x2 | 0x3 | 0x4 | 0x5 | 0x9 | 0xB | 0xE => true,
+        0xF => matches!(opcode & 0xFF, 0x0A | 0x33 | 0x55),
+        _ => false,
+    };
+}
+
+// the op_ function decode picks for the instruction, wrapped up with what Chip8::step would
+// have done before calling it
+fn translate_instruction(opcode: u16, next: u16) -> Step {
+    let handler = decode(opcode);
+    return Box::new(move |machine: &mut Chip8| {
+        machine.pc = next;
+        machine.opcode = opcode;
+        handler(machine)
+    });
+}
@@ -0,0 +1,209 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use minifb::{Key, KeyRepeat, Scale, ScaleMode, Window, WindowOptions};
+
+use chip8_rust::colour::Colour;
+use chip8_rust::core::MACHINE_SCREEN_WIDTH;
+use chip8_rust::core::MACHINE_SCREEN_HEIGHT;
+use chip8_rust::keymap::Keymap;
+use chip8_rust::menu::MenuInput;
+use chip8_rust::platform::{Display, Frame, Frontend, Hotkey, Input, InputEvent, Parts, Silence};
+use chip8_rust::postfx::Effects;
+
+use super::hud::Hud;
+use super::text::{self, Canvas};
+
+const HOTKEYS: [(Key, Hotkey); 14] = [
+    (Key::F1, Hotkey::Hud),
+    (Key::F2, Hotkey::Palette),
+    (Key::F3, Hotkey::Effects),
+    (Key::F4, Hotkey::Persistence),
+    (Key::F5, Hotkey::Reset),
+    (Key::F6, Hotkey::SoftReset),
+    (Key::F11, Hotkey::Record),
+    (Key::F12, Hotkey::Screenshot),
+    (Key::Tab, Hotkey::FastForward),
+    (Key::Equal, Hotkey::Faster),
+    (Key::Minus, Hotkey::Slower),
+    (Key::P, Hotkey::Pause),
+    (Key::N, Hotkey::FrameAdvance),
+    (Key::Escape, Hotkey::Menu)
+];
+
+const MENU_KEYS: [(Key, MenuInput); 6] = [
+    (Key::Up, MenuInput::Up),
+    (Key::Down, MenuInput::Down),
+    (Key::Left, MenuInput::Left),
+    (Key::Right, MenuInput::Right),
+    (Key::Enter, MenuInput::Select),
+    (Key::Backspace, MenuInput::Back)
+];
+
+// a minifb window drawn entirely in software, for machines where the gpu window won't start.
+// post processing goes through the software version in postfx.rs
+pub struct FramebufferWindow {
+    pub title: String,
+    pub width: u32,
+    pub height: u32
+}
+
+// the display and input both need the window, minifb picks up input while drawing
+pub struct FramebufferDisplay {
+    window: Rc<RefCell<Window>>,
+    buffer: Vec<u32>,
+    width: usize,
+    height: usize,
+    hud: Hud,
+    overlay: Canvas,
+    overlay_scale: usize
+}
+
+pub struct FramebufferInput {
+    window: Rc<RefCell<Window>>,
+    keys: [Option<Key>; 16], // keyboard key for each chip-8 key, from the keymap
+    held: [bool; 16]
+}
+
+impl Frontend for FramebufferWindow {
+    type Display = FramebufferDisplay;
+    type Input = FramebufferInput;
+    type Audio = Silence;
+
+    fn open(self) -> Result<Parts<Self>, String> {
+        let width = self.width as usize;
+        let height = self.height as usize;
+        let options = WindowOptions {
+            resize: true,
+            scale: Scale::X1,
+            scale_mode: ScaleMode::AspectRatioStretch,
+            ..WindowOptions::default()
+        };
+
+        let window = Window::new(&self.title, width, height, options)
+            .map_err(|e| format!("couldn't open a window: {}", e))?;
+        let window = Rc::new(RefCell::new(window));
+
+        let overlay_scale = text::canvas_scale(height);
+        let display = FramebufferDisplay {
+            window: window.clone(),
+            buffer: vec![0; width * height],
+            width,
+            height,
+            hud: Hud::new(),
+            overlay: Canvas::new(width / overlay_scale, height / overlay_scale),
+            overlay_scale
+        };
+        let input = FramebufferInput {
+            window,
+            keys: [None; 16],
+            held: [false; 16]
+        };
+
+        return Ok(Parts { display, input, audio: Silence });
+    }
+}
+
+impl Display for FramebufferDisplay {
+    fn present(&mut self, frame: &Frame) -> Result<(), String> {
+        if frame.effects == Effects::default() {
+            // nothing to post process, so a plain nearest neighbour scale will do
+            for (y, row) in self.buffer.chunks_mut(self.width).enumerate() {
+                let source_row = y * MACHINE_SCREEN_HEIGHT / self.height * MACHINE_SCREEN_WIDTH;
+                for (x, pixel) in row.iter_mut().enumerate() {
+                    *pixel = frame.pixels[source_row + x * MACHINE_SCREEN_WIDTH / self.width].to_argb();
+                }
+            }
+        } else {
+            let output = frame.effects.render(frame.pixels, MACHINE_SCREEN_WIDTH, MACHINE_SCREEN_HEIGHT, self.width, self.height);
+            for (pixel, colour) in self.buffer.iter_mut().zip(output) {
+                *pixel = colour.to_argb();
+            }
+        }
+
+        self.overlay.clear();
+        if self.hud.draw(&mut self.overlay, frame) {
+            self.draw_overlay();
+        }
+
+        // this also picks up the input for the next frame
+        return self.window.borrow_mut().update_with_buffer(&self.buffer, self.width, self.height)
+            .map_err(|e| format!("couldn't update the window: {}", e));
+    }
+
+    fn show_message(&mut self, message: &str) {
+        self.hud.show_message(message);
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.window.borrow_mut().set_title(title);
+    }
+}
+
+impl FramebufferDisplay {
+    fn draw_overlay(&mut self) {
+        let overlay = &self.overlay;
+        for (y, row) in self.buffer.chunks_mut(self.width).enumerate().take(overlay.height * self.overlay_scale) {
+            let overlay_row = &overlay.pixels[(y / self.overlay_scale) * overlay.width..][..overlay.width];
+            for (x, pixel) in row.iter_mut().enumerate().take(overlay.width * self.overlay_scale) {
+                let colour = overlay_row[x / self.overlay_scale];
+                if colour.alpha() > 0 {
+                    *pixel = colour.blend_over(Colour::from_argb(*pixel)).to_argb();
+                }
+            }
+        }
+    }
+}
+
+impl Input for FramebufferInput {
+    fn poll(&mut self) -> Result<Vec<InputEvent>, String> {
+        let window = self.window.borrow();
+        if !window.is_open() {
+            return Ok(vec![InputEvent::Quit]);
+        }
+
+        let mut events = vec![];
+        for (chip8_key, key) in self.keys.iter().enumerate() {
+            let pressed = key.is_some_and(|key| window.is_key_down(key));
+            if pressed != self.held[chip8_key] {
+                self.held[chip8_key] = pressed;
+                events.push(InputEvent::Key { key: chip8_key, pressed });
+            }
+        }
+
+        // the keymap wins if it wants one of the hotkeys' keys
+        for (key, hotkey) in HOTKEYS.iter() {
+            if !self.keys.contains(&Some(*key)) && window.is_key_pressed(*key, KeyRepeat::No) {
+                events.push(InputEvent::Hotkey(*hotkey));
+            }
+        }
+
+        for (key, input) in MENU_KEYS.iter() {
+            if window.is_key_pressed(*key, KeyRepeat::Yes) {
+                events.push(InputEvent::Menu(*input));
+            }
+        }
+
+        return Ok(events);
+    }
+
+    fn set_keymap(&mut self, keymap: &Keymap) {
+        for (chip8_key, key) in self.keys.iter_mut().enumerate() {
+            *key = key_for_label(keymap.label(chip8_key));
+        }
+    }
+}
+
+fn key_for_label(label: char) -> Option<Key> {
+    let key = match label.to_ascii_uppercase() {
+        '0' => Key::Key0, '1' => Key::Key1, '2' => Key::Key2, '3' => Key::Key3, '4' => Key::Key4,
+        '5' => Key::Key5, '6' => Key::Key6, '7' => Key::Key7, '8' => Key::Key8, '9' => Key::Key9,
+        'A' => Key::A, 'B' => Key::B, 'C' => Key::C, 'D' => Key::D, 'E' => Key::E, 'F' => Key::F,
+        'G' => Key::G, 'H' => Key::H, 'I' => Key::I, 'J' => Key::J, 'K' => Key::K, 'L' => Key::L,
+        'M' => Key::M, 'N' => Key::N, 'O' => Key::O, 'P' => Key::P, 'Q' => Key::Q, 'R' => Key::R,
+        'S' => Key::S, 'T' => Key::T, 'U' => Key::U, 'V' => Key::V, 'W' => Key::W, 'X' => Key::X,
+        'Y' => Key::Y, 'Z' => Key::Z,
+        _ => return None,
+    };
+
+    return Some(key);
+}
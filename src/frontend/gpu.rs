@@ -0,0 +1,346 @@
+// gfx_defines! expects to find gfx in the enclosing module
+extern crate gfx;
+
+use gfx::traits::{Factory, FactoryExt};
+use gfx::Device;
+use gfx_device_gl::{CommandBuffer, Resources};
+use gfx_window_glutin as gfx_glutin;
+
+use chip8_rust::core::MACHINE_SCREEN_WIDTH;
+use chip8_rust::core::MACHINE_SCREEN_HEIGHT;
+use chip8_rust::keymap::Keymap;
+use chip8_rust::menu::MenuInput;
+use chip8_rust::platform::{Display, Frame, Frontend, Hotkey, Input, InputEvent, Parts, Silence};
+
+use super::hud::Hud;
+use super::text::{self, Canvas};
+
+pub type ColourFormat = gfx::format::Srgba8;
+pub type DepthFormat = gfx::format::DepthStencil;
+
+gfx_defines! {
+    vertex Vertex {
+        pos: [f32; 2] = "a_Pos",
+        uv: [f32; 2] = "a_Uv",
+    }
+
+    pipeline pipe {
+        vbuf: gfx::VertexBuffer<Vertex> = (),
+        screen: gfx::TextureSampler<[f32; 4]> = "t_Screen",
+        screen_size: gfx::Global<[f32; 2]> = "u_ScreenSize",
+        output_size: gfx::Global<[f32; 2]> = "u_OutputSize",
+        curvature: gfx::Global<f32> = "u_Curvature",
+        sharp_bilinear: gfx::Global<f32> = "u_SharpBilinear",
+        bloom: gfx::Global<f32> = "u_Bloom",
+        scanlines: gfx::Global<f32> = "u_Scanlines",
+        pixel_grid: gfx::Global<f32> = "u_PixelGrid",
+        out: gfx::RenderTarget<ColourFormat> = "Target0",
+    }
+
+    // the hud, blended over the top of the screen
+    pipeline overlay_pipe {
+        vbuf: gfx::VertexBuffer<Vertex> = (),
+        overlay: gfx::TextureSampler<[f32; 4]> = "t_Overlay",
+        out: gfx::BlendTarget<ColourFormat> = ("Target0", gfx::state::MASK_ALL, gfx::preset::blend::ALPHA),
+    }
+}
+
+// a glutin window with the post processing done in shaders/fragment.glsl
+pub struct GpuWindow {
+    pub title: String,
+    pub width: u32,
+    pub height: u32
+}
+
+pub struct GpuDisplay {
+    window: glutin::Window,
+    device: gfx_device_gl::Device,
+    encoder: gfx::Encoder<Resources, CommandBuffer>,
+    pso: gfx::PipelineState<Resources, pipe::Meta>,
+    slice: gfx::Slice<Resources>,
+    screen_texture: gfx::handle::Texture<Resources, gfx::format::R8_G8_B8_A8>,
+    screen_info: gfx::texture::NewImageInfo,
+    data: pipe::Data<Resources>,
+    depth: gfx::handle::DepthStencilView<Resources, DepthFormat>,
+    overlay_pso: gfx::PipelineState<Resources, overlay_pipe::Meta>,
+    overlay_texture: gfx::handle::Texture<Resources, gfx::format::R8_G8_B8_A8>,
+    overlay_info: gfx::texture::NewImageInfo,
+    overlay_data: overlay_pipe::Data<Resources>,
+    overlay: Canvas,
+    hud: Hud
+}
+
+pub struct GpuInput {
+    events_loop: glutin::EventsLoop,
+    keymap: Keymap
+}
+
+impl Frontend for GpuWindow {
+    type Display = GpuDisplay;
+    type Input = GpuInput;
+    type Audio = Silence;
+
+    fn open(self) -> Result<Parts<Self>, String> {
+        // set up windowing/graphics stuffs
+        let events_loop = glutin::EventsLoop::new();
+        let builder = glutin::WindowBuilder::new()
+            .with_title(self.title)
+            .with_dimensions(self.width, self.height)
+            .with_vsync();
+
+        let (window, device, mut factory, main_colour, depth) =
+            gfx_glutin::init::<ColourFormat, DepthFormat>(builder, &events_loop);
+
+        let encoder: gfx::Encoder<_, _> = factory.create_command_buffer().into();
+        // read in the shaders
+        let pso = factory.create_pipeline_simple(
+            include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/shaders/vertex.glsl")),
+            include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/shaders/fragment.glsl")),
+            pipe::new()
+        ).map_err(|e| format!("couldn't build the shaders: {}", e))?;
+        let overlay_pso = factory.create_pipeline_simple(
+            include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/shaders/vertex.glsl")),
+            include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/shaders/overlay.glsl")),
+            overlay_pipe::new()
+        ).map_err(|e| format!("couldn't build the overlay shaders: {}", e))?;
+
+        // the screen gets uploaded to a texture every frame, and the shaders stretch it over
+        // a single quad covering the whole window. v runs top to bottom to match the rows
+        // in video
+        let quad = [
+            Vertex { pos: [-1.0, 1.0], uv: [0.0, 0.0] },
+            Vertex { pos: [1.0, 1.0], uv: [1.0, 0.0] },
+            Vertex { pos: [1.0, -1.0], uv: [1.0, 1.0] },
+            Vertex { pos: [-1.0, -1.0], uv: [0.0, 1.0] },
+        ];
+        let (vertex_buffer, slice) = factory.create_vertex_buffer_with_slice(&quad, &[0u16, 1, 2, 0, 2, 3][..]);
+
+        let screen_kind = gfx::texture::Kind::D2(
+            MACHINE_SCREEN_WIDTH as u16,
+            MACHINE_SCREEN_HEIGHT as u16,
+            gfx::texture::AaMode::Single
+        );
+        let screen_texture = factory.create_texture::<gfx::format::R8_G8_B8_A8>(
+            screen_kind,
+            1,
+            gfx::SHADER_RESOURCE,
+            gfx::memory::Usage::Dynamic,
+            Some(gfx::format::ChannelType::Srgb)
+        ).map_err(|e| format!("couldn't create the screen texture: {}", e))?;
+        let screen_view = factory.view_texture_as_shader_resource::<ColourFormat>(
+            &screen_texture,
+            (0, 0),
+            gfx::format::Swizzle::new()
+        ).map_err(|e| format!("couldn't view the screen texture: {}", e))?;
+        let sampler = factory.create_sampler(gfx::texture::SamplerInfo::new(
+            gfx::texture::FilterMethod::Bilinear,
+            gfx::texture::WrapMode::Clamp
+        ));
+        let screen_info = gfx::texture::NewImageInfo {
+            xoffset: 0,
+            yoffset: 0,
+            zoffset: 0,
+            width: MACHINE_SCREEN_WIDTH as u16,
+            height: MACHINE_SCREEN_HEIGHT as u16,
+            depth: 0,
+            format: (),
+            mipmap: 0
+        };
+
+        // the overlay is drawn at a fraction of the window's size and stretched over it without
+        // any filtering, so the text comes out blocky like the game
+        let overlay_scale = text::canvas_scale(self.height as usize);
+        let overlay = Canvas::new(self.width as usize / overlay_scale, self.height as usize / overlay_scale);
+        let overlay_texture = factory.create_texture::<gfx::format::R8_G8_B8_A8>(
+            gfx::texture::Kind::D2(overlay.width as u16, overlay.height as u16, gfx::texture::AaMode::Single),
+            1,
+            gfx::SHADER_RESOURCE,
+            gfx::memory::Usage::Dynamic,
+            Some(gfx::format::ChannelType::Srgb)
+        ).map_err(|e| format!("couldn't create the overlay texture: {}", e))?;
+        let overlay_view = factory.view_texture_as_shader_resource::<ColourFormat>(
+            &overlay_texture,
+            (0, 0),
+            gfx::format::Swizzle::new()
+        ).map_err(|e| format!("couldn't view the overlay texture: {}", e))?;
+        let overlay_sampler = factory.create_sampler(gfx::texture::SamplerInfo::new(
+            gfx::texture::FilterMethod::Scale,
+            gfx::texture::WrapMode::Clamp
+        ));
+        let overlay_info = gfx::texture::NewImageInfo {
+            width: overlay.width as u16,
+            height: overlay.height as u16,
+            ..screen_info
+        };
+        let overlay_data = overlay_pipe::Data {
+            vbuf: vertex_buffer.clone(),
+            overlay: (overlay_view, overlay_sampler),
+            out: main_colour.clone()
+        };
+
+        let data = pipe::Data {
+            vbuf: vertex_buffer,
+            screen: (screen_view, sampler),
+            screen_size: [MACHINE_SCREEN_WIDTH as f32, MACHINE_SCREEN_HEIGHT as f32],
+            output_size: [self.width as f32, self.height as f32],
+            curvature: 0.0,
+            sharp_bilinear: 0.0,
+            bloom: 0.0,
+            scanlines: 0.0,
+            pixel_grid: 0.0,
+            out: main_colour
+        };
+
+        let display = GpuDisplay {
+            window,
+            device,
+            encoder,
+            pso,
+            slice,
+            screen_texture,
+            screen_info,
+            data,
+            depth,
+            overlay_pso,
+            overlay_texture,
+            overlay_info,
+            overlay_data,
+            overlay,
+            hud: Hud::new()
+        };
+
+        return Ok(Parts { display, input: GpuInput { events_loop, keymap: Keymap::default() }, audio: Silence });
+    }
+}
+
+impl Display for GpuDisplay {
+    fn present(&mut self, frame: &Frame) -> Result<(), String> {
+        // this only does anything when the window has been resized
+        gfx_glutin::update_views(&self.window, &mut self.data.out, &mut self.depth);
+        self.overlay_data.out = self.data.out.clone();
+        let (width, height, _, _) = self.data.out.get_dimensions();
+        self.data.output_size = [width as f32, height as f32];
+
+        let texels: Vec<[u8; 4]> = frame.pixels.iter().map(|colour| colour.to_rgba()).collect();
+        self.encoder.update_texture::<gfx::format::R8_G8_B8_A8, ColourFormat>(
+            &self.screen_texture,
+            None,
+            self.screen_info,
+            &texels
+        ).map_err(|e| format!("couldn't upload the screen: {:?}", e))?;
+
+        let effects = frame.effects;
+        self.data.curvature = effects.curvature;
+        self.data.sharp_bilinear = if effects.sharp_bilinear { 1.0 } else { 0.0 };
+        self.data.bloom = effects.bloom;
+        self.data.scanlines = effects.scanlines;
+        self.data.pixel_grid = effects.pixel_grid;
+
+        self.encoder.clear(&self.data.out, [0.0, 0.0, 0.0, 1.0]);
+        self.encoder.draw(&self.slice, &self.pso, &self.data);
+
+        self.overlay.clear();
+        if self.hud.draw(&mut self.overlay, frame) {
+            let texels: Vec<[u8; 4]> = self.overlay.pixels.iter().map(|colour| colour.to_rgba()).collect();
+            self.encoder.update_texture::<gfx::format::R8_G8_B8_A8, ColourFormat>(
+                &self.overlay_texture,
+                None,
+                self.overlay_info,
+                &texels
+            ).map_err(|e| format!("couldn't upload the hud: {:?}", e))?;
+            self.encoder.draw(&self.slice, &self.overlay_pso, &self.overlay_data);
+        }
+
+        self.encoder.flush(&mut self.device);
+        self.window.swap_buffers().map_err(|e| e.to_string())?;
+        self.device.cleanup();
+
+        return Ok(());
+    }
+
+    fn show_message(&mut self, message: &str) {
+        self.hud.show_message(message);
+    }
+
+    fn set_title(&mut self, title: &str) {
+        self.window.set_title(title);
+    }
+}
+
+impl Input for GpuInput {
+    fn poll(&mut self) -> Result<Vec<InputEvent>, String> {
+        let mut events = vec![];
+        let keymap = &self.keymap;
+        self.events_loop.poll_events(|glutin::Event::WindowEvent { window_id: _, event }| {
+            use glutin::WindowEvent::*;
+            match event {
+                Closed => events.push(InputEvent::Quit),
+                KeyboardInput(state, _, Some(key), _) => {
+                    let pressed = state == glutin::ElementState::Pressed;
+                    events.extend(translate_key(key, pressed, keymap));
+                },
+                _ => (),
+            }
+        });
+
+        return Ok(events);
+    }
+
+    fn set_keymap(&mut self, keymap: &Keymap) {
+        self.keymap = *keymap;
+    }
+}
+
+// see platform.rs for the hotkeys. the keymap wins if it wants one of their keys
+fn translate_key(key: glutin::VirtualKeyCode, pressed: bool, keymap: &Keymap) -> Option<InputEvent> {
+    use glutin::VirtualKeyCode::*;
+
+    if let Some(chip8_key) = label_for_key(key).and_then(|label| keymap.key_for_label(label)) {
+        return Some(InputEvent::Key { key: chip8_key, pressed });
+    }
+
+    if !pressed {
+        return None;
+    }
+
+    let hotkey = match key {
+        F1 => Hotkey::Hud,
+        F2 => Hotkey::Palette,
+        F3 => Hotkey::Effects,
+        F4 => Hotkey::Persistence,
+        F5 => Hotkey::Reset,
+        F6 => Hotkey::SoftReset,
+        F11 => Hotkey::Record,
+        F12 => Hotkey::Screenshot,
+        Tab => Hotkey::FastForward,
+        Equals => Hotkey::Faster,
+        Minus => Hotkey::Slower,
+        P => Hotkey::Pause,
+        N => Hotkey::FrameAdvance,
+        Escape => Hotkey::Menu,
+        Up => return Some(InputEvent::Menu(MenuInput::Up)),
+        Down => return Some(InputEvent::Menu(MenuInput::Down)),
+        Left => return Some(InputEvent::Menu(MenuInput::Left)),
+        Right => return Some(InputEvent::Menu(MenuInput::Right)),
+        Return => return Some(InputEvent::Menu(MenuInput::Select)),
+        Back => return Some(InputEvent::Menu(MenuInput::Back)),
+        _ => return None,
+    };
+
+    return Some(InputEvent::Hotkey(hotkey));
+}
+
+fn label_for_key(key: glutin::VirtualKeyCode) -> Option<char> {
+    use glutin::VirtualKeyCode::*;
+
+    let label = match key {
+        Key0 => '0', Key1 => '1', Key2 => '2', Key3 => '3', Key4 => '4',
+        Key5 => '5', Key6 => '6', Key7 => '7', Key8 => '8', Key9 => '9',
+        A => 'A', B => 'B', C => 'C', D => 'D', E => 'E', F => 'F', G => 'G', H => 'H', I => 'I',
+        J => 'J', K => 'K', L => 'L', M => 'M', N => 'N', O => 'O', P => 'P', Q => 'Q', R => 'R',
+        S => 'S', T => 'T', U => 'U', V => 'V', W => 'W', X => 'X', Y => 'Y', Z => 'Z',
+        _ => return None,
+    };
+
+    return Some(label);
+}
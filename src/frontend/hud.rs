@@ -0,0 +1,121 @@
+use std::time::{Duration, Instant};
+
+use chip8_rust::colour::Colour;
+use chip8_rust::menu::MenuView;
+use chip8_rust::platform::Frame;
+
+use super::text::{Canvas, CELL_HEIGHT, CELL_WIDTH};
+
+const MESSAGE_DURATION: Duration = Duration::from_secs(3);
+const MARGIN: usize = 2;
+
+// what gets drawn over the game in the windows: the stats and machine state in the top left
+// when F1 turns it on, messages in the bottom left for a few seconds whether it's on or not,
+// and the menu in the middle when it's open
+pub struct Hud {
+    message: Option<(String, Instant)> // and when it goes away
+}
+
+impl Hud {
+    pub fn new() -> Self {
+        Hud { message: None }
+    }
+
+    pub fn show_message(&mut self, message: &str) {
+        self.message = Some((message.to_string(), Instant::now() + MESSAGE_DURATION));
+    }
+
+    // draws onto a cleared canvas, returning false if there was nothing to draw so the caller
+    // can skip compositing it
+    pub fn draw(&mut self, canvas: &mut Canvas, frame: &Frame) -> bool {
+        if self.message.as_ref().is_some_and(|(_, until)| Instant::now() >= *until) {
+            self.message = None;
+        }
+
+        if !frame.hud && self.message.is_none() && frame.menu.is_none() {
+            return false;
+        }
+
+        let text = Colour::new(255, 255, 255);
+        let background = Colour::with_alpha(0, 0, 0, 176);
+
+        if let Some(menu) = &frame.menu {
+            draw_menu(canvas, menu, text, background);
+        } else if frame.hud {
+            canvas.draw_panel(MARGIN, MARGIN, &status_lines(frame), text, background);
+        }
+
+        if let Some((message, _)) = &self.message {
+            let y = canvas.height.saturating_sub(CELL_HEIGHT + 1 + MARGIN);
+            canvas.draw_panel(MARGIN, y, std::slice::from_ref(message), text, background);
+        }
+
+        return true;
+    }
+}
+
+fn status_lines(frame: &Frame) -> Vec<String> {
+    let stats = frame.stats;
+    let machine = frame.machine;
+
+    let mut speed = format!("FPS {:.0}  SPEED {:.2}X  IPF {}", stats.fps, stats.speed, machine.tick_rate);
+    if stats.paused {
+        speed.push_str("  PAUSED");
+    } else if stats.fast_forward {
+        speed.push_str("  FAST");
+    }
+
+    let state = format!(
+        "PC {:04X}  I {:04X}  SP {}  DT {:02X}  ST {:02X}",
+        machine.pc(), machine.index(), machine.stack_depth(), machine.delay_timer(), machine.sound_timer()
+    );
+
+    let registers: Vec<String> = machine.registers().iter().enumerate()
+        .map(|(i, value)| format!("V{:X} {:02X}", i, value))
+        .collect();
+
+    return vec![speed, state, registers[..8].join(" "), registers[8..].join(" ")];
+}
+
+// dims the game and puts the menu in a panel in the middle, scrolled so the selected item is
+// always in view
+fn draw_menu(canvas: &mut Canvas, menu: &MenuView, text: Colour, background: Colour) {
+    canvas.fill_rect(0, 0, canvas.width, canvas.height, Colour::with_alpha(0, 0, 0, 96));
+
+    let columns = (canvas.width.saturating_sub(2 * MARGIN + 1) / CELL_WIDTH).max(3);
+    let rows = (canvas.height.saturating_sub(2 * MARGIN + 1) / CELL_HEIGHT).max(3);
+
+    // the title and a gap take up two rows
+    let visible = rows - 2;
+    let first = (menu.selected + 1).saturating_sub(visible).min(menu.items.len().saturating_sub(visible));
+
+    let mut lines = vec![truncate_start(&menu.title, columns), String::new()];
+    for (i, item) in menu.items.iter().enumerate().skip(first).take(visible) {
+        let cursor = if i == menu.selected { "> " } else { "  " };
+        lines.push(truncate_end(&format!("{}{}", cursor, item), columns));
+    }
+
+    let width = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0) * CELL_WIDTH + 1;
+    let height = lines.len() * CELL_HEIGHT + 1;
+    let x = canvas.width.saturating_sub(width) / 2;
+    let y = canvas.height.saturating_sub(height) / 2;
+    canvas.draw_panel(x, y, &lines, text, background);
+}
+
+// long paths are more useful with the end kept
+fn truncate_start(text: &str, columns: usize) -> String {
+    let length = text.chars().count();
+    if length <= columns {
+        return text.to_string();
+    }
+
+    return format!("..{}", text.chars().skip(length - columns + 2).collect::<String>());
+}
+
+fn truncate_end(text: &str, columns: usize) -> String {
+    if text.chars().count() <= columns {
+        return text.to_string();
+    }
+
+    return format!("{}..", text.chars().take(columns - 2).collect::<String>());
+}
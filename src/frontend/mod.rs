@@ -0,0 +1,8 @@
+// the ways the interpreter can be shown and played: a window drawn on the gpu, a plain
+// software drawn window, or a terminal. each one is a platform::Frontend. the windows share
+// the hud, which is drawn with the little font in text.rs
+pub mod framebuffer;
+pub mod gpu;
+pub mod hud;
+pub mod terminal;
+pub mod text;
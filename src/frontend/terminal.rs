@@ -0,0 +1,315 @@
+use std::error::Error;
+use std::io::{self, Stdout, Write};
+use std::time::{Duration, Instant};
+use crossterm::{cursor, event, queue, style, terminal};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use crossterm::style::Color;
+
+use chip8_rust::colour::Colour;
+use chip8_rust::core::{self, MACHINE_SCREEN_WIDTH, MACHINE_SCREEN_HEIGHT};
+use chip8_rust::keymap::Keymap;
+use chip8_rust::platform::{Audio, Display, Frame, Frontend, Hotkey, Input, InputEvent, Parts};
+
+// runs the interpreter inside a terminal, for when there's no display to open a window on.
+// each character cell shows two pixels stacked on top of each other by drawing an upper half
+// block in the top pixel's colour over a background of the bottom pixel's colour
+const UPPER_HALF_BLOCK: char = '\u{2580}';
+const SCREEN_ROWS: usize = MACHINE_SCREEN_HEIGHT / 2;
+const STATUS_ROWS: usize = 4;
+const MESSAGE_DURATION: Duration = Duration::from_secs(3);
+const BELL: char = '\u{7}';
+
+// the keys are the same as in the window, see platform.rs. esc or ctrl+c quits
+//
+// terminals only tell us when a key is pressed, never when it's released, so a key is held
+// down until key_timeout milliseconds after the last time we heard about it. holding a key
+// down keeps it pressed thanks to the terminal's key repeat, as long as the timeout is longer
+// than the repeat delay
+pub struct TerminalFrontend {
+    pub key_timeout: u64
+}
+
+// the terminal is put into raw mode on the alternate screen while this is around, and put
+// back how it was found once it's dropped, even if something goes wrong along the way
+pub struct TerminalDisplay {
+    stdout: Stdout,
+    size: (u16, u16),
+    last_frame: Vec<Colour>,
+    last_status: [String; STATUS_ROWS],
+    message: Option<(String, Instant)>, // and when it goes away
+    too_small: bool
+}
+
+pub struct TerminalInput {
+    key_timeout: Duration,
+    keymap: Keymap,
+    releases: [Option<Instant>; 16]
+}
+
+// all a terminal can do is ring its bell, so it does that whenever a tone starts
+pub struct TerminalBell {
+    playing: bool
+}
+
+impl Frontend for TerminalFrontend {
+    type Display = TerminalDisplay;
+    type Input = TerminalInput;
+    type Audio = TerminalBell;
+
+    fn open(self) -> Result<Parts<Self>, String> {
+        let size = terminal::size().map_err(describe)?;
+        terminal::enable_raw_mode().map_err(describe)?;
+
+        let mut display = TerminalDisplay {
+            stdout: io::stdout(),
+            size,
+            last_frame: vec![],
+            last_status: Default::default(),
+            message: None,
+            too_small: false
+        };
+        queue!(display.stdout, terminal::EnterAlternateScreen, cursor::Hide, terminal::Clear(terminal::ClearType::All))
+            .map_err(describe)?;
+        display.stdout.flush().map_err(|e| e.to_string())?;
+
+        let input = TerminalInput {
+            key_timeout: Duration::from_millis(self.key_timeout),
+            keymap: Keymap::default(),
+            releases: [None; 16]
+        };
+
+        return Ok(Parts { display, input, audio: TerminalBell { playing: false } });
+    }
+}
+
+impl TerminalDisplay {
+    // whatever was on screen is gone, so everything needs drawing again
+    fn redraw(&mut self) -> crossterm::Result<()> {
+        self.last_frame.clear();
+        self.last_status = Default::default();
+        self.too_small = false;
+        queue!(self.stdout, style::ResetColor, terminal::Clear(terminal::ClearType::All))?;
+
+        return Ok(());
+    }
+
+    fn draw(&mut self, frame: &Frame) -> crossterm::Result<()> {
+        let size = terminal::size()?;
+        if size != self.size {
+            self.size = size;
+            self.redraw()?;
+        }
+
+        let (columns, rows) = size;
+        if (columns as usize) < MACHINE_SCREEN_WIDTH || (rows as usize) < SCREEN_ROWS + STATUS_ROWS {
+            if !self.too_small {
+                queue!(
+                    self.stdout,
+                    style::ResetColor,
+                    terminal::Clear(terminal::ClearType::All),
+                    cursor::MoveTo(0, 0),
+                    style::Print(format!("terminal too small, needs to be at least {}x{}", MACHINE_SCREEN_WIDTH, SCREEN_ROWS + STATUS_ROWS))
+                )?;
+                self.stdout.flush()?;
+                self.too_small = true;
+            }
+            return Ok(());
+        }
+
+        if self.too_small {
+            self.redraw()?;
+        }
+
+        // only draw what's changed, which saves a lot of bandwidth over a slow connection
+        if frame.pixels != self.last_frame.as_slice() {
+            draw_screen(&mut self.stdout, frame.pixels)?;
+            self.last_frame = frame.pixels.to_vec();
+        }
+
+        if self.message.as_ref().is_some_and(|(_, until)| Instant::now() >= *until) {
+            self.message = None;
+        }
+
+        let message = self.message.as_ref().map(|(message, _)| message.as_str()).unwrap_or("");
+        let status = status_lines(frame.machine, message);
+        if status != self.last_status {
+            draw_status(&mut self.stdout, &status)?;
+            self.last_status = status;
+        }
+
+        self.stdout.flush()?;
+
+        return Ok(());
+    }
+}
+
+impl Display for TerminalDisplay {
+    fn present(&mut self, frame: &Frame) -> Result<(), String> {
+        return self.draw(frame).map_err(describe);
+    }
+
+    // printing would scribble all over the screen, so messages go under the status lines
+    fn show_message(&mut self, message: &str) {
+        self.message = Some((message.to_string(), Instant::now() + MESSAGE_DURATION));
+    }
+}
+
+impl Drop for TerminalDisplay {
+    fn drop(&mut self) {
+        let _ = queue!(self.stdout, style::ResetColor, cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = self.stdout.flush();
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+impl TerminalInput {
+    fn read_events(&mut self) -> crossterm::Result<Vec<InputEvent>> {
+        let mut events = vec![];
+
+        while event::poll(Duration::from_secs(0))? {
+            let key = match event::read()? {
+                Event::Key(key) => key,
+                _ => continue,
+            };
+
+            match key {
+                // there's no menu down here, so Esc quits
+                KeyEvent { code: KeyCode::Esc, .. } => events.push(InputEvent::Quit),
+                KeyEvent { code: KeyCode::Char('c'), modifiers } if modifiers.contains(KeyModifiers::CONTROL) => {
+                    events.push(InputEvent::Quit);
+                },
+                KeyEvent { code: KeyCode::Tab, .. } => events.push(InputEvent::Hotkey(Hotkey::FastForward)),
+                KeyEvent { code: KeyCode::Char(label), .. } => {
+                    // the keymap wins if it wants one of the hotkeys' keys
+                    if let Some(key) = self.keymap.key_for_label(label) {
+                        events.push(InputEvent::Key { key, pressed: true });
+                        self.releases[key] = Some(Instant::now() + self.key_timeout);
+                    } else if let Some(hotkey) = hotkey_for_char(label) {
+                        events.push(InputEvent::Hotkey(hotkey));
+                    }
+                },
+                // there's no post processing down here, so there's nothing for F3 to do
+                KeyEvent { code: KeyCode::F(2), .. } => events.push(InputEvent::Hotkey(Hotkey::Palette)),
+                KeyEvent { code: KeyCode::F(4), .. } => events.push(InputEvent::Hotkey(Hotkey::Persistence)),
+                KeyEvent { code: KeyCode::F(5), .. } => events.push(InputEvent::Hotkey(Hotkey::Reset)),
+                KeyEvent { code: KeyCode::F(6), .. } => events.push(InputEvent::Hotkey(Hotkey::SoftReset)),
+                KeyEvent { code: KeyCode::F(11), .. } => events.push(InputEvent::Hotkey(Hotkey::Record)),
+                KeyEvent { code: KeyCode::F(12), .. } => events.push(InputEvent::Hotkey(Hotkey::Screenshot)),
+                _ => (),
+            }
+        }
+
+        let now = Instant::now();
+        for (key, release) in self.releases.iter_mut().enumerate() {
+            if release.is_some_and(|time| now >= time) {
+                events.push(InputEvent::Key { key, pressed: false });
+                *release = None;
+            }
+        }
+
+        return Ok(events);
+    }
+}
+
+impl Input for TerminalInput {
+    fn poll(&mut self) -> Result<Vec<InputEvent>, String> {
+        return self.read_events().map_err(describe);
+    }
+
+    fn set_keymap(&mut self, keymap: &Keymap) {
+        self.keymap = *keymap;
+    }
+}
+
+impl Audio for TerminalBell {
+    fn set_tone(&mut self, playing: bool) -> Result<(), String> {
+        if playing && !self.playing {
+            let mut stdout = io::stdout();
+            queue!(stdout, style::Print(BELL)).map_err(describe)?;
+            stdout.flush().map_err(|e| e.to_string())?;
+        }
+        self.playing = playing;
+
+        return Ok(());
+    }
+}
+
+fn hotkey_for_char(c: char) -> Option<Hotkey> {
+    match c.to_ascii_lowercase() {
+        '=' | '+' => Some(Hotkey::Faster),
+        '-' => Some(Hotkey::Slower),
+        'p' => Some(Hotkey::Pause),
+        'n' => Some(Hotkey::FrameAdvance),
+        _ => None,
+    }
+}
+
+// crossterm's own descriptions don't say what actually went wrong ("IO-error occurred"), so
+// this goes by the error underneath instead, if there is one
+fn describe(error: crossterm::ErrorKind) -> String {
+    if let crossterm::ErrorKind::ResizingTerminalFailure(reason) = &error {
+        return format!("couldn't resize the terminal: {}", reason);
+    }
+
+    return match error.source() {
+        Some(source) => source.to_string(),
+        None => error.to_string(),
+    };
+}
+
+fn to_color(colour: Colour) -> Color {
+    return Color::Rgb { r: colour.red(), g: colour.green(), b: colour.blue() };
+}
+
+fn draw_screen(stdout: &mut Stdout, frame: &[Colour]) -> crossterm::Result<()> {
+    for row in 0..SCREEN_ROWS {
+        queue!(stdout, cursor::MoveTo(0, row as u16))?;
+
+        // only send colours when they change, most neighbouring cells are the same
+        let mut current = None;
+        for x in 0..MACHINE_SCREEN_WIDTH {
+            let top = frame[(row * 2) * MACHINE_SCREEN_WIDTH + x];
+            let bottom = frame[(row * 2 + 1) * MACHINE_SCREEN_WIDTH + x];
+
+            if current != Some((top, bottom)) {
+                queue!(stdout, style::SetForegroundColor(to_color(top)), style::SetBackgroundColor(to_color(bottom)))?;
+                current = Some((top, bottom));
+            }
+            queue!(stdout, style::Print(UPPER_HALF_BLOCK))?;
+        }
+    }
+
+    return Ok(());
+}
+
+fn status_lines(machine: &core::Chip8, message: &str) -> [String; STATUS_ROWS] {
+    let status = format!(
+        "PC {:04X}  I {:04X}  SP {:X}  DT {:02X}  ST {:02X}",
+        machine.pc(),
+        machine.index(),
+        machine.stack_depth(),
+        machine.delay_timer(),
+        machine.sound_timer()
+    );
+
+    let registers: Vec<String> = machine.registers().iter()
+        .enumerate()
+        .map(|(i, value)| format!("V{:X} {:02X}", i, value))
+        .collect();
+
+    return [status, registers[..8].join(" "), registers[8..].join(" "), message.to_string()];
+}
+
+fn draw_status(stdout: &mut Stdout, lines: &[String]) -> crossterm::Result<()> {
+    queue!(stdout, style::ResetColor)?;
+    for (i, line) in lines.iter().enumerate() {
+        queue!(
+            stdout,
+            cursor::MoveTo(0, (SCREEN_ROWS + i) as u16),
+            style::Print(line),
+            terminal::Clear(terminal::ClearType::UntilNewLine)
+        )?;
+    }
+
+    return Ok(());
+}
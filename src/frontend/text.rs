@@ -0,0 +1,161 @@
+use chip8_rust::colour::Colour;
+
+// a tiny 3x5 pixel font for drawing text over the game, in the spirit of the chip-8's own
+// digits. there's only upper case, lower case gets drawn as upper case and anything else
+// without a glyph comes out as a ?
+pub const GLYPH_WIDTH: usize = 3;
+pub const GLYPH_HEIGHT: usize = 5;
+
+// each glyph plus a pixel of space to the right and below
+pub const CELL_WIDTH: usize = GLYPH_WIDTH + 1;
+pub const CELL_HEIGHT: usize = GLYPH_HEIGHT + 1;
+
+// one row per byte, with the leftmost pixel in the highest of the three bits. starts at ' '
+const FIRST_GLYPH: char = ' ';
+const FONT: [[u8; GLYPH_HEIGHT]; 64] = [
+    [0b000, 0b000, 0b000, 0b000, 0b000], // ' '
+    [0b010, 0b010, 0b010, 0b000, 0b010], // !
+    [0b101, 0b101, 0b000, 0b000, 0b000], // "
+    [0b101, 0b111, 0b101, 0b111, 0b101], // #
+    [0b011, 0b110, 0b010, 0b011, 0b110], // $
+    [0b101, 0b001, 0b010, 0b100, 0b101], // %
+    [0b010, 0b101, 0b010, 0b101, 0b011], // &
+    [0b010, 0b010, 0b000, 0b000, 0b000], // '
+    [0b001, 0b010, 0b010, 0b010, 0b001], // (
+    [0b100, 0b010, 0b010, 0b010, 0b100], // )
+    [0b000, 0b101, 0b010, 0b101, 0b000], // *
+    [0b000, 0b010, 0b111, 0b010, 0b000], // +
+    [0b000, 0b000, 0b000, 0b010, 0b100], // ,
+    [0b000, 0b000, 0b111, 0b000, 0b000], // -
+    [0b000, 0b000, 0b000, 0b000, 0b010], // .
+    [0b001, 0b001, 0b010, 0b100, 0b100], // /
+    [0b111, 0b101, 0b101, 0b101, 0b111], // 0
+    [0b010, 0b110, 0b010, 0b010, 0b111], // 1
+    [0b111, 0b001, 0b111, 0b100, 0b111], // 2
+    [0b111, 0b001, 0b111, 0b001, 0b111], // 3
+    [0b101, 0b101, 0b111, 0b001, 0b001], // 4
+    [0b111, 0b100, 0b111, 0b001, 0b111], // 5
+    [0b111, 0b100, 0b111, 0b101, 0b111], // 6
+    [0b111, 0b001, 0b001, 0b001, 0b001], // 7
+    [0b111, 0b101, 0b111, 0b101, 0b111], // 8
+    [0b111, 0b101, 0b111, 0b001, 0b111], // 9
+    [0b000, 0b010, 0b000, 0b010, 0b000], // :
+    [0b000, 0b010, 0b000, 0b010, 0b100], // ;
+    [0b001, 0b010, 0b100, 0b010, 0b001], // <
+    [0b000, 0b111, 0b000, 0b111, 0b000], // =
+    [0b100, 0b010, 0b001, 0b010, 0b100], // >
+    [0b111, 0b001, 0b010, 0b000, 0b010], // ?
+    [0b111, 0b101, 0b111, 0b100, 0b111], // @
+    [0b010, 0b101, 0b111, 0b101, 0b101], // A
+    [0b110, 0b101, 0b110, 0b101, 0b110], // B
+    [0b011, 0b100, 0b100, 0b100, 0b011], // C
+    [0b110, 0b101, 0b101, 0b101, 0b110], // D
+    [0b111, 0b100, 0b110, 0b100, 0b111], // E
+    [0b111, 0b100, 0b110, 0b100, 0b100], // F
+    [0b011, 0b100, 0b101, 0b101, 0b011], // G
+    [0b101, 0b101, 0b111, 0b101, 0b101], // H
+    [0b111, 0b010, 0b010, 0b010, 0b111], // I
+    [0b001, 0b001, 0b001, 0b101, 0b010], // J
+    [0b101, 0b101, 0b110, 0b101, 0b101], // K
+    [0b100, 0b100, 0b100, 0b100, 0b111], // L
+    [0b101, 0b111, 0b111, 0b101, 0b101], // M
+    [0b110, 0b101, 0b101, 0b101, 0b101], // N
+    [0b010, 0b101, 0b101, 0b101, 0b010], // O
+    [0b110, 0b101, 0b110, 0b100, 0b100], // P
+    [0b010, 0b101, 0b101, 0b110, 0b011], // Q
+    [0b110, 0b101, 0b110, 0b101, 0b101], // R
+    [0b011, 0b100, 0b010, 0b001, 0b110], // S
+    [0b111, 0b010, 0b010, 0b010, 0b010], // T
+    [0b101, 0b101, 0b101, 0b101, 0b111], // U
+    [0b101, 0b101, 0b101, 0b101, 0b010], // V
+    [0b101, 0b101, 0b111, 0b111, 0b101], // W
+    [0b101, 0b101, 0b010, 0b101, 0b101], // X
+    [0b101, 0b101, 0b010, 0b010, 0b010], // Y
+    [0b111, 0b001, 0b010, 0b100, 0b111], // Z
+    [0b011, 0b010, 0b010, 0b010, 0b011], // [
+    [0b100, 0b100, 0b010, 0b001, 0b001], // \
+    [0b110, 0b010, 0b010, 0b010, 0b110], // ]
+    [0b010, 0b101, 0b000, 0b000, 0b000], // ^
+    [0b000, 0b000, 0b000, 0b000, 0b111]  // _
+];
+
+// how much a canvas gets scaled up by to cover a window of a given height. it's a whole number
+// so the text stays crisp
+pub fn canvas_scale(window_height: usize) -> usize {
+    return (window_height / 100).max(1);
+}
+
+// somewhere to draw text and boxes that goes over the game, transparent everywhere else
+pub struct Canvas {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Colour>
+}
+
+impl Canvas {
+    pub fn new(width: usize, height: usize) -> Self {
+        Canvas {
+            width,
+            height,
+            pixels: vec![Colour::with_alpha(0, 0, 0, 0); width * height]
+        }
+    }
+
+    pub fn clear(&mut self) {
+        for pixel in self.pixels.iter_mut() {
+            *pixel = Colour::with_alpha(0, 0, 0, 0);
+        }
+    }
+
+    // anything hanging off the edges gets cut off
+    pub fn fill_rect(&mut self, x: usize, y: usize, width: usize, height: usize, colour: Colour) {
+        let right = (x + width).min(self.width);
+        let bottom = (y + height).min(self.height);
+
+        for row in y.min(bottom)..bottom {
+            for pixel in &mut self.pixels[row * self.width + x.min(right)..row * self.width + right] {
+                *pixel = colour;
+            }
+        }
+    }
+
+    // draws text with its top left corner at x, y, on a single line
+    pub fn draw_text(&mut self, x: usize, y: usize, text: &str, colour: Colour) {
+        for (i, c) in text.chars().enumerate() {
+            let glyph = glyph(c);
+            let left = x + i * CELL_WIDTH;
+
+            for (row, bits) in glyph.iter().enumerate() {
+                for column in 0..GLYPH_WIDTH {
+                    if bits & (0b100 >> column) == 0 {
+                        continue;
+                    }
+
+                    let (px, py) = (left + column, y + row);
+                    if px < self.width && py < self.height {
+                        self.pixels[py * self.width + px] = colour;
+                    }
+                }
+            }
+        }
+    }
+
+    // a panel of text lines with a background behind, which is what the hud and menus are made
+    // of. x, y is the top left of the panel, in pixels
+    pub fn draw_panel(&mut self, x: usize, y: usize, lines: &[String], text: Colour, background: Colour) {
+        let columns = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+        let width = columns * CELL_WIDTH + 1;
+        let height = lines.len() * CELL_HEIGHT + 1;
+
+        self.fill_rect(x, y, width, height, background);
+        for (i, line) in lines.iter().enumerate() {
+            self.draw_text(x + 1, y + 1 + i * CELL_HEIGHT, line, text);
+        }
+    }
+}
+
+fn glyph(c: char) -> [u8; GLYPH_HEIGHT] {
+    let index = (c.to_ascii_uppercase() as usize).wrapping_sub(FIRST_GLYPH as usize);
+
+    return *FONT.get(index).unwrap_or(&FONT['?' as usize - FIRST_GLYPH as usize]);
+}
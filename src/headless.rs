@@ -0,0 +1,46 @@
+use crate::platform::{Display, Frame, Frontend, Input, InputEvent, Parts, Silence};
+
+// runs the interpreter without showing anything, for a set number of frames. handy for
+// taking screenshots from scripts and for tests. use Platform::step rather than run to go as
+// fast as possible instead of in real time
+pub struct Headless {
+    pub frames: u64
+}
+
+pub struct HeadlessDisplay;
+
+pub struct HeadlessInput {
+    frames_left: u64
+}
+
+impl Frontend for Headless {
+    type Display = HeadlessDisplay;
+    type Input = HeadlessInput;
+    type Audio = Silence;
+
+    fn open(self) -> Result<Parts<Self>, String> {
+        return Ok(Parts {
+            display: HeadlessDisplay,
+            input: HeadlessInput { frames_left: self.frames },
+            audio: Silence
+        });
+    }
+}
+
+impl Display for HeadlessDisplay {
+    fn present(&mut self, _frame: &Frame) -> Result<(), String> {
+        return Ok(());
+    }
+}
+
+impl Input for HeadlessInput {
+    fn poll(&mut self) -> Result<Vec<InputEvent>, String> {
+        if self.frames_left == 0 {
+            return Ok(vec![InputEvent::Quit]);
+        }
+
+        self.frames_left -= 1;
+
+        return Ok(vec![]);
+    }
+}
@@ -0,0 +1,107 @@
+use std::convert::TryFrom;
+use serde::Deserialize;
+
+// the chip-8's keypad is a 4x4 grid:
+// 1 2 3 C
+// 4 5 6 D
+// 7 8 9 E
+// A 0 B F
+// which by default gets mapped to the same shaped block on the left of a qwerty keyboard:
+// 1 2 3 4
+// Q W E R
+// A S D F
+// Z X C V
+// this should hopefully fit enough people's needs, or at least just work for me. other layouts
+// keep the block in the same place on the keyboard
+pub const LAYOUTS: [(&str, &str); 4] = [
+    ("qwerty", "1234QWERASDFZXCV"),
+    ("qwertz", "1234QWERASDFYXCV"),
+    ("azerty", "1234AZERQSDFWXCV"),
+    ("colemak", "1234QWFPARSTZXCD")
+];
+
+// the chip-8 key for each spot in the block above, reading left to right and top to bottom
+const GRID: [usize; 16] = [
+    0x1, 0x2, 0x3, 0xC,
+    0x4, 0x5, 0x6, 0xD,
+    0x7, 0x8, 0x9, 0xE,
+    0xA, 0x0, 0xB, 0xF
+];
+
+// which keyboard key goes with each chip-8 key. frontends work in terms of the character on
+// the key, so only letters and digits are any use here
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Keymap {
+    labels: [char; 16]
+}
+
+impl Keymap {
+    pub fn layout(name: &str) -> Option<Self> {
+        let (_, block) = LAYOUTS.iter().find(|(layout, _)| *layout == name)?;
+
+        return Some(Keymap::from_block(block));
+    }
+
+    // a layout name, or the 16 keys of the block reading left to right and top to bottom,
+    // e.g. 1234QWERASDFZXCV for qwerty
+    pub fn parse(value: &str) -> Result<Self, String> {
+        if let Some(keymap) = Keymap::layout(value) {
+            return Ok(keymap);
+        }
+
+        let block: Vec<char> = value.chars().map(|c| c.to_ascii_uppercase()).collect();
+        if block.len() != 16 || !block.iter().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(format!("expected a keyboard layout or 16 letters and digits, got {}", value));
+        }
+
+        for (i, c) in block.iter().enumerate() {
+            if block[..i].contains(c) {
+                return Err(format!("{} is used for more than one key in {}", c, value));
+            }
+        }
+
+        return Ok(Keymap::from_block(&block.iter().collect::<String>()));
+    }
+
+    fn from_block(block: &str) -> Self {
+        let mut labels = [' '; 16];
+        for (c, key) in block.chars().zip(GRID.iter()) {
+            labels[*key] = c;
+        }
+
+        return Keymap { labels };
+    }
+
+    // the name of the layout this matches, if it's one of the built in ones
+    pub fn name(&self) -> Option<&'static str> {
+        return LAYOUTS.iter()
+            .map(|(name, _)| *name)
+            .find(|name| Keymap::layout(name).as_ref() == Some(self));
+    }
+
+    // the keyboard key for a chip-8 key, as an upper case letter or a digit
+    pub fn label(&self, key: usize) -> char {
+        return self.labels[key];
+    }
+
+    pub fn key_for_label(&self, label: char) -> Option<usize> {
+        let label = label.to_ascii_uppercase();
+
+        return self.labels.iter().position(|key_label| *key_label == label);
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap::layout("qwerty").unwrap()
+    }
+}
+
+impl TryFrom<String> for Keymap {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Keymap::parse(&value)
+    }
+}
@@ -0,0 +1,19 @@
+#![allow(clippy::needless_return)]
+
+pub mod assembler;
+pub mod colour;
+pub mod config;
+pub mod core;
+pub mod headless;
+pub mod keymap;
+pub mod menu;
+pub mod palette;
+pub mod persistence;
+pub mod platform;
+pub mod postfx;
+pub mod recording;
+pub mod romdb;
+pub mod screenshot;
+pub mod script;
+pub mod trace;
+pub mod watcher;
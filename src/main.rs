@@ -1,142 +1,192 @@
-mod core;
-mod colour;
+#![allow(clippy::needless_return)]
+
+mod cli;
 
 #[macro_use] extern crate gfx;
 
 extern crate gfx_window_glutin;
 extern crate glutin;
 
+mod frontend;
+
 use std::env;
-use std::time::SystemTime;
-use gfx::traits::FactoryExt;
-use gfx::Device;
-use gfx_window_glutin as gfx_glutin;
+use std::path::Path;
 
-pub type ColourFormat = gfx::format::Srgba8;
-pub type DepthFormat = gfx::format::DepthStencil;
+use chip8_rust::{config, core, romdb};
+use chip8_rust::core::MACHINE_SCREEN_WIDTH;
+use chip8_rust::core::MACHINE_SCREEN_HEIGHT;
 
-use crate::core::MACHINE_SCREEN_WIDTH;
-use crate::core::MACHINE_SCREEN_HEIGHT;
+use chip8_rust::headless::Headless;
+use chip8_rust::keymap::Keymap;
+use chip8_rust::platform::{Audio, Display, Exit, Frontend, Input, Platform};
+use chip8_rust::trace::Tracer;
+use frontend::framebuffer::FramebufferWindow;
+use frontend::gpu::GpuWindow;
+use frontend::terminal::TerminalFrontend;
 
-const SCREEN_MULTIPLIER: u32 = 10;
-const SCREEN_WIDTH: u32 = MACHINE_SCREEN_WIDTH as u32 * SCREEN_MULTIPLIER;
-const SCREEN_HEIGHT: u32 = MACHINE_SCREEN_HEIGHT as u32 * SCREEN_MULTIPLIER;
-const SCREEN_CLEAR_COLOUR: [f32; 4] = [0.0, 0.0, 0.0, 0.0];
+const DEFAULT_WINDOW_TITLE: &str = "Chip8 Interpreter";
 
 fn main() {
-    let mut machine = core::Chip8::new();
-    let args: Vec<String> = env::args().collect();
-
-    match machine.load_rom(&args[1]) {
-        Err(e) => println!("error: {}", e),
-        Ok(()) => {
-            let mut platform = Platform::new(
-                SCREEN_WIDTH,
-                SCREEN_HEIGHT,
-                machine
-            );
-
-            let white = colour::Colour::new(255, 255, 255);
-
-            // set up windowing/graphics stuffs
-            let events_loop = glutin::EventsLoop::new();
-            let builder = glutin::WindowBuilder::new()
-                .with_title("Chip8 Interpreter")
-                .with_dimensions(SCREEN_WIDTH, SCREEN_HEIGHT)
-                .with_vsync();
-
-            let (window, mut device, mut factory, mut main_colour, mut main_depth) =
-                gfx_glutin::init::<ColourFormat, DepthFormat>(builder, &events_loop);
-
-            let mut encoder: gfx::Encoder<_, _> = factory.create_command_buffer().into();
-            // read in the shaders
-            let pso = factory.create_pipeline_simple(
-                include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/shaders/vertex.glsl")),
-                include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/shaders/fragment.glsl")),
-                pipe::new()
-            ).unwrap();
-
-            let mut vertices: Vec<Vertex> = vec![];
-            let mut indices: Vec<u16> = vec![];
-            let (vertex_buffer, mut slice) = factory.create_vertex_buffer_with_slice(&vertices, &*indices);
-            let mut data = pipe::Data {
-                vbuf: vertex_buffer,
-                out: main_colour
-            };
-
-
-            let mut last_cycle = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_micros();
-            let mut running = true;
-
-            while running {
-                // main program loop
-                platform.process_input();
-
-                let current_time = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_micros();
-                let duration = current_time - last_cycle;
-                if duration > 10 {
-                    last_cycle = current_time;
-
-                    // handle window events
-                    events_loop.poll_events(|glutin::Event::WindowEvent { window_id: _, event }| {
-                        use glutin::WindowEvent::*;
-                        match event {
-                            Closed => running = false,
-                            _ => (),
-                        }
-                    });
-
-                    platform.machine.cycle();
-
-                    // process video stuffs
-                    encoder.clear(&data.out, SCREEN_CLEAR_COLOUR);
-                    encoder.draw(&slice, &pso, &data);
-                    encoder.flush(&mut device);
-                    window.swap_buffers().unwrap();
-                    device.cleanup();
-                }
-            }
+    let args = match cli::Args::parse(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            println!("error: {}\n\n{}", e, cli::USAGE);
+            return;
+        },
+    };
+
+    // only the windows have the menu to pick a rom from
+    let has_menu = match args.frontend {
+        cli::FrontendKind::Gpu | cli::FrontendKind::Minifb => true,
+        cli::FrontendKind::Terminal | cli::FrontendKind::Headless => false,
+    };
+    if args.rom_path.is_none() && !has_menu {
+        println!("error: no rom given\n\n{}", cli::USAGE);
+        return;
+    }
+
+    let database = load_database();
+    let (machine, settings) = match load_machine(&args, &database, args.rom_path.as_deref()) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            println!("error: {}", e);
+            return;
         },
     };
+
+    let title = window_title(&settings);
+    let width = MACHINE_SCREEN_WIDTH as u32 * settings.scale;
+    let height = MACHINE_SCREEN_HEIGHT as u32 * settings.scale;
+
+    let result = match args.frontend {
+        cli::FrontendKind::Gpu => run(machine, &settings, &args, database, GpuWindow { title, width, height }),
+        cli::FrontendKind::Minifb => run(machine, &settings, &args, database, FramebufferWindow { title, width, height }),
+        cli::FrontendKind::Terminal => run(machine, &settings, &args, database, TerminalFrontend { key_timeout: settings.key_timeout }),
+        cli::FrontendKind::Headless => run(machine, &settings, &args, database, Headless { frames: args.frames }),
+    };
+
+    if let Err(e) = result {
+        println!("error: {}", e);
+    }
+}
+
+fn run_until_exit<D: Display, I: Input, A: Audio>(platform: &mut Platform<D, I, A>, args: &cli::Args) -> Result<(), String> {
+    // there's nobody watching when headless, so there's no point waiting around between frames
+    match args.frontend {
+        cli::FrontendKind::Headless => while platform.step()? {},
+        _ => while let Exit::Load(path) = platform.run()? {
+            match load_machine(args, &platform.database, Some(&path)) {
+                Ok((machine, settings)) => {
+                    platform.display.set_title(&window_title(&settings));
+                    platform.load(machine, &settings, path);
+                },
+                Err(e) => platform.display.show_message(&e),
+            }
+        },
+    }
+
+    return Ok(());
+}
+
+fn run<F: Frontend>(machine: core::Chip8, settings: &config::Settings, args: &cli::Args, database: romdb::RomDatabase, frontend: F) -> Result<(), String> {
+    let mut platform = Platform::from_frontend(machine, settings, frontend)?;
+    platform.rom_path = args.rom_path.clone();
+    platform.database = database;
+    if platform.rom_path.is_none() {
+        platform.open_menu();
+    }
+
+    if let Some(path) = &args.record_path {
+        platform.start_recording(path)?;
+    }
+
+    if let Some(path) = &args.trace_path {
+        let tracer = Tracer::create(path, args.trace_options.clone())
+            .map_err(|e| format!("couldn't create {}: {}", path.display(), e))?;
+        platform.tracer = Some(tracer);
+    }
+
+    // the trace is most useful when something's gone wrong, so it gets finished either way
+    let result = run_until_exit(&mut platform, args);
+    if let Some(tracer) = platform.tracer.take() {
+        let path = tracer.path().to_path_buf();
+        tracer.finish().map_err(|e| format!("couldn't write {}: {}", path.display(), e))?;
+    }
+    result?;
+
+    platform.stop_recording()?;
+
+    if let Some(path) = &args.screenshot_path {
+        platform.save_screenshot(path)?;
+    }
+
+    return Ok(());
+}
+
+fn load_database() -> romdb::RomDatabase {
+    match romdb::RomDatabase::default_dir() {
+        Some(dir) if dir.exists() => romdb::RomDatabase::load(&dir).unwrap_or_else(|e| {
+            println!("warning: couldn't load rom database, using the built in one: {}", e);
+            romdb::RomDatabase::embedded()
+        }),
+        _ => romdb::RomDatabase::embedded(),
+    }
 }
 
-gfx_defines! {
-    vertex Vertex {
-        pos: [f32; 2] = "a_Pos",
-        colour: [f32; 3] = "a_Colour",
+// a fresh machine with the rom at rom_path loaded, if there is one, and the settings to run it
+// with
+fn load_machine(args: &cli::Args, database: &romdb::RomDatabase, rom_path: Option<&Path>) -> Result<(core::Chip8, config::Settings), String> {
+    let mut machine = core::Chip8::new();
+    if let Some(path) = rom_path {
+        machine.load_rom(path).map_err(|e| format!("couldn't load {}: {}", path.display(), e))?;
+    }
+
+    let rom_info = database.lookup(machine.rom_hash());
+    let database_overrides = rom_info.as_ref().map(|info| &info.overrides);
+    let settings = load_settings(args, machine.rom_hash(), database_overrides)?;
+
+    if let Some(info) = &rom_info {
+        print_rom_info(info, &settings.keymap);
     }
 
-    pipeline pipe {
-        vbuf: gfx::VertexBuffer<Vertex> = (),
-        out: gfx::RenderTarget<ColourFormat> = "Target0",
+    machine.quirks = settings.quirks;
+    machine.tick_rate = settings.tick_rate;
+    if let Some(seed) = args.seed {
+        machine.seed_random(seed);
     }
+
+    return Ok((machine, settings));
 }
 
-struct Platform {
-    screen_width: u32,
-    screen_height: u32,
-    buffer: Vec<u8>,
-    machine: core::Chip8
+fn window_title(settings: &config::Settings) -> String {
+    return settings.title.clone().unwrap_or_else(|| DEFAULT_WINDOW_TITLE.to_string());
 }
 
-impl Platform {
-    fn new(screen_width: u32, screen_height: u32, machine: core::Chip8) -> Platform {
-        let buffer = vec![0; (screen_height * screen_width) as usize + 1];
-        Platform {
-            screen_width,
-            screen_height,
-            buffer,
-            machine
-        }
+fn print_rom_info(info: &romdb::RomInfo, keymap: &Keymap) {
+    match info.platform {
+        Some(platform) => println!("{} ({})", info.title, platform.name),
+        None => println!("{}", info.title),
     }
 
-    // we will map the key inputs as such:
-    // 1 2 3 4
-    // Q W E R
-    // A S D F
-    // Z X C V
-    // this should hopefully fit enough people's needs, or at least just work for me
-    fn process_input(&mut self) {
-        // TODO: implement
+    let hints: Vec<String> = info.keys.iter()
+        .filter(|(_, key)| *key < 16)
+        .map(|(name, key)| format!("{} = {}", name, keymap.label(*key as usize)))
+        .collect();
+    if !hints.is_empty() {
+        println!("controls: {}", hints.join(", "));
     }
 }
+
+fn load_settings(args: &cli::Args, rom_hash: &str, database: Option<&config::Overrides>) -> Result<config::Settings, String> {
+    let path = match &args.config_path {
+        Some(path) => Some(path.clone()),
+        None => config::Config::default_path(),
+    };
+
+    let config = match path {
+        Some(path) => config::Config::load(&path).map_err(|e| e.to_string())?,
+        None => config::Config::default(),
+    };
+
+    return config.resolve(rom_hash, database, &args.overrides);
+}
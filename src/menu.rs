@@ -0,0 +1,295 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::assembler;
+use crate::core::Quirks;
+use crate::romdb::RomDatabase;
+
+// files bigger than this can't be chip-8 roms, so the browser doesn't bother listing them,
+// unless they're source to be assembled
+const MAX_ROM_SIZE: u64 = 4096 - 0x200;
+
+pub const QUIRKS: [&str; 5] = ["shift", "load_store", "jump", "vf_reset", "clip"];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MenuInput {
+    Up,
+    Down,
+    Left,
+    Right,
+    Select,
+    Back
+}
+
+// what the menu wants done, which is up to the platform since it owns everything involved
+#[derive(Clone, Debug, PartialEq)]
+pub enum MenuAction {
+    Close,
+    Load(PathBuf),
+    Reset,
+    SoftReset,
+    Reload,
+    Quirk(usize), // toggle QUIRKS[n]
+    Palette(bool), // true for the next one, false for the previous one
+    Speed(bool),   // true for faster
+    Keymap(bool),
+    Quit
+}
+
+// the current values of everything the menu can change, to show next to them
+pub struct MenuValues<'a> {
+    pub palette: &'a str,
+    pub speed: f64,
+    pub keymap: &'a str,
+    pub quirks: Quirks
+}
+
+// everything a frontend needs to draw the menu
+pub struct MenuView {
+    pub title: String,
+    pub items: Vec<String>,
+    pub selected: usize
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Page {
+    Main,
+    Roms,
+    Quirks
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum MainItem {
+    Resume,
+    LoadRom,
+    Reset,
+    SoftReset,
+    Reload,
+    Quirks,
+    Palette,
+    Speed,
+    Keymap,
+    Quit
+}
+
+const MAIN_ITEMS: [MainItem; 10] = [
+    MainItem::Resume,
+    MainItem::LoadRom,
+    MainItem::Reset,
+    MainItem::SoftReset,
+    MainItem::Reload,
+    MainItem::Quirks,
+    MainItem::Palette,
+    MainItem::Speed,
+    MainItem::Keymap,
+    MainItem::Quit
+];
+
+struct RomEntry {
+    name: String, // the title from the database if it's in there, otherwise the file name
+    path: PathBuf,
+    is_dir: bool
+}
+
+// the menu Esc brings up in the windows. up and down move, enter picks, left and right change
+// values (or go up a directory in the rom browser) and backspace goes back. it only keeps
+// track of where the user is, the platform applies whatever they pick
+pub struct Menu {
+    page: Page,
+    selected: usize,
+    dir: PathBuf,
+    entries: Vec<RomEntry>,
+    error: Option<String> // from reading the current directory
+}
+
+impl Menu {
+    pub fn new(dir: PathBuf) -> Self {
+        Menu {
+            page: Page::Main,
+            selected: 0,
+            dir,
+            entries: vec![],
+            error: None
+        }
+    }
+
+    pub fn show_main(&mut self) {
+        self.page = Page::Main;
+        self.selected = 0;
+    }
+
+    // straight to the rom browser, for when there's nothing loaded yet
+    pub fn show_roms(&mut self, database: &RomDatabase) {
+        self.page = Page::Roms;
+        self.selected = 0;
+        self.read_dir(database);
+    }
+
+    pub fn input(&mut self, input: MenuInput, database: &RomDatabase) -> Option<MenuAction> {
+        let count = self.item_count();
+        match input {
+            MenuInput::Up => self.selected = (self.selected + count - 1) % count,
+            MenuInput::Down => self.selected = (self.selected + 1) % count,
+            MenuInput::Back if self.page == Page::Main => return Some(MenuAction::Close),
+            MenuInput::Back => self.show_main(),
+            MenuInput::Left | MenuInput::Right | MenuInput::Select => {
+                let forward = input != MenuInput::Left;
+                match self.page {
+                    Page::Main => return self.main_input(MAIN_ITEMS[self.selected], input, forward, database),
+                    Page::Roms if input == MenuInput::Select => return self.open_entry(database),
+                    Page::Roms if input == MenuInput::Left => self.up_dir(database),
+                    Page::Roms => (),
+                    Page::Quirks if self.selected == QUIRKS.len() => self.show_main(),
+                    Page::Quirks => return Some(MenuAction::Quirk(self.selected)),
+                }
+            },
+        }
+
+        return None;
+    }
+
+    pub fn view(&self, values: &MenuValues) -> MenuView {
+        let on_off = |on: bool| if on { "on" } else { "off" };
+
+        let (title, items) = match self.page {
+            Page::Main => {
+                let items = MAIN_ITEMS.iter().map(|item| match item {
+                    MainItem::Resume => "resume".to_string(),
+                    MainItem::LoadRom => "load rom".to_string(),
+                    MainItem::Reset => "reset".to_string(),
+                    MainItem::SoftReset => "soft reset".to_string(),
+                    MainItem::Reload => "reload rom".to_string(),
+                    MainItem::Quirks => "quirks".to_string(),
+                    MainItem::Palette => format!("palette  < {} >", values.palette),
+                    MainItem::Speed => format!("speed  < {}x >", values.speed),
+                    MainItem::Keymap => format!("keys  < {} >", values.keymap),
+                    MainItem::Quit => "quit".to_string(),
+                }).collect();
+                ("chip-8".to_string(), items)
+            },
+            Page::Roms => {
+                let mut items: Vec<String> = self.entries.iter()
+                    .map(|entry| if entry.is_dir { format!("{}/", entry.name) } else { entry.name.clone() })
+                    .collect();
+                if let Some(error) = &self.error {
+                    items.push(error.clone());
+                } else if items.is_empty() {
+                    items.push("no roms here".to_string());
+                }
+                (self.dir.display().to_string(), items)
+            },
+            Page::Quirks => {
+                let quirks = values.quirks;
+                let enabled = [quirks.shift, quirks.load_store, quirks.jump, quirks.vf_reset, quirks.clip];
+                let mut items: Vec<String> = QUIRKS.iter().zip(enabled.iter())
+                    .map(|(name, on)| format!("{}  < {} >", name, on_off(*on)))
+                    .collect();
+                items.push("back".to_string());
+                ("quirks".to_string(), items)
+            },
+        };
+
+        let selected = self.selected.min(items.len().saturating_sub(1));
+
+        return MenuView { title, items, selected };
+    }
+
+    fn item_count(&self) -> usize {
+        let count = match self.page {
+            Page::Main => MAIN_ITEMS.len(),
+            Page::Roms => self.entries.len(),
+            Page::Quirks => QUIRKS.len() + 1,
+        };
+
+        return count.max(1);
+    }
+
+    fn up_dir(&mut self, database: &RomDatabase) {
+        if let Some(dir) = self.dir.parent() {
+            let dir = dir.to_path_buf();
+            self.change_dir(dir, database);
+        }
+    }
+
+    fn main_input(&mut self, item: MainItem, input: MenuInput, forward: bool, database: &RomDatabase) -> Option<MenuAction> {
+        match item {
+            MainItem::Palette => return Some(MenuAction::Palette(forward)),
+            MainItem::Speed => return Some(MenuAction::Speed(forward)),
+            MainItem::Keymap => return Some(MenuAction::Keymap(forward)),
+            _ if input != MenuInput::Select => return None,
+            MainItem::Resume => return Some(MenuAction::Close),
+            MainItem::LoadRom => self.show_roms(database),
+            MainItem::Reset => return Some(MenuAction::Reset),
+            MainItem::SoftReset => return Some(MenuAction::SoftReset),
+            MainItem::Reload => return Some(MenuAction::Reload),
+            MainItem::Quirks => {
+                self.page = Page::Quirks;
+                self.selected = 0;
+            },
+            MainItem::Quit => return Some(MenuAction::Quit),
+        }
+
+        return None;
+    }
+
+    fn open_entry(&mut self, database: &RomDatabase) -> Option<MenuAction> {
+        let entry = self.entries.get(self.selected)?;
+        if entry.is_dir {
+            let dir = entry.path.clone();
+            self.change_dir(dir, database);
+            return None;
+        }
+
+        return Some(MenuAction::Load(entry.path.clone()));
+    }
+
+    fn change_dir(&mut self, dir: PathBuf, database: &RomDatabase) {
+        self.dir = dir;
+        self.selected = 0;
+        self.read_dir(database);
+    }
+
+    // lists the directories and anything that could be a rom, directories first
+    fn read_dir(&mut self, database: &RomDatabase) {
+        // relative paths have no parent to go up to once they run out
+        if let Ok(dir) = self.dir.canonicalize() {
+            self.dir = dir;
+        }
+
+        self.entries.clear();
+        self.error = None;
+
+        let read = match fs::read_dir(&self.dir) {
+            Ok(read) => read,
+            Err(e) => {
+                self.error = Some(format!("couldn't read this directory: {}", e));
+                return;
+            },
+        };
+
+        for entry in read.filter_map(|entry| entry.ok()) {
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            let metadata = match entry.metadata() {
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+            let path = entry.path();
+            let too_big = metadata.is_file() && metadata.len() > MAX_ROM_SIZE && !assembler::is_source(&path);
+            if file_name.starts_with('.') || too_big {
+                continue;
+            }
+
+            let name = if metadata.is_file() { rom_title(&path, database).unwrap_or(file_name) } else { file_name };
+            self.entries.push(RomEntry { name, path, is_dir: metadata.is_dir() });
+        }
+
+        self.entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase())));
+    }
+}
+
+fn rom_title(path: &Path, database: &RomDatabase) -> Option<String> {
+    let contents = fs::read(path).ok()?;
+    let hash = sha1_smol::Sha1::from(&contents).digest().to_string();
+
+    return database.lookup(&hash).map(|info| info.title.to_string());
+}
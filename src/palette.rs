@@ -0,0 +1,112 @@
+use std::convert::TryFrom;
+use serde::Deserialize;
+
+use crate::colour::Colour;
+
+// built in themes, in the order the palette hotkey cycles through them
+pub const THEMES: [&str; 5] = ["classic", "green", "amber", "gameboy", "high-contrast"];
+
+// what a palette can be written as in config files: a theme name or a list of colours as a
+// string, or an array of colours
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PaletteRepr {
+    Text(String),
+    Colours(Vec<Colour>)
+}
+
+// maps the values in Chip8::video to colours. entry 0 is the background and entry 1 the
+// foreground. xo-chip draws to two bit planes so it needs four entries, and sixteen leaves
+// room for four planes
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(try_from = "PaletteRepr")]
+pub struct Palette {
+    colours: Vec<Colour>
+}
+
+impl Palette {
+    pub fn from_colours(colours: Vec<Colour>) -> Result<Self, String> {
+        match colours.len() {
+            2 | 4 | 16 => Ok(Palette { colours }),
+            n => Err(format!("a palette needs 2, 4 or 16 colours, not {}", n)),
+        }
+    }
+
+    pub fn theme(name: &str) -> Option<Self> {
+        let hex: &[&str] = match name {
+            "classic" => &["#000000", "#FFFFFF", "#AAAAAA", "#555555"],
+            "green" => &["#0A1A0A", "#33FF66", "#1F9940", "#145C28"],
+            "amber" => &["#1A1000", "#FFB000", "#B37B00", "#664600"],
+            "gameboy" => &["#9BBC0F", "#0F380F", "#306230", "#8BAC0F"],
+            "high-contrast" => &["#000000", "#FFFF00", "#00FFFF", "#FF00FF"],
+            _ => return None,
+        };
+
+        let colours = hex.iter().filter_map(|hex| Colour::from_hex(hex)).collect();
+
+        return Some(Palette { colours });
+    }
+
+    // either the name of a theme or a comma separated list of colours
+    pub fn parse(value: &str) -> Result<Self, String> {
+        if let Some(palette) = Palette::theme(value) {
+            return Ok(palette);
+        }
+
+        let colours = value.split(',')
+            .map(|text| Colour::parse(text.trim()).ok_or_else(|| format!("invalid colour: {}", text)))
+            .collect::<Result<Vec<Colour>, String>>()?;
+
+        return Palette::from_colours(colours);
+    }
+
+    pub fn background(&self) -> Colour {
+        return self.colours[0];
+    }
+
+    pub fn set_background(&mut self, colour: Colour) {
+        self.colours[0] = colour;
+    }
+
+    pub fn set_foreground(&mut self, colour: Colour) {
+        self.colours[1] = colour;
+    }
+
+    // the name of the theme this matches, if it's one of the built in ones
+    pub fn theme_name(&self) -> Option<&'static str> {
+        return THEMES.iter().copied().find(|name| Palette::theme(name).as_ref() == Some(self));
+    }
+
+    pub fn colour_count(&self) -> usize {
+        return self.colours.len();
+    }
+
+    // pixel is a value straight out of Chip8::video, which has one bit per plane
+    pub fn colour(&self, pixel: u32) -> Colour {
+        return self.colours[pixel as usize % self.colours.len()];
+    }
+
+    // a colour for every pixel in video, with any translucent ones blended over the background
+    pub fn colourise(&self, video: &[u32]) -> Vec<Colour> {
+        let background = self.background();
+
+        return video.iter().map(|pixel| self.colour(*pixel).blend_over(background)).collect();
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::theme("classic").unwrap()
+    }
+}
+
+impl TryFrom<PaletteRepr> for Palette {
+    type Error = String;
+
+    fn try_from(repr: PaletteRepr) -> Result<Self, Self::Error> {
+        match repr {
+            PaletteRepr::Text(text) => Palette::parse(&text),
+            PaletteRepr::Colours(colours) => Palette::from_colours(colours),
+        }
+    }
+}
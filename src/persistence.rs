@@ -0,0 +1,107 @@
+use std::convert::TryFrom;
+use serde::Deserialize;
+
+use crate::colour::Colour;
+use crate::palette::Palette;
+
+const DEFAULT_DECAY: f32 = 0.6;
+
+// most games erase sprites by drawing them again, so a moving sprite spends every other frame
+// switched off and flickers. these smooth that over when the screen is drawn, without
+// touching what the interpreter itself sees in video
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Persistence {
+    #[default]
+    Off,
+    Or,         // a pixel is lit if it was lit in either of the last two frames
+    Decay(f32)  // pixels fade out, keeping this much of their brightness each frame
+}
+
+impl Persistence {
+    // the modes the persistence hotkey cycles through
+    pub fn modes() -> [Persistence; 3] {
+        return [Persistence::Off, Persistence::Or, Persistence::Decay(DEFAULT_DECAY)];
+    }
+
+    // off, or, decay, or decay=<amount kept each frame>
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let mut parts = value.splitn(2, '=');
+        let mode = match (parts.next().unwrap_or(""), parts.next()) {
+            ("off", None) => Persistence::Off,
+            ("or", None) => Persistence::Or,
+            ("decay", None) => Persistence::Decay(DEFAULT_DECAY),
+            ("decay", Some(decay)) => {
+                let decay: f32 = decay.parse().map_err(|_| format!("invalid decay: {}", decay))?;
+                if !(0.0..1.0).contains(&decay) {
+                    return Err("decay must be at least 0 and less than 1".to_string());
+                }
+                Persistence::Decay(decay)
+            },
+            _ => return Err(format!("unknown persistence mode: {}", value)),
+        };
+
+        return Ok(mode);
+    }
+}
+
+impl TryFrom<String> for Persistence {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Persistence::parse(&value)
+    }
+}
+
+// remembers as much of the previous frames as the mode needs. feed it every emulated frame,
+// since decay is counted in frames
+pub struct PersistenceFilter {
+    pub mode: Persistence,
+    previous_video: Vec<u32>,
+    previous_frame: Vec<Colour>
+}
+
+impl PersistenceFilter {
+    pub fn new(mode: Persistence) -> Self {
+        PersistenceFilter {
+            mode,
+            previous_video: vec![],
+            previous_frame: vec![]
+        }
+    }
+
+    pub fn apply(&mut self, video: &[u32], palette: &Palette) -> Vec<Colour> {
+        let frame = match self.mode {
+            Persistence::Off => palette.colourise(video),
+            Persistence::Or => {
+                let combined: Vec<u32> = if self.previous_video.len() == video.len() {
+                    video.iter().zip(&self.previous_video).map(|(current, previous)| current | previous).collect()
+                } else {
+                    video.to_vec()
+                };
+                palette.colourise(&combined)
+            },
+            Persistence::Decay(decay) => {
+                let background = palette.background();
+                let mut frame = palette.colourise(video);
+                if self.previous_frame.len() == frame.len() {
+                    // anything switched off fades from wherever it was last frame
+                    for (colour, previous) in frame.iter_mut().zip(&self.previous_frame) {
+                        if *colour == background {
+                            // rounding can leave the fade stuck just short of the background
+                            let faded = previous.lerp(background, 1.0 - decay);
+                            *colour = if faded == *previous { background } else { faded };
+                        }
+                    }
+                }
+                frame
+            },
+        };
+
+        self.previous_video.clear();
+        self.previous_video.extend_from_slice(video);
+        self.previous_frame.clone_from(&frame);
+
+        return frame;
+    }
+}
@@ -0,0 +1,753 @@
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::assembler;
+use crate::colour::Colour;
+use crate::config::Settings;
+use crate::core::{Chip8, ExecutionError, MACHINE_SCREEN_WIDTH, MACHINE_SCREEN_HEIGHT};
+use crate::keymap::{self, Keymap};
+use crate::menu::{self, Menu, MenuAction, MenuInput, MenuValues, MenuView};
+use crate::palette::{self, Palette};
+use crate::persistence::{Persistence, PersistenceFilter};
+use crate::postfx::{self, Effects};
+use crate::recording::{Recorder, RecordingFormat};
+use crate::romdb::RomDatabase;
+use crate::screenshot;
+use crate::trace::Tracer;
+use crate::watcher::{WatchMode, Watcher};
+
+// the machine runs one frame at a time, 60 times a second
+pub const FRAME_DURATION: Duration = Duration::from_micros(16_667);
+
+// how many emulated frames run for each real one, picked between with = and -
+pub const SPEEDS: [f64; 6] = [0.25, 0.5, 1.0, 2.0, 4.0, 8.0];
+const NORMAL_SPEED: usize = 2;
+
+// the chip-8's own keys are set up in keymap.rs. the function keys are left over for controlling the interpreter itself:
+// F1 - show or hide the hud
+// F2 - cycle through palettes
+// F3 - cycle through post processing effects
+// F4 - cycle through flicker reduction modes
+// F5 - reset, putting the machine back how it was when the rom was loaded
+// F6 - soft reset, starting the program again but keeping memory and the screen
+// F11 - start or stop recording
+// F12 - save a screenshot
+// Esc - open the menu, see menu.rs
+// along with a few keys the chip-8 doesn't use for controlling the speed:
+// Tab - fast forward as fast as the machine will go
+// = and - - speed up and slow down
+// P - pause
+// N - advance a single frame, pausing if it isn't already
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Hotkey {
+    Hud,
+    Palette,
+    Effects,
+    Persistence,
+    Reset,
+    SoftReset,
+    Record,
+    Screenshot,
+    FastForward,
+    Faster,
+    Slower,
+    Pause,
+    FrameAdvance,
+    Menu
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InputEvent {
+    Key { key: usize, pressed: bool },
+    Hotkey(Hotkey),
+    Menu(MenuInput), // only does anything while the menu is open
+    Quit
+}
+
+// why Platform::run stopped
+#[derive(Clone, Debug, PartialEq)]
+pub enum Exit {
+    Quit,
+    Load(PathBuf) // a rom was picked from the menu, see Platform::load
+}
+
+// everything a display needs to show one frame
+pub struct Frame<'a> {
+    // the screen with the palette and flicker reduction already applied,
+    // MACHINE_SCREEN_WIDTH * MACHINE_SCREEN_HEIGHT colours
+    pub pixels: &'a [Colour],
+    pub effects: Effects,
+    pub machine: &'a Chip8,
+    pub stats: Stats,
+    pub hud: bool, // whether to show the stats and machine state over the game
+    pub menu: Option<MenuView> // if the menu is open
+}
+
+// how things are actually running, as opposed to how they're meant to be
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Stats {
+    pub fps: f64,   // frames shown a second
+    pub speed: f64, // emulated frames a second, as a multiple of 60
+    pub paused: bool,
+    pub fast_forward: bool
+}
+
+// stats are averaged over this long so they don't flicker too much to read
+const STATS_INTERVAL: Duration = Duration::from_millis(500);
+
+pub trait Display {
+    fn present(&mut self, frame: &Frame) -> Result<(), String>;
+
+    // lets the user know something happened, like a screenshot being saved
+    fn show_message(&mut self, message: &str) {
+        println!("{}", message);
+    }
+
+    // for when a different rom gets loaded
+    fn set_title(&mut self, _title: &str) {}
+}
+
+pub trait Input {
+    // whatever's happened since the last time this was called. this shouldn't block
+    fn poll(&mut self) -> Result<Vec<InputEvent>, String>;
+
+    // which keyboard keys to use for the chip-8's keys from now on
+    fn set_keymap(&mut self, _keymap: &Keymap) {}
+}
+
+pub trait Audio {
+    // called every frame with whether the sound timer is running
+    fn set_tone(&mut self, playing: bool) -> Result<(), String>;
+}
+
+// for when there's nothing to play sound on
+pub struct Silence;
+
+impl Audio for Silence {
+    fn set_tone(&mut self, _playing: bool) -> Result<(), String> {
+        return Ok(());
+    }
+}
+
+// a display, input and audio that come as a set. they usually share something behind the
+// scenes, like a window that frames are drawn to and keys come from, so they're opened
+// together
+pub trait Frontend {
+    type Display: Display;
+    type Input: Input;
+    type Audio: Audio;
+
+    fn open(self) -> Result<Parts<Self>, String>;
+}
+
+pub struct Parts<F: Frontend + ?Sized> {
+    pub display: F::Display,
+    pub input: F::Input,
+    pub audio: F::Audio
+}
+
+// the machine, plus everything around it that turns it into something playable
+pub struct Platform<D: Display, I: Input, A: Audio> {
+    pub machine: Chip8,
+    pub display: D,
+    pub input: I,
+    pub audio: A,
+    pub rom_path: Option<PathBuf>, // where the machine's rom came from, for resetting it
+    pub database: RomDatabase,     // for showing titles in the menu
+    pub tracer: Option<Tracer>,    // logs every instruction if it's there, see trace.rs
+    palettes: Vec<Palette>,
+    palette_index: usize,
+    effects: Vec<Effects>,
+    effects_index: usize,
+    persistence: PersistenceFilter,
+    last_frame: Vec<Colour>,
+    screenshot_name: String,
+    screenshot_dir: PathBuf,
+    screenshot_scale: u32,
+    screenshot_effects: bool,
+    recorder: Option<Recorder>,
+    record_format: RecordingFormat,
+    record_scale: u32,
+    speed_index: usize,
+    fast_forward: bool,
+    paused: bool,
+    advance: bool,     // run one frame while paused
+    frame_budget: f64, // emulated frames owed, for speeds that aren't a whole number
+    hud: bool,
+    stats: Stats,
+    stats_start: Instant,
+    frames_shown: u32,   // since stats_start
+    frames_emulated: u32,
+    keymaps: Vec<Keymap>,
+    keymap_index: usize,
+    menu: Menu,
+    menu_open: bool,
+    watch: WatchMode,
+    watcher: Option<Watcher>, // follows rom_path around, so it's made on demand
+    exit: Option<Exit>
+}
+
+impl<D: Display, I: Input, A: Audio> Platform<D, I, A> {
+    pub fn new(machine: Chip8, settings: &Settings, display: D, input: I, audio: A) -> Self {
+        let rom_dir = settings.rom_dir.clone().unwrap_or_else(|| PathBuf::from("."));
+
+        let mut platform = Platform {
+            machine,
+            display,
+            input,
+            audio,
+            rom_path: None,
+            database: RomDatabase::embedded(),
+            tracer: None,
+            palettes: vec![],
+            palette_index: 0,
+            effects: vec![],
+            effects_index: 0,
+            persistence: PersistenceFilter::new(settings.persistence),
+            last_frame: vec![],
+            screenshot_name: String::new(),
+            screenshot_dir: PathBuf::new(),
+            screenshot_scale: 1,
+            screenshot_effects: false,
+            recorder: None,
+            record_format: settings.record_format,
+            record_scale: settings.record_scale,
+            speed_index: NORMAL_SPEED,
+            fast_forward: false,
+            paused: false,
+            advance: false,
+            frame_budget: 1.0,
+            hud: settings.hud,
+            stats: Stats::default(),
+            stats_start: Instant::now(),
+            frames_shown: 0,
+            frames_emulated: 0,
+            keymaps: vec![],
+            keymap_index: 0,
+            menu: Menu::new(rom_dir),
+            menu_open: false,
+            watch: settings.watch,
+            watcher: None,
+            exit: None
+        };
+        platform.apply_settings(settings);
+
+        // there's always something to show, even if the menu's open before anything has run
+        platform.last_frame = platform.build_frame();
+
+        return platform;
+    }
+
+    pub fn from_frontend<F>(machine: Chip8, settings: &Settings, frontend: F) -> Result<Self, String>
+    where
+        F: Frontend<Display = D, Input = I, Audio = A>
+    {
+        let parts = frontend.open()?;
+
+        return Ok(Platform::new(machine, settings, parts.display, parts.input, parts.audio));
+    }
+
+    // swaps in a new machine, along with the settings that go with its rom
+    pub fn load(&mut self, machine: Chip8, settings: &Settings, rom_path: PathBuf) {
+        self.machine = machine;
+        self.rom_path = Some(rom_path);
+        self.apply_settings(settings);
+        self.menu_open = false;
+        self.paused = false;
+    }
+
+    // the parts of the settings that can change from one rom to the next
+    fn apply_settings(&mut self, settings: &Settings) {
+        // the configured palette comes first, followed by whichever themes it isn't already
+        self.palettes = vec![settings.palette.clone()];
+        for name in palette::THEMES.iter() {
+            let theme = Palette::theme(name).unwrap();
+            if theme != self.palettes[0] {
+                self.palettes.push(theme);
+            }
+        }
+        self.palette_index = 0;
+
+        // same again for the post processing presets
+        self.effects = vec![settings.effects];
+        for name in postfx::PRESETS.iter() {
+            let preset = Effects::preset(name).unwrap();
+            if preset != self.effects[0] {
+                self.effects.push(preset);
+            }
+        }
+        self.effects_index = 0;
+
+        // and the keyboard layouts
+        self.keymaps = vec![settings.keymap];
+        for (name, _) in keymap::LAYOUTS.iter() {
+            let layout = Keymap::layout(name).unwrap();
+            if layout != self.keymaps[0] {
+                self.keymaps.push(layout);
+            }
+        }
+        self.keymap_index = 0;
+        self.input.set_keymap(&settings.keymap);
+
+        self.persistence = PersistenceFilter::new(settings.persistence);
+        self.screenshot_name = settings.title.clone().unwrap_or_default();
+        self.screenshot_dir = settings.screenshot_dir.clone();
+        self.screenshot_scale = settings.screenshot_scale;
+        self.screenshot_effects = settings.screenshot_effects;
+        self.record_format = settings.record_format;
+        self.record_scale = settings.record_scale;
+        self.hud = settings.hud;
+        self.watch = settings.watch;
+    }
+
+    // shows frames at 60 a second until the input asks to quit or a rom gets picked from the
+    // menu. the speed controls change how many emulated frames run in between, rather than
+    // how long each one takes, so the timers always count down once for every tick_rate
+    // instructions
+    pub fn run(&mut self) -> Result<Exit, String> {
+        let mut next_frame = Instant::now();
+
+        while self.poll()? {
+            self.watch_rom();
+
+            if self.fast_forward && self.running() {
+                // as many as fit in one real frame, leaving a bit of time to draw
+                let deadline = Instant::now() + FRAME_DURATION * 3 / 4;
+                while self.running() && Instant::now() < deadline {
+                    self.run_frame();
+                }
+            } else {
+                for _ in 0..self.frames_due() {
+                    if !self.run_frame() {
+                        break;
+                    }
+                }
+            }
+
+            self.audio.set_tone(self.running() && self.machine.sound_timer() > 0)?;
+            self.present()?;
+
+            // wait for the next frame to be due, without trying to catch up if we fell behind,
+            // e.g. after being suspended
+            next_frame += FRAME_DURATION;
+            let now = Instant::now();
+            if next_frame > now {
+                thread::sleep(next_frame - now);
+            } else {
+                next_frame = now;
+            }
+        }
+
+        return Ok(self.exit.take().unwrap_or(Exit::Quit));
+    }
+
+    // runs a single frame, as fast as possible and ignoring the speed controls. returns false
+    // once the input asks to quit
+    pub fn step(&mut self) -> Result<bool, String> {
+        if !self.poll()? {
+            return Ok(false);
+        }
+
+        self.watch_rom();
+        self.emulate_frame().map_err(|e| e.to_string())?;
+        self.audio.set_tone(self.machine.sound_timer() > 0)?;
+        self.present()?;
+
+        return Ok(true);
+    }
+
+    pub fn paused(&self) -> bool {
+        return self.paused;
+    }
+
+    pub fn fast_forward(&self) -> bool {
+        return self.fast_forward;
+    }
+
+    pub fn speed(&self) -> f64 {
+        return SPEEDS[self.speed_index];
+    }
+
+    // opens the menu, straight into the rom browser if there's no rom to go back to
+    pub fn open_menu(&mut self) {
+        if self.rom_path.is_some() {
+            self.menu.show_main();
+        } else {
+            self.menu.show_roms(&self.database);
+        }
+        self.menu_open = true;
+    }
+
+    fn running(&self) -> bool {
+        return !self.paused && !self.menu_open;
+    }
+
+    // handles whatever's come in from the input, returning false if it's time to stop
+    fn poll(&mut self) -> Result<bool, String> {
+        for event in self.input.poll()? {
+            match event {
+                InputEvent::Key { key, pressed } => self.machine.keypad[key] = pressed as u8,
+                InputEvent::Hotkey(hotkey) => self.hotkey(hotkey),
+                InputEvent::Menu(input) if self.menu_open => self.menu_input(input),
+                InputEvent::Menu(_) => (),
+                InputEvent::Quit => self.exit = Some(Exit::Quit),
+            }
+        }
+
+        return Ok(self.exit.is_none());
+    }
+
+    fn menu_input(&mut self, input: MenuInput) {
+        let action = match self.menu.input(input, &self.database) {
+            Some(action) => action,
+            None => return,
+        };
+
+        match action {
+            MenuAction::Close => self.menu_open = false,
+            MenuAction::Load(path) => self.exit = Some(Exit::Load(path)),
+            MenuAction::Reload => match &self.rom_path {
+                Some(path) => self.exit = Some(Exit::Load(path.clone())),
+                None => self.display.show_message("there's no rom to reload"),
+            },
+            MenuAction::Reset => self.reset(),
+            MenuAction::SoftReset => self.soft_reset(),
+            MenuAction::Quirk(index) => {
+                let quirks = &mut self.machine.quirks;
+                let quirk = match menu::QUIRKS[index] {
+                    "shift" => &mut quirks.shift,
+                    "load_store" => &mut quirks.load_store,
+                    "jump" => &mut quirks.jump,
+                    "vf_reset" => &mut quirks.vf_reset,
+                    _ => &mut quirks.clip,
+                };
+                *quirk = !*quirk;
+            },
+            MenuAction::Palette(forward) => self.palette_index = cycle(self.palette_index, self.palettes.len(), forward),
+            MenuAction::Speed(faster) => self.change_speed(faster),
+            MenuAction::Keymap(forward) => {
+                self.keymap_index = cycle(self.keymap_index, self.keymaps.len(), forward);
+                self.input.set_keymap(&self.keymaps[self.keymap_index]);
+            },
+            MenuAction::Quit => self.exit = Some(Exit::Quit),
+        }
+    }
+
+    // starts the rom again from scratch, keeping the quirks and speed as they are
+    fn reset(&mut self) {
+        if self.rom_path.is_none() {
+            return self.display.show_message("there's no rom to reset");
+        }
+
+        self.machine.reset();
+        self.menu_open = false;
+        self.display.show_message("reset");
+    }
+
+    fn soft_reset(&mut self) {
+        if self.rom_path.is_none() {
+            return self.display.show_message("there's no rom to reset");
+        }
+
+        self.machine.soft_reset();
+        self.menu_open = false;
+        self.display.show_message("soft reset");
+    }
+
+    // reloads the rom if it's changed on disk since it was loaded, going by the watch mode
+    fn watch_rom(&mut self) {
+        let path = match &self.rom_path {
+            Some(path) if self.watch != WatchMode::Off => path,
+            _ => {
+                self.watcher = None;
+                return;
+            },
+        };
+
+        // a different rom has been loaded since the last check, so start again with that one
+        if self.watcher.as_ref().map(|watcher| watcher.path()) != Some(path.as_path()) {
+            self.watcher = Some(Watcher::new(path));
+            return;
+        }
+
+        if !self.watcher.as_mut().is_some_and(|watcher| watcher.changed()) {
+            return;
+        }
+
+        let path = path.clone();
+        let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+
+        // if it doesn't assemble, the old version keeps running until the next save
+        let program = match assembler::read_program(&path) {
+            Ok(program) => program,
+            Err(e) => {
+                let mut errors = e.lines();
+                let mut message = format!("couldn't reload {}: {}", name, errors.next().unwrap_or(""));
+                let more = errors.count();
+                if more > 0 {
+                    message.push_str(&format!(" (and {} more)", more));
+                }
+                return self.display.show_message(&message);
+            },
+        };
+
+        let result = match self.watch {
+            WatchMode::Preserve => self.machine.load_program(&program),
+            _ => self.machine.load_program(&program).map(|()| self.machine.reset()),
+        };
+
+        match result {
+            Ok(()) => self.display.show_message(&format!("reloaded {}", name)),
+            Err(e) => self.display.show_message(&format!("couldn't reload {}: {}", name, e)),
+        }
+    }
+
+    fn menu_view(&self) -> Option<MenuView> {
+        if !self.menu_open {
+            return None;
+        }
+
+        let palette = &self.palettes[self.palette_index];
+        let keymap = &self.keymaps[self.keymap_index];
+        let values = MenuValues {
+            palette: palette.theme_name().unwrap_or("custom"),
+            speed: self.speed(),
+            keymap: keymap.name().unwrap_or("custom"),
+            quirks: self.machine.quirks
+        };
+
+        return Some(self.menu.view(&values));
+    }
+
+    // one 60hz frame of the machine, which is what everything that keeps time goes by
+    fn emulate_frame(&mut self) -> Result<(), ExecutionError> {
+        let result = match &mut self.tracer {
+            Some(tracer) => tracer.cycle(&mut self.machine),
+            None => self.machine.cycle(),
+        };
+
+        self.record_frame();
+        self.last_frame = self.build_frame();
+        self.frames_emulated += 1;
+
+        return result;
+    }
+
+    // runs a frame, pausing if the program hits an error so it can be looked at with the hud
+    // and carried on from or reset. returns false if it did
+    fn run_frame(&mut self) -> bool {
+        if let Err(e) = self.emulate_frame() {
+            self.paused = true;
+            self.display.show_message(&format!("paused: {}", e));
+            return false;
+        }
+
+        return true;
+    }
+
+    fn present(&mut self) -> Result<(), String> {
+        self.update_stats();
+
+        let frame = Frame {
+            pixels: &self.last_frame,
+            effects: self.effects(),
+            machine: &self.machine,
+            stats: self.stats,
+            hud: self.hud,
+            menu: self.menu_view()
+        };
+
+        return self.display.present(&frame);
+    }
+
+    fn update_stats(&mut self) {
+        self.frames_shown += 1;
+        self.stats.paused = self.paused;
+        self.stats.fast_forward = self.fast_forward;
+
+        let elapsed = self.stats_start.elapsed();
+        if elapsed >= STATS_INTERVAL {
+            let seconds = elapsed.as_secs_f64();
+            self.stats.fps = self.frames_shown as f64 / seconds;
+            self.stats.speed = self.frames_emulated as f64 / seconds / 60.0;
+
+            self.stats_start = Instant::now();
+            self.frames_shown = 0;
+            self.frames_emulated = 0;
+        }
+    }
+
+    // how many emulated frames to run before the next one is shown
+    fn frames_due(&mut self) -> u32 {
+        if self.menu_open {
+            return 0;
+        }
+
+        if self.paused {
+            let advance = self.advance as u32;
+            self.advance = false;
+            return advance;
+        }
+
+        self.frame_budget += self.speed();
+        let due = self.frame_budget.floor();
+        self.frame_budget -= due;
+
+        return due as u32;
+    }
+
+    fn change_speed(&mut self, faster: bool) {
+        self.speed_index = if faster {
+            (self.speed_index + 1).min(SPEEDS.len() - 1)
+        } else {
+            self.speed_index.saturating_sub(1)
+        };
+        self.frame_budget = 0.0;
+
+        self.display.show_message(&format!("speed {}x", self.speed()));
+    }
+
+    // saves the last frame shown as a png, as set up in the settings
+    pub fn save_screenshot(&self, path: &Path) -> Result<(), String> {
+        let effects = if self.screenshot_effects { Some(self.effects()) } else { None };
+        let (pixels, width, height) = screenshot::render(&self.last_frame, self.screenshot_scale, effects);
+
+        return screenshot::write_png(path, &pixels, width, height)
+            .map_err(|e| format!("couldn't save {}: {}", path.display(), e));
+    }
+
+    // starts recording to path, as a gif if it ends in .gif or as a directory of frames if not
+    pub fn start_recording(&mut self, path: &Path) -> Result<(), String> {
+        self.stop_recording()?;
+
+        let format = RecordingFormat::from_path(path);
+        let recorder = Recorder::start(path, format, self.record_scale, &self.palettes[self.palette_index])
+            .map_err(|e| format!("couldn't record to {}: {}", path.display(), e))?;
+        self.recorder = Some(recorder);
+
+        return Ok(());
+    }
+
+    // finishes off the recording, if there is one
+    pub fn stop_recording(&mut self) -> Result<(), String> {
+        if let Some(recorder) = self.recorder.take() {
+            let path = recorder.path().to_path_buf();
+            recorder.finish().map_err(|e| format!("couldn't finish {}: {}", path.display(), e))?;
+        }
+
+        return Ok(());
+    }
+
+    fn toggle_recording(&mut self) {
+        if let Some(recorder) = &self.recorder {
+            let path = recorder.path().to_path_buf();
+            match self.stop_recording() {
+                Ok(()) => self.display.show_message(&format!("saved {}", path.display())),
+                Err(e) => self.display.show_message(&format!("recording failed: {}", e)),
+            }
+            return;
+        }
+
+        let extension = match self.record_format {
+            RecordingFormat::Gif => "gif",
+            RecordingFormat::Frames => "",
+        };
+        let result = screenshot::next_path(&self.screenshot_dir, &self.screenshot_name, extension)
+            .map_err(|e| format!("couldn't make {}: {}", self.screenshot_dir.display(), e))
+            .and_then(|path| self.start_recording(&path).map(|()| path));
+
+        match result {
+            Ok(path) => self.display.show_message(&format!("recording to {}", path.display())),
+            Err(e) => self.display.show_message(&format!("recording failed: {}", e)),
+        }
+    }
+
+    // recordings get the raw screen rather than what's shown, so flicker reduction and post
+    // processing are left for whatever the recording gets played back on
+    fn record_frame(&mut self) {
+        let recorder = match &mut self.recorder {
+            Some(recorder) => recorder,
+            None => return,
+        };
+
+        let video = &self.machine.video[..MACHINE_SCREEN_WIDTH * MACHINE_SCREEN_HEIGHT];
+        let tone = self.machine.sound_timer() > 0;
+        if let Err(e) = recorder.record(video, &self.palettes[self.palette_index], tone) {
+            self.recorder = None;
+            self.display.show_message(&format!("recording stopped: {}", e));
+        }
+    }
+
+    fn take_screenshot(&mut self) {
+        let result = screenshot::next_path(&self.screenshot_dir, &self.screenshot_name, "png")
+            .map_err(|e| format!("couldn't make {}: {}", self.screenshot_dir.display(), e))
+            .and_then(|path| self.save_screenshot(&path).map(|()| path));
+
+        match result {
+            Ok(path) => self.display.show_message(&format!("saved {}", path.display())),
+            Err(e) => self.display.show_message(&format!("screenshot failed: {}", e)),
+        }
+    }
+
+    pub fn hotkey(&mut self, hotkey: Hotkey) {
+        match hotkey {
+            Hotkey::Hud => self.hud = !self.hud,
+            Hotkey::Palette => self.palette_index = (self.palette_index + 1) % self.palettes.len(),
+            Hotkey::Effects => self.effects_index = (self.effects_index + 1) % self.effects.len(),
+            Hotkey::Persistence => self.next_persistence(),
+            Hotkey::Reset => self.reset(),
+            Hotkey::SoftReset => self.soft_reset(),
+            Hotkey::Record => self.toggle_recording(),
+            Hotkey::Screenshot => self.take_screenshot(),
+            Hotkey::FastForward => {
+                self.fast_forward = !self.fast_forward;
+                self.display.show_message(if self.fast_forward { "fast forward" } else { "normal speed" });
+            },
+            Hotkey::Faster => self.change_speed(true),
+            Hotkey::Slower => self.change_speed(false),
+            Hotkey::Pause => {
+                self.paused = !self.paused;
+                self.display.show_message(if self.paused { "paused" } else { "running" });
+            },
+            Hotkey::FrameAdvance => {
+                self.paused = true;
+                self.advance = true;
+            },
+            // there's nothing to go back to without a rom, so the menu stays open
+            Hotkey::Menu if self.menu_open && self.rom_path.is_some() => self.menu_open = false,
+            Hotkey::Menu if self.menu_open => (),
+            Hotkey::Menu => self.open_menu(),
+        }
+    }
+
+    pub fn effects(&self) -> Effects {
+        return self.effects[self.effects_index];
+    }
+
+    // cycles through the standard modes, so a custom decay from the config gets swapped for
+    // the default one
+    fn next_persistence(&mut self) {
+        let modes = Persistence::modes();
+        let current = modes.iter().position(|mode| *mode == self.persistence.mode);
+        let next = current.map(|i| (i + 1) % modes.len()).unwrap_or(0);
+        self.persistence.mode = modes[next];
+    }
+
+    // the screen with the current palette and flicker reduction applied, ready to be post
+    // processed. this should be called once per emulated frame
+    fn build_frame(&mut self) -> Vec<Colour> {
+        let pixels = &self.machine.video[..MACHINE_SCREEN_WIDTH * MACHINE_SCREEN_HEIGHT];
+
+        return self.persistence.apply(pixels, &self.palettes[self.palette_index]);
+    }
+}
+
+// the next or previous of count things, wrapping around at either end
+fn cycle(index: usize, count: usize, forward: bool) -> usize {
+    if forward {
+        return (index + 1) % count;
+    }
+
+    return (index + count - 1) % count;
+}
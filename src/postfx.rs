@@ -0,0 +1,233 @@
+use std::convert::TryFrom;
+use std::f32::consts::PI;
+use serde::Deserialize;
+
+use crate::colour::Colour;
+
+// presets, in the order the effects hotkey cycles through them
+pub const PRESETS: [&str; 5] = ["none", "sharp", "scanlines", "crt", "lcd"];
+
+const DEFAULT_SCANLINES: f32 = 0.5;
+const DEFAULT_BLOOM: f32 = 0.35;
+const DEFAULT_CURVATURE: f32 = 0.08;
+const DEFAULT_PIXEL_GRID: f32 = 0.5;
+
+// post processing applied to the chip-8 screen once it's been coloured in. each strength runs
+// from 0 (off) to 1. these get applied in the order they're listed here, and
+// shaders/fragment.glsl has to stay in step with this file so screenshots taken without a gpu
+// look the same as the window
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Effects {
+    pub curvature: f32,       // barrel distortion, like the bulge of a crt
+    pub sharp_bilinear: bool, // blend at pixel edges when the scale isn't a whole number
+    pub bloom: f32,           // glow bleeding out of lit pixels
+    pub scanlines: f32,       // darken the gaps between rows
+    pub pixel_grid: f32       // darken the gaps between rows and columns, like an lcd
+}
+
+impl Effects {
+    pub fn preset(name: &str) -> Option<Self> {
+        let effects = match name {
+            "none" => Effects::default(),
+            "sharp" => Effects {
+                sharp_bilinear: true,
+                ..Effects::default()
+            },
+            "scanlines" => Effects {
+                sharp_bilinear: true,
+                scanlines: DEFAULT_SCANLINES,
+                ..Effects::default()
+            },
+            "crt" => Effects {
+                curvature: DEFAULT_CURVATURE,
+                sharp_bilinear: true,
+                bloom: DEFAULT_BLOOM,
+                scanlines: 0.35,
+                pixel_grid: 0.0
+            },
+            "lcd" => Effects {
+                sharp_bilinear: true,
+                pixel_grid: DEFAULT_PIXEL_GRID,
+                ..Effects::default()
+            },
+            _ => return None,
+        };
+
+        return Some(effects);
+    }
+
+    // either a preset name or a comma separated list of effects, each with an optional
+    // strength, e.g. "scanlines=0.3,bloom,sharp"
+    pub fn parse(value: &str) -> Result<Self, String> {
+        if let Some(effects) = Effects::preset(value) {
+            return Ok(effects);
+        }
+
+        let mut effects = Effects::default();
+        for item in value.split(',').map(|item| item.trim()) {
+            let mut parts = item.splitn(2, '=');
+            let name = parts.next().unwrap_or("");
+            let strength = match parts.next() {
+                Some(strength) => {
+                    let strength: f32 = strength.parse()
+                        .map_err(|_| format!("invalid strength for {}: {}", name, strength))?;
+                    if !(0.0..=1.0).contains(&strength) {
+                        return Err(format!("strength for {} must be between 0 and 1", name));
+                    }
+                    Some(strength)
+                },
+                None => None,
+            };
+
+            match name {
+                "curvature" => effects.curvature = strength.unwrap_or(DEFAULT_CURVATURE),
+                "sharp" => effects.sharp_bilinear = strength.map(|s| s > 0.0).unwrap_or(true),
+                "bloom" => effects.bloom = strength.unwrap_or(DEFAULT_BLOOM),
+                "scanlines" => effects.scanlines = strength.unwrap_or(DEFAULT_SCANLINES),
+                "grid" => effects.pixel_grid = strength.unwrap_or(DEFAULT_PIXEL_GRID),
+                _ => return Err(format!("unknown effect: {}", name)),
+            }
+        }
+
+        return Ok(effects);
+    }
+
+    // software version of shaders/fragment.glsl. frame is width * height colours, and the result
+    // is out_width * out_height
+    pub fn render(&self, frame: &[Colour], width: usize, height: usize, out_width: usize, out_height: usize) -> Vec<Colour> {
+        let source = Source {
+            texels: frame.iter().map(|colour| colour.to_linear_array()).collect(),
+            width,
+            height
+        };
+        let scale = [out_width as f32 / width as f32, out_height as f32 / height as f32];
+
+        let mut output = Vec::with_capacity(out_width * out_height);
+        for y in 0..out_height {
+            for x in 0..out_width {
+                let uv = [(x as f32 + 0.5) / out_width as f32, (y as f32 + 0.5) / out_height as f32];
+                output.push(Colour::from_linear_array(self.shade(&source, uv, scale)));
+            }
+        }
+
+        return output;
+    }
+
+    fn shade(&self, source: &Source, uv: [f32; 2], scale: [f32; 2]) -> [f32; 4] {
+        let uv = match self.curve(uv) {
+            Some(uv) => uv,
+            None => return [0.0, 0.0, 0.0, 1.0],
+        };
+
+        let size = [source.width as f32, source.height as f32];
+        let mut texel_uv = [0.0; 2];
+        for i in 0..2 {
+            let position = uv[i] * size[i] - 0.5;
+            let cell = position.floor();
+            let mut offset = position - cell;
+            if self.sharp_bilinear {
+                offset = ((offset - 0.5) * scale[i] + 0.5).clamp(0.0, 1.0);
+            } else {
+                offset = offset.round();
+            }
+            texel_uv[i] = (cell + 0.5 + offset) / size[i];
+        }
+        let mut colour = source.sample(texel_uv);
+
+        if self.bloom > 0.0 {
+            let mut glow = [0.0; 4];
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    let weight = [1.0, 2.0, 1.0][(dx + 1) as usize] * [1.0, 2.0, 1.0][(dy + 1) as usize] / 16.0;
+                    let neighbour = source.sample([uv[0] + dx as f32 / size[0], uv[1] + dy as f32 / size[1]]);
+                    for i in 0..3 {
+                        glow[i] += neighbour[i] * weight;
+                    }
+                }
+            }
+
+            for i in 0..3 {
+                colour[i] += glow[i] * self.bloom;
+            }
+        }
+
+        let row = (uv[1] * size[1]).fract();
+        let col = (uv[0] * size[0]).fract();
+        let mut brightness = 1.0;
+        if self.scanlines > 0.0 {
+            brightness *= 1.0 - self.scanlines * (1.0 - (PI * row).sin());
+        }
+
+        if self.pixel_grid > 0.0 {
+            brightness *= 1.0 - self.pixel_grid * (1.0 - ((PI * row).sin() * (PI * col).sin()).sqrt());
+        }
+
+        for channel in colour.iter_mut().take(3) {
+            *channel *= brightness;
+        }
+
+        return colour;
+    }
+
+    // None means uv has been pushed off the edge of the screen
+    fn curve(&self, uv: [f32; 2]) -> Option<[f32; 2]> {
+        if self.curvature <= 0.0 {
+            return Some(uv);
+        }
+
+        let centred = [uv[0] * 2.0 - 1.0, uv[1] * 2.0 - 1.0];
+        let bulge = 1.0 + self.curvature * (centred[0] * centred[0] + centred[1] * centred[1]);
+        let curved = [(centred[0] * bulge + 1.0) / 2.0, (centred[1] * bulge + 1.0) / 2.0];
+        if curved.iter().any(|c| *c < 0.0 || *c > 1.0) {
+            return None;
+        }
+
+        return Some(curved);
+    }
+}
+
+impl TryFrom<String> for Effects {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Effects::parse(&value)
+    }
+}
+
+// linear colours, sampled the same way a gpu does with bilinear filtering and clamp to edge
+struct Source {
+    texels: Vec<[f32; 4]>,
+    width: usize,
+    height: usize
+}
+
+impl Source {
+    fn texel(&self, x: f32, y: f32) -> [f32; 4] {
+        let x = (x.max(0.0) as usize).min(self.width - 1);
+        let y = (y.max(0.0) as usize).min(self.height - 1);
+
+        return self.texels[y * self.width + x];
+    }
+
+    fn sample(&self, uv: [f32; 2]) -> [f32; 4] {
+        let x = uv[0] * self.width as f32 - 0.5;
+        let y = uv[1] * self.height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+
+        let top_left = self.texel(x0, y0);
+        let top_right = self.texel(x0 + 1.0, y0);
+        let bottom_left = self.texel(x0, y0 + 1.0);
+        let bottom_right = self.texel(x0 + 1.0, y0 + 1.0);
+
+        let mut colour = [0.0; 4];
+        for i in 0..4 {
+            let top = top_left[i] + (top_right[i] - top_left[i]) * fx;
+            let bottom = bottom_left[i] + (bottom_right[i] - bottom_left[i]) * fx;
+            colour[i] = top + (bottom - top) * fy;
+        }
+
+        return colour;
+    }
+}
@@ -0,0 +1,253 @@
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use serde::Deserialize;
+
+use crate::core::{MACHINE_SCREEN_WIDTH, MACHINE_SCREEN_HEIGHT};
+use crate::palette::Palette;
+use crate::screenshot;
+
+// recordings are timed by the emulated 60hz frame clock, not by the wall clock, so they play
+// back at the right speed however fast the interpreter was actually running
+const FRAMES_PER_SECOND: u64 = 60;
+const SAMPLE_RATE: u32 = 44100;
+const TONE_FREQUENCY: u32 = 440;
+const TONE_VOLUME: i16 = i16::MAX / 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum RecordingFormat {
+    Gif,    // an animated gif using the palette's colours
+    Frames  // a directory of pngs plus an ffmpeg concat file and a wav of the buzzer
+}
+
+impl RecordingFormat {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "gif" => Ok(RecordingFormat::Gif),
+            "frames" => Ok(RecordingFormat::Frames),
+            _ => Err(format!("unknown recording format: {}", value)),
+        }
+    }
+
+    // gif files record as a gif, anything else is taken to be a directory for frames
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension() {
+            Some(extension) if extension.eq_ignore_ascii_case("gif") => RecordingFormat::Gif,
+            _ => RecordingFormat::Frames,
+        }
+    }
+}
+
+impl TryFrom<String> for RecordingFormat {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        RecordingFormat::parse(&value)
+    }
+}
+
+enum Sink {
+    Gif {
+        encoder: gif::Encoder<BufWriter<File>>,
+        palette: Vec<u8> // the global palette, frames only get their own if it changes
+    },
+    Frames {
+        dir: PathBuf,
+        count: u64,
+        concat: BufWriter<File>,
+        audio: hound::WavWriter<BufWriter<File>>,
+        tone_phase: u32
+    }
+}
+
+// records the screen one emulated frame at a time. a frame that's the same as the one before
+// isn't written again, the previous one is just held on screen for longer
+pub struct Recorder {
+    path: PathBuf,
+    sink: Sink,
+    scale: usize,
+    pending: Option<(Vec<u32>, Palette)>,
+    pending_start: u64, // the frame the pending one first appeared on
+    frames: u64         // how many frames have been recorded so far
+}
+
+impl Recorder {
+    pub fn start(path: &Path, format: RecordingFormat, scale: u32, palette: &Palette) -> io::Result<Recorder> {
+        let scale = scale.max(1) as usize;
+        let sink = match format {
+            RecordingFormat::Gif => {
+                let file = BufWriter::new(File::create(path)?);
+                let palette = gif_palette(palette);
+                let width = (MACHINE_SCREEN_WIDTH * scale) as u16;
+                let height = (MACHINE_SCREEN_HEIGHT * scale) as u16;
+                let mut encoder = gif::Encoder::new(file, width, height, &palette).map_err(to_io_error)?;
+                encoder.set_repeat(gif::Repeat::Infinite).map_err(to_io_error)?;
+                Sink::Gif { encoder, palette }
+            },
+            RecordingFormat::Frames => {
+                fs::create_dir_all(path)?;
+                let mut concat = BufWriter::new(File::create(path.join("frames.ffconcat"))?);
+                writeln!(concat, "ffconcat version 1.0")?;
+                let spec = hound::WavSpec {
+                    channels: 1,
+                    sample_rate: SAMPLE_RATE,
+                    bits_per_sample: 16,
+                    sample_format: hound::SampleFormat::Int
+                };
+                let audio = hound::WavWriter::create(path.join("audio.wav"), spec).map_err(to_io_error)?;
+                Sink::Frames { dir: path.to_path_buf(), count: 0, concat, audio, tone_phase: 0 }
+            },
+        };
+
+        return Ok(Recorder {
+            path: path.to_path_buf(),
+            sink,
+            scale,
+            pending: None,
+            pending_start: 0,
+            frames: 0
+        });
+    }
+
+    pub fn path(&self) -> &Path {
+        return &self.path;
+    }
+
+    // call once per emulated frame, with whether the buzzer is sounding during it
+    pub fn record(&mut self, video: &[u32], palette: &Palette, tone: bool) -> io::Result<()> {
+        if let Sink::Frames { audio, tone_phase, .. } = &mut self.sink {
+            write_tone(audio, tone_phase, tone)?;
+        }
+
+        let unchanged = match &self.pending {
+            Some((pending_video, pending_palette)) => pending_video.as_slice() == video && pending_palette == palette,
+            None => false,
+        };
+
+        if !unchanged {
+            self.flush()?;
+            self.pending = Some((video.to_vec(), palette.clone()));
+            self.pending_start = self.frames;
+        }
+
+        self.frames += 1;
+
+        return Ok(());
+    }
+
+    // writes out whatever's left and closes the files
+    pub fn finish(mut self) -> io::Result<()> {
+        self.flush()?;
+
+        match self.sink {
+            Sink::Gif { encoder, .. } => {
+                // the trailer only gets written when the encoder is dropped
+                drop(encoder);
+            },
+            Sink::Frames { mut concat, audio, .. } => {
+                concat.flush()?;
+                audio.finalize().map_err(to_io_error)?;
+            },
+        }
+
+        return Ok(());
+    }
+
+    // writes the pending frame now that we know how long it stays on screen
+    fn flush(&mut self) -> io::Result<()> {
+        let (video, palette) = match self.pending.take() {
+            Some(pending) => pending,
+            None => return Ok(()),
+        };
+
+        let start = self.pending_start;
+        let end = self.frames;
+
+        match &mut self.sink {
+            Sink::Gif { encoder, palette: global_palette } => {
+                // gif delays are in hundredths of a second, which 60hz doesn't divide into, so
+                // both ends get rounded to keep the total in step with the frame clock
+                let delay = frames_to_time(end, 100) - frames_to_time(start, 100);
+                let local_palette = gif_palette(&palette);
+                let size = palette.colour_count() as u32;
+
+                let indices: Vec<u8> = video.iter().map(|pixel| (pixel % size) as u8).collect();
+                let frame = gif::Frame {
+                    width: (MACHINE_SCREEN_WIDTH * self.scale) as u16,
+                    height: (MACHINE_SCREEN_HEIGHT * self.scale) as u16,
+                    delay: delay as u16,
+                    palette: if local_palette != *global_palette { Some(local_palette) } else { None },
+                    buffer: Cow::Owned(scale_up(&indices, self.scale)),
+                    ..gif::Frame::default()
+                };
+                encoder.write_frame(&frame).map_err(to_io_error)?;
+            },
+            Sink::Frames { dir, count, concat, .. } => {
+                *count += 1;
+                let name = format!("frame-{:06}.png", count);
+                let (pixels, width, height) = screenshot::render(&palette.colourise(&video), self.scale as u32, None);
+                screenshot::write_png(&dir.join(&name), &pixels, width, height)?;
+
+                let duration = frames_to_time(end, 1_000_000) - frames_to_time(start, 1_000_000);
+                writeln!(concat, "file '{}'", name)?;
+                writeln!(concat, "duration {}.{:06}", duration / 1_000_000, duration % 1_000_000)?;
+            },
+        }
+
+        return Ok(());
+    }
+}
+
+// the time a frame starts at, in units per second, rounded to the nearest
+fn frames_to_time(frames: u64, units: u64) -> u64 {
+    return (frames * units + FRAMES_PER_SECOND / 2) / FRAMES_PER_SECOND;
+}
+
+fn gif_palette(palette: &Palette) -> Vec<u8> {
+    let values: Vec<u32> = (0..palette.colour_count() as u32).collect();
+
+    return palette.colourise(&values).iter()
+        .flat_map(|colour| vec![colour.red(), colour.green(), colour.blue()])
+        .collect();
+}
+
+fn scale_up(pixels: &[u8], scale: usize) -> Vec<u8> {
+    let width = MACHINE_SCREEN_WIDTH * scale;
+    let height = MACHINE_SCREEN_HEIGHT * scale;
+
+    let mut scaled = Vec::with_capacity(width * height);
+    for y in 0..height {
+        let row = &pixels[(y / scale) * MACHINE_SCREEN_WIDTH..][..MACHINE_SCREEN_WIDTH];
+        scaled.extend((0..width).map(|x| row[x / scale]));
+    }
+
+    return scaled;
+}
+
+// a frame's worth of square wave, or silence. the phase carries on between frames so the
+// tone doesn't click at every frame boundary
+fn write_tone(audio: &mut hound::WavWriter<BufWriter<File>>, phase: &mut u32, tone: bool) -> io::Result<()> {
+    let samples = SAMPLE_RATE / FRAMES_PER_SECOND as u32;
+
+    for _ in 0..samples {
+        let sample = if !tone {
+            0
+        } else if *phase < SAMPLE_RATE / 2 {
+            TONE_VOLUME
+        } else {
+            -TONE_VOLUME
+        };
+        audio.write_sample(sample).map_err(to_io_error)?;
+
+        *phase = (*phase + TONE_FREQUENCY) % SAMPLE_RATE;
+    }
+
+    return Ok(());
+}
+
+fn to_io_error<E: std::error::Error>(error: E) -> io::Error {
+    return io::Error::other(error.to_string());
+}
@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use serde::Deserialize;
+
+use crate::colour::Colour;
+use crate::config::{Overrides, QuirkOverrides};
+use crate::palette::Palette;
+
+// these follow the layout of the community chip-8-database (github.com/chip-8/chip-8-database),
+// so its files can be dropped straight into database/ to be built in, or into
+// <config dir>/chip8-rust/database/ to be picked up at runtime instead of the built in copy
+const EMBEDDED_PLATFORMS: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/database/platforms.json"));
+const EMBEDDED_PROGRAMS: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/database/programs.json"));
+const EMBEDDED_HASHES: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/database/sha1-hashes.json"));
+
+const PLATFORMS_FILE_NAME: &str = "platforms.json";
+const PROGRAMS_FILE_NAME: &str = "programs.json";
+const HASHES_FILE_NAME: &str = "sha1-hashes.json";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlatformEntry {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub default_tickrate: Option<u32>,
+    #[serde(default)]
+    quirks: DatabaseQuirks
+}
+
+// vblank (waiting for the display interrupt before drawing) isn't emulated, and the
+// interpreter only knows whether fx55/fx65 move I at all rather than by how much
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct DatabaseQuirks {
+    shift: Option<bool>,
+    memory_increment_by_x: Option<bool>,
+    memory_leave_i_unchanged: Option<bool>,
+    wrap: Option<bool>,
+    jump: Option<bool>,
+    logic: Option<bool>
+}
+
+#[derive(Debug, Deserialize)]
+struct Program {
+    title: String,
+    #[serde(default)]
+    roms: HashMap<String, RomEntry>
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RomEntry {
+    #[serde(default)]
+    platforms: Vec<String>,
+    #[serde(default)]
+    tickrate: Option<u32>,
+    #[serde(default)]
+    quirky_platforms: HashMap<String, DatabaseQuirks>,
+    #[serde(default)]
+    colors: Option<RomColours>,
+    #[serde(default)]
+    keys: HashMap<String, u8>
+}
+
+#[derive(Debug, Deserialize)]
+struct RomColours {
+    #[serde(default)]
+    pixels: Vec<String>
+}
+
+pub struct RomDatabase {
+    platforms: Vec<PlatformEntry>,
+    programs: Vec<Program>,
+    hashes: HashMap<String, usize>
+}
+
+// everything the database knows about a single rom
+pub struct RomInfo<'a> {
+    pub title: &'a str,
+    pub platform: Option<&'a PlatformEntry>,
+    pub overrides: Overrides,
+    pub keys: Vec<(&'a str, u8)>
+}
+
+impl RomDatabase {
+    pub fn default_dir() -> Option<PathBuf> {
+        return dirs::config_dir().map(|dir| dir.join("chip8-rust").join("database"));
+    }
+
+    pub fn embedded() -> RomDatabase {
+        return RomDatabase::parse(EMBEDDED_PLATFORMS, EMBEDDED_PROGRAMS, EMBEDDED_HASHES)
+            .expect("built in rom database is invalid");
+    }
+
+    // any file missing from dir falls back to the built in one
+    pub fn load(dir: &Path) -> io::Result<RomDatabase> {
+        let read = |name: &str, fallback: &str| -> io::Result<String> {
+            match fs::read_to_string(dir.join(name)) {
+                Ok(contents) => Ok(contents),
+                Err(ref e) if e.kind() == io::ErrorKind::NotFound => Ok(fallback.to_string()),
+                Err(e) => Err(e),
+            }
+        };
+
+        let platforms = read(PLATFORMS_FILE_NAME, EMBEDDED_PLATFORMS)?;
+        let programs = read(PROGRAMS_FILE_NAME, EMBEDDED_PROGRAMS)?;
+        let hashes = read(HASHES_FILE_NAME, EMBEDDED_HASHES)?;
+
+        return RomDatabase::parse(&platforms, &programs, &hashes).map_err(|e| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", dir.display(), e))
+        });
+    }
+
+    fn parse(platforms: &str, programs: &str, hashes: &str) -> serde_json::Result<RomDatabase> {
+        return Ok(RomDatabase {
+            platforms: serde_json::from_str(platforms)?,
+            programs: serde_json::from_str(programs)?,
+            hashes: serde_json::from_str(hashes)?
+        });
+    }
+
+    pub fn lookup(&self, sha1: &str) -> Option<RomInfo<'_>> {
+        let sha1 = sha1.to_ascii_lowercase();
+        let program = self.programs.get(*self.hashes.get(&sha1)?)?;
+        let rom = program.roms.get(&sha1)?;
+
+        // roms list the platforms they run on in order of preference
+        let platform_id = rom.platforms.first();
+        let platform = platform_id.and_then(|id| self.platforms.iter().find(|p| &p.id == id));
+
+        let mut overrides = Overrides {
+            name: Some(program.title.clone()),
+            tick_rate: rom.tickrate.or_else(|| platform.and_then(|p| p.default_tickrate)),
+            ..Overrides::default()
+        };
+
+        if let Some(platform) = platform {
+            overrides.quirks.apply(&platform.quirks);
+        }
+
+        if let Some(quirks) = platform_id.and_then(|id| rom.quirky_platforms.get(id)) {
+            overrides.quirks.apply(quirks);
+        }
+
+        // the first two pixel colours are the background and foreground, any more are for
+        // xo-chip's extra planes
+        if let Some(colours) = &rom.colors {
+            let pixels: Option<Vec<Colour>> = colours.pixels.iter().map(|hex| Colour::from_hex(hex)).collect();
+            overrides.palette = pixels.and_then(|pixels| Palette::from_colours(pixels).ok());
+        }
+
+        let mut keys: Vec<(&str, u8)> = rom.keys.iter().map(|(name, key)| (name.as_str(), *key)).collect();
+        keys.sort();
+
+        return Some(RomInfo {
+            title: &program.title,
+            platform,
+            overrides,
+            keys
+        });
+    }
+}
+
+impl QuirkOverrides {
+    fn apply(&mut self, quirks: &DatabaseQuirks) {
+        self.shift = quirks.shift.or(self.shift);
+        self.jump = quirks.jump.or(self.jump);
+        self.vf_reset = quirks.logic.or(self.vf_reset);
+        self.clip = quirks.wrap.map(|wrap| !wrap).or(self.clip);
+
+        // incrementing by x instead of x + 1 is close enough to be treated the same. not
+        // incrementing by x doesn't say whether I moves at all though, so that's left alone
+        let increment_by_x = quirks.memory_increment_by_x.filter(|by_x| *by_x);
+        let leave_unchanged = quirks.memory_leave_i_unchanged.or(increment_by_x.map(|_| false));
+        self.load_store = leave_unchanged.map(|unchanged| !unchanged).or(self.load_store);
+    }
+}
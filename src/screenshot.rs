@@ -0,0 +1,68 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+
+use crate::colour::Colour;
+use crate::core::{MACHINE_SCREEN_WIDTH, MACHINE_SCREEN_HEIGHT};
+use crate::postfx::Effects;
+
+// the screen as it would be saved, scale times the native 64x32. frame already has the
+// palette applied, and effects are the post processing to include, if any
+pub fn render(frame: &[Colour], scale: u32, effects: Option<Effects>) -> (Vec<Colour>, usize, usize) {
+    let scale = scale.max(1) as usize;
+    let width = MACHINE_SCREEN_WIDTH * scale;
+    let height = MACHINE_SCREEN_HEIGHT * scale;
+
+    if let Some(effects) = effects.filter(|effects| *effects != Effects::default()) {
+        let pixels = effects.render(frame, MACHINE_SCREEN_WIDTH, MACHINE_SCREEN_HEIGHT, width, height);
+        return (pixels, width, height);
+    }
+
+    let mut pixels = Vec::with_capacity(width * height);
+    for y in 0..height {
+        let row = &frame[(y / scale) * MACHINE_SCREEN_WIDTH..][..MACHINE_SCREEN_WIDTH];
+        for x in 0..width {
+            pixels.push(row[x / scale]);
+        }
+    }
+
+    return (pixels, width, height);
+}
+
+pub fn write_png(path: &Path, pixels: &[Colour], width: usize, height: usize) -> io::Result<()> {
+    let file = File::create(path)?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width as u32, height as u32);
+    encoder.set_color(png::ColorType::RGBA);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let data: Vec<u8> = pixels.iter().flat_map(|colour| colour.to_rgba().to_vec()).collect();
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&data)?;
+
+    return Ok(());
+}
+
+// the first of <dir>/<name>-001.<extension>, <name>-002.<extension> and so on that isn't taken
+// yet, making the directory if it isn't there. an empty extension leaves it off, for
+// directories
+pub fn next_path(dir: &Path, name: &str, extension: &str) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+
+    let name = safe_name(name);
+    let path = (1..)
+        .map(|number| dir.join(format!("{}-{:03}", name, number)).with_extension(extension))
+        .find(|path| !path.exists())
+        .unwrap();
+
+    return Ok(path);
+}
+
+// name cut down to something that's safe as a file name everywhere
+pub fn safe_name(name: &str) -> String {
+    let name: String = name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+    let name = name.trim_matches('-');
+
+    return if name.is_empty() { "chip8".to_string() } else { name.to_string() };
+}
@@ -0,0 +1,61 @@
+use std::fs;
+use std::path::Path;
+
+// which keys are held down when, for running roms without anyone at the keyboard. a script has
+// a line for each change to the keys held: the frame it happens on, then the keys held from
+// then on as hex digits or - for none, and # starts a comment. e.g. press 5, let go, then hold
+// 4 and 6 together:
+//
+// 60 5
+// 65 -
+// 120 46
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Script {
+    pub changes: Vec<(u64, u16)> // the frame and the keys held from then on, a bit each
+}
+
+impl Script {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("couldn't read {}: {}", path.display(), e))?;
+        return Script::parse(&text).map_err(|e| format!("{}: {}", path.display(), e));
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut changes: Vec<(u64, u16)> = vec![];
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let invalid = || format!("line {} should be a frame and the keys held, e.g. 60 5A", i + 1);
+            let mut parts = line.split_whitespace();
+            let frame: u64 = parts.next().and_then(|frame| frame.parse().ok()).ok_or_else(invalid)?;
+            let keys = match parts.next().ok_or_else(invalid)? {
+                "-" => 0,
+                keys => keys.chars().try_fold(0u16, |held, key| {
+                    key.to_digit(16).map(|key| held | 1 << key)
+                }).ok_or_else(invalid)?,
+            };
+            if parts.next().is_some() {
+                return Err(invalid());
+            }
+
+            if changes.last().is_some_and(|(last, _)| *last > frame) {
+                return Err(format!("line {} goes back to frame {}, they need to be in order", i + 1, frame));
+            }
+            changes.push((frame, keys));
+        }
+
+        return Ok(Script { changes });
+    }
+
+    // the keys held on a frame, a bit each, with nothing held before the first change
+    pub fn keys_at(&self, frame: u64) -> u16 {
+        return self.changes.iter()
+            .take_while(|(from, _)| *from <= frame)
+            .last()
+            .map_or(0, |(_, keys)| *keys);
+    }
+}
@@ -0,0 +1,351 @@
+use std::collections::VecDeque;
+use std::convert::TryInto;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::assembler;
+use crate::core::{Chip8, ExecutionError};
+
+// a log of every instruction the machine runs, for working out what a program was doing when
+// it went wrong. each entry has how many instructions ran before it, where it was, the opcode,
+// and whichever of the registers and I it changed. the text format is a line per instruction
+// with tab separated columns:
+//
+// 12	204	F029	LD F, V0	I=050
+// 13	206	D015	DRW V0, V1, 5	VF=00
+//
+// the binary format starts with TRACE_MAGIC and then has a record per instruction: the cycle
+// as a u64, pc and opcode as u16s, a u8 count of changes, and then for each change a u8 saying
+// what changed (0 to 15 for the registers, CHANGE_INDEX for I) followed by the new value, a u8
+// for registers and a u16 for I. everything is little endian
+pub const TRACE_MAGIC: &[u8; 5] = b"C8TR\x01";
+const CHANGE_INDEX: u8 = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TraceFormat {
+    Text,
+    Binary
+}
+
+impl TraceFormat {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "text" => Ok(TraceFormat::Text),
+            "binary" => Ok(TraceFormat::Binary),
+            _ => Err(format!("unknown trace format: {}", value)),
+        }
+    }
+}
+
+// an inclusive range of addresses to trace instructions from
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AddressRange {
+    pub start: u16,
+    pub end: u16
+}
+
+impl AddressRange {
+    // a single address or a range in hex, e.g. 200 or 200-2FF
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let parse_address = |address: &str| {
+            let address = address.trim().trim_start_matches("0x").trim_start_matches('#');
+            u16::from_str_radix(address, 16).map_err(|_| format!("expected a hex address, got {}", value))
+        };
+
+        let mut parts = value.splitn(2, '-');
+        let start = parse_address(parts.next().unwrap_or(""))?;
+        let end = match parts.next() {
+            Some(end) => parse_address(end)?,
+            None => start,
+        };
+
+        if end < start {
+            return Err(format!("address range {} ends before it starts", value));
+        }
+
+        return Ok(AddressRange { start, end });
+    }
+
+    pub fn contains(&self, address: u16) -> bool {
+        return self.start <= address && address <= self.end;
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraceOptions {
+    pub format: TraceFormat,
+    pub ranges: Vec<AddressRange>, // only instructions in these get traced, or all of them if empty
+    pub last: Option<usize>        // keep just this many in memory, written out on an execution error
+}
+
+impl Default for TraceOptions {
+    fn default() -> Self {
+        TraceOptions {
+            format: TraceFormat::Text,
+            ranges: vec![],
+            last: None
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Change {
+    Register(u8, u8), // which one and its new value
+    Index(u16)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub cycle: u64, // instructions run before this one since tracing started
+    pub pc: u16,
+    pub opcode: u16,
+    pub changes: Vec<Change>
+}
+
+impl Entry {
+    // runs a single instruction and describes what it did
+    pub fn step(machine: &mut Chip8, cycle: u64) -> (Entry, Result<(), ExecutionError>) {
+        let pc = machine.pc();
+        let memory = machine.memory();
+        let byte = |address: u16| *memory.get(address as usize).unwrap_or(&0);
+        let opcode = u16::from_be_bytes([byte(pc), byte(pc.wrapping_add(1))]);
+        let registers = *machine.registers();
+        let index = machine.index();
+
+        let result = machine.step();
+
+        let mut changes = vec![];
+        for (register, (before, after)) in registers.iter().zip(machine.registers().iter()).enumerate() {
+            if before != after {
+                changes.push(Change::Register(register as u8, *after));
+            }
+        }
+        if machine.index() != index {
+            changes.push(Change::Index(machine.index()));
+        }
+
+        return (Entry { cycle, pc, opcode, changes }, result);
+    }
+
+    pub fn to_text(&self) -> String {
+        let changes: Vec<String> = self.changes.iter().map(|change| match change {
+            Change::Register(register, value) => format!("V{:X}={:02X}", register, value),
+            Change::Index(index) => format!("I={:03X}", index),
+        }).collect();
+
+        return format!(
+            "{}\t{:03X}\t{:04X}\t{}\t{}",
+            self.cycle, self.pc, self.opcode, assembler::disassemble(self.opcode), changes.join(" ")
+        );
+    }
+
+    fn write_binary<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        out.write_all(&self.cycle.to_le_bytes())?;
+        out.write_all(&self.pc.to_le_bytes())?;
+        out.write_all(&self.opcode.to_le_bytes())?;
+        out.write_all(&[self.changes.len() as u8])?;
+
+        for change in self.changes.iter() {
+            match change {
+                Change::Register(register, value) => out.write_all(&[*register, *value])?,
+                Change::Index(index) => {
+                    out.write_all(&[CHANGE_INDEX])?;
+                    out.write_all(&index.to_le_bytes())?;
+                },
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// runs the machine in place of Chip8::cycle, logging each instruction as it goes
+pub struct Tracer {
+    path: PathBuf,
+    out: BufWriter<File>,
+    options: TraceOptions,
+    cycle: u64,
+    recent: VecDeque<Entry>, // only used when keeping the last few
+    error: Option<io::Error> // the first write that failed, after which writing stops
+}
+
+impl Tracer {
+    pub fn create(path: &Path, options: TraceOptions) -> io::Result<Self> {
+        let mut out = BufWriter::new(File::create(path)?);
+        if options.format == TraceFormat::Binary {
+            out.write_all(TRACE_MAGIC)?;
+        }
+
+        return Ok(Tracer {
+            path: path.to_path_buf(),
+            out,
+            options,
+            cycle: 0,
+            recent: VecDeque::new(),
+            error: None
+        });
+    }
+
+    pub fn path(&self) -> &Path {
+        return &self.path;
+    }
+
+    // the same as Chip8::cycle, a frame's worth of instructions and then the timers
+    pub fn cycle(&mut self, machine: &mut Chip8) -> Result<(), ExecutionError> {
+        for _ in 0..machine.tick_rate {
+            self.step(machine)?;
+        }
+
+        machine.tick_timers();
+
+        Ok(())
+    }
+
+    pub fn step(&mut self, machine: &mut Chip8) -> Result<(), ExecutionError> {
+        let (entry, result) = Entry::step(machine, self.cycle);
+        self.cycle += 1;
+
+        let ranges = &self.options.ranges;
+        if ranges.is_empty() || ranges.iter().any(|range| range.contains(entry.pc)) {
+            self.record(entry);
+        }
+
+        // this is what the last few were being kept for
+        if result.is_err() {
+            while let Some(entry) = self.recent.pop_front() {
+                self.write(&entry);
+            }
+            if let Err(e) = self.out.flush() {
+                self.error.get_or_insert(e);
+            }
+        }
+
+        return result;
+    }
+
+    // writes the entry out, or keeps it for later
+    fn record(&mut self, entry: Entry) {
+        match self.options.last {
+            Some(last) => {
+                if self.recent.len() == last {
+                    self.recent.pop_front();
+                }
+                if last > 0 {
+                    self.recent.push_back(entry);
+                }
+            },
+            None => self.write(&entry),
+        }
+    }
+
+    // flushes everything out, returning the first error from writing if there was one
+    pub fn finish(mut self) -> io::Result<()> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+
+        return self.out.flush();
+    }
+
+    fn write(&mut self, entry: &Entry) {
+        if self.error.is_some() {
+            return;
+        }
+
+        let result = match self.options.format {
+            TraceFormat::Text => writeln!(self.out, "{}", entry.to_text()),
+            TraceFormat::Binary => entry.write_binary(&mut self.out),
+        };
+
+        if let Err(e) = result {
+            self.error = Some(e);
+        }
+    }
+}
+
+// reads a trace written in either format back in, telling them apart by the magic number
+pub fn read_trace(path: &Path) -> Result<Vec<Entry>, String> {
+    let contents = std::fs::read(path).map_err(|e| format!("couldn't read {}: {}", path.display(), e))?;
+
+    let result = match contents.strip_prefix(&TRACE_MAGIC[..]) {
+        Some(records) => parse_binary(records),
+        None => parse_text(&String::from_utf8_lossy(&contents)),
+    };
+
+    return result.map_err(|e| format!("{}: {}", path.display(), e));
+}
+
+fn parse_text(text: &str) -> Result<Vec<Entry>, String> {
+    let mut entries = vec![];
+
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let invalid = || format!("line {} isn't a trace entry", i + 1);
+        let columns: Vec<&str> = line.split('\t').collect();
+        if columns.len() != 5 {
+            return Err(invalid());
+        }
+
+        let cycle = columns[0].parse().map_err(|_| invalid())?;
+        let pc = u16::from_str_radix(columns[1], 16).map_err(|_| invalid())?;
+        let opcode = u16::from_str_radix(columns[2], 16).map_err(|_| invalid())?;
+
+        let mut changes = vec![];
+        for change in columns[4].split_whitespace() {
+            let mut parts = change.splitn(2, '=');
+            let target = parts.next().unwrap_or("");
+            let value = parts.next().ok_or_else(invalid)?;
+            changes.push(match target {
+                "I" => Change::Index(u16::from_str_radix(value, 16).map_err(|_| invalid())?),
+                _ => {
+                    let register = target.strip_prefix('V').and_then(|register| u8::from_str_radix(register, 16).ok());
+                    Change::Register(register.ok_or_else(invalid)?, u8::from_str_radix(value, 16).map_err(|_| invalid())?)
+                },
+            });
+        }
+
+        entries.push(Entry { cycle, pc, opcode, changes });
+    }
+
+    return Ok(entries);
+}
+
+fn parse_binary(mut records: &[u8]) -> Result<Vec<Entry>, String> {
+    let mut entries = vec![];
+
+    // takes n bytes off the front
+    fn take<'a>(records: &mut &'a [u8], n: usize) -> Result<&'a [u8], String> {
+        if records.len() < n {
+            return Err("the trace ends part way through an entry".to_string());
+        }
+        let (taken, rest) = records.split_at(n);
+        *records = rest;
+
+        return Ok(taken);
+    }
+
+    while !records.is_empty() {
+        let cycle = u64::from_le_bytes(take(&mut records, 8)?.try_into().unwrap());
+        let pc = u16::from_le_bytes(take(&mut records, 2)?.try_into().unwrap());
+        let opcode = u16::from_le_bytes(take(&mut records, 2)?.try_into().unwrap());
+        let count = take(&mut records, 1)?[0];
+
+        let mut changes = vec![];
+        for _ in 0..count {
+            let target = take(&mut records, 1)?[0];
+            changes.push(match target {
+                CHANGE_INDEX => Change::Index(u16::from_le_bytes(take(&mut records, 2)?.try_into().unwrap())),
+                0..=15 => Change::Register(target, take(&mut records, 1)?[0]),
+                _ => return Err(format!("unknown change {} in the entry for cycle {}", target, cycle)),
+            });
+        }
+
+        entries.push(Entry { cycle, pc, opcode, changes });
+    }
+
+    return Ok(entries);
+}
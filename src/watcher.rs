@@ -0,0 +1,86 @@
+use std::convert::TryFrom;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+use serde::Deserialize;
+
+// how often the rom's modification time gets looked at. it's only a stat, but there's no
+// point doing it every frame
+const CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+// what happens when the watched rom changes on disk
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum WatchMode {
+    Off,
+    Reset,   // start the new version from scratch, as if it had just been loaded
+    Preserve // swap in the new program but keep the registers, timers, screen and the rest of memory
+}
+
+impl WatchMode {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "off" => Ok(WatchMode::Off),
+            "reset" => Ok(WatchMode::Reset),
+            "preserve" => Ok(WatchMode::Preserve),
+            _ => Err(format!("unknown watch mode: {}", value)),
+        }
+    }
+}
+
+impl TryFrom<String> for WatchMode {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        WatchMode::parse(&value)
+    }
+}
+
+// keeps an eye on a file by polling its modification time, which is good enough for picking up
+// saves from an editor or assembler without needing anything platform specific
+pub struct Watcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    next_check: Instant
+}
+
+impl Watcher {
+    pub fn new(path: &Path) -> Self {
+        Watcher {
+            path: path.to_path_buf(),
+            modified: modified(path),
+            next_check: Instant::now() + CHECK_INTERVAL
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        return &self.path;
+    }
+
+    // whether the file has changed since the last time this said so. a file that's gone
+    // missing doesn't count, since editors often delete and recreate files when saving
+    pub fn changed(&mut self) -> bool {
+        let now = Instant::now();
+        if now < self.next_check {
+            return false;
+        }
+        self.next_check = now + CHECK_INTERVAL;
+
+        let modified = match modified(&self.path) {
+            Some(modified) => modified,
+            None => return false,
+        };
+
+        if self.modified == Some(modified) {
+            return false;
+        }
+
+        self.modified = Some(modified);
+
+        return true;
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    return fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+}
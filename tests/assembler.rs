@@ -0,0 +1,152 @@
+#![allow(clippy::needless_return)]
+
+use std::fs;
+use std::path::PathBuf;
+
+use chip8_rust::assembler::{self, AssemblyError};
+
+fn assemble_one(source: &str) -> u16 {
+    let program = assembler::assemble(source).unwrap();
+    assert_eq!(program.len(), 2, "{}", source);
+    return u16::from_be_bytes([program[0], program[1]]);
+}
+
+fn errors(source: &str) -> Vec<(usize, String)> {
+    return assembler::assemble(source).unwrap_err().into_iter()
+        .map(|AssemblyError { line, message }| (line, message))
+        .collect();
+}
+
+#[test]
+fn every_instruction_assembles_to_its_opcode() {
+    let instructions = [
+        ("CLS", 0x00E0),
+        ("RET", 0x00EE),
+        ("SYS 0x123", 0x0123),
+        ("JP 0x234", 0x1234),
+        ("CALL 0x345", 0x2345),
+        ("SE V1, 0x22", 0x3122),
+        ("SNE V2, 0x33", 0x4233),
+        ("SE V3, V4", 0x5340),
+        ("LD V5, 0x66", 0x6566),
+        ("ADD V6, 0x77", 0x7677),
+        ("LD V7, V8", 0x8780),
+        ("OR V8, V9", 0x8891),
+        ("AND V9, VA", 0x89A2),
+        ("XOR VA, VB", 0x8AB3),
+        ("ADD VB, VC", 0x8BC4),
+        ("SUB VC, VD", 0x8CD5),
+        ("SHR VD", 0x8D06),
+        ("SHR VD, VE", 0x8DE6),
+        ("SUBN VE, VF", 0x8EF7),
+        ("SHL VF", 0x8F0E),
+        ("SHL VF, V0", 0x8F0E),
+        ("SNE V0, V1", 0x9010),
+        ("LD I, 0x456", 0xA456),
+        ("JP V0, 0x567", 0xB567),
+        ("RND V1, 0xFF", 0xC1FF),
+        ("DRW V2, V3, 15", 0xD23F),
+        ("SKP V4", 0xE49E),
+        ("SKNP V5", 0xE5A1),
+        ("LD V6, DT", 0xF607),
+        ("LD V7, K", 0xF70A),
+        ("LD DT, V8", 0xF815),
+        ("LD ST, V9", 0xF918),
+        ("ADD I, VA", 0xFA1E),
+        ("LD F, VB", 0xFB29),
+        ("LD B, VC", 0xFC33),
+        ("LD [I], VD", 0xFD55),
+        ("LD VE, [I]", 0xFE65),
+    ];
+
+    for (source, opcode) in instructions.iter() {
+        assert_eq!(assemble_one(source), *opcode, "{}", source);
+        assert_eq!(assemble_one(&source.to_ascii_lowercase()), *opcode, "{}", source);
+    }
+}
+
+#[test]
+fn numbers_can_be_decimal_hex_or_binary() {
+    let program = assembler::assemble("DB 165, #A5, $A5, 0xA5, 0XA5, %10100101, 0b10100101").unwrap();
+    assert_eq!(program, [0xA5; 7]);
+
+    assert_eq!(assembler::assemble("DW $1234, 4660").unwrap(), [0x12, 0x34, 0x12, 0x34]);
+}
+
+#[test]
+fn labels_can_be_used_before_and_after_they_are_defined() {
+    let source = "
+        start:  JP end       ; forward
+                DB 1, 2
+        loop:   JP loop
+        end:    CALL start   ; backward
+        data:
+                LD I, data
+    ";
+
+    let program = assembler::assemble(source).unwrap();
+    assert_eq!(program, [
+        0x12, 0x06, // start is 0x200, end comes after an instruction and two bytes
+        0x01, 0x02,
+        0x12, 0x04,
+        0x22, 0x00,
+        0xA2, 0x08  // a label on a line of its own is the address of the next thing
+    ]);
+
+    // and they're no more case sensitive than the mnemonics
+    assert_eq!(assembler::assemble("Here: jp HERE").unwrap(), [0x12, 0x00]);
+}
+
+#[test]
+fn errors_give_the_line_they_are_on() {
+    let source = "CLS\nFOO V0\n\nLD V0, 256\nDRW V0, V1, 16\nJP 0x1000\nJP nowhere";
+    assert_eq!(errors(source), [
+        (2, "unknown instruction: FOO".to_string()),
+        (4, "256 is too big, it needs to be at most 255".to_string()),
+        (5, "16 is too big, it needs to be at most 15".to_string()),
+        (6, "4096 is too big, it needs to be at most 4095".to_string()),
+        (7, "unknown label: nowhere".to_string()),
+    ]);
+}
+
+#[test]
+fn bad_operands_and_labels_are_errors() {
+    assert_eq!(errors("\nLD V0"), [(2, "invalid operands for LD".to_string())]);
+    assert_eq!(errors("LD VG, 1"), [(1, "invalid register: VG".to_string())]);
+    assert_eq!(errors("DB 0x"), [(1, "invalid operand: 0x".to_string())]);
+    assert_eq!(errors("DB"), [(1, "DB needs at least one value".to_string())]);
+    assert_eq!(errors("V0: CLS"), [(1, "invalid label: V0".to_string())]);
+    assert_eq!(errors("a: CLS\nA: CLS"), [(2, "A is defined more than once".to_string())]);
+}
+
+#[test]
+fn only_source_files_are_assembled() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("assembler");
+    fs::create_dir_all(&dir).unwrap();
+
+    let source = dir.join("game.ASM");
+    fs::write(&source, "CLS").unwrap();
+    assert!(assembler::is_source(&source));
+    assert_eq!(assembler::read_program(&source), Ok(vec![0x00, 0xE0]));
+
+    // a rom is loaded as it is, even if it happens to look like source
+    let rom = dir.join("game.ch8");
+    fs::write(&rom, "CLS").unwrap();
+    assert!(!assembler::is_source(&rom));
+    assert_eq!(assembler::read_program(&rom), Ok(b"CLS".to_vec()));
+
+    fs::write(&source, "CLS\nNOPE").unwrap();
+    assert_eq!(assembler::read_program(&source), Err("line 2: unknown instruction: NOPE".to_string()));
+}
+
+#[test]
+fn disassembling_then_assembling_gives_back_the_same_opcode() {
+    for opcode in 0..=0xFFFF_u16 {
+        let source = assembler::disassemble(opcode);
+        assert_eq!(assembler::assemble(&source), Ok(opcode.to_be_bytes().to_vec()), "{:04X}: {}", opcode, source);
+    }
+
+    // anything that isn't an instruction comes out as data
+    assert_eq!(assembler::disassemble(0x5121), "DW #5121");
+    assert_eq!(assembler::disassemble(0xF0FF), "DW #F0FF");
+}
@@ -0,0 +1,130 @@
+#![allow(clippy::needless_return)]
+
+// block translation has to give exactly what the interpreter does, frame for frame, including
+// when there are errors part way through a frame and when a program writes over its own code
+
+use std::path::Path;
+
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use chip8_rust::assembler;
+use chip8_rust::core::{Chip8, Quirks, ExecutionError, DEFAULT_TICK_RATE};
+
+const FRAMES: usize = 200;
+
+fn compare(name: &str, program: &[u8], quirks: Quirks, seed: u64) {
+    compare_at_tick_rate(name, program, quirks, seed, DEFAULT_TICK_RATE);
+}
+
+// runs the program both ways with the same keys, checking everything after every frame
+fn compare_at_tick_rate(name: &str, program: &[u8], quirks: Quirks, seed: u64, tick_rate: u32) {
+    let mut machines = [Chip8::new(), Chip8::new()];
+    for (machine, translate_blocks) in machines.iter_mut().zip([true, false].iter()) {
+        machine.translate_blocks = *translate_blocks;
+        machine.tick_rate = tick_rate;
+        machine.quirks = quirks;
+        machine.seed_random(seed);
+        machine.load_program(program).unwrap();
+    }
+
+    let mut keys = StdRng::seed_from_u64(seed);
+    for frame in 0..FRAMES {
+        let pressed: Vec<u8> = (0..16).map(|_| keys.gen_bool(0.2) as u8).collect();
+        let results: Vec<Result<(), ExecutionError>> = machines.iter_mut().map(|machine| {
+            machine.keypad.copy_from_slice(&pressed);
+            machine.cycle()
+        }).collect();
+
+        let (blocks, interpreter) = (&machines[0], &machines[1]);
+        let same = results[0] == results[1]
+            && blocks.pc() == interpreter.pc()
+            && blocks.index() == interpreter.index()
+            && blocks.registers() == interpreter.registers()
+            && blocks.stack_depth() == interpreter.stack_depth()
+            && blocks.delay_timer() == interpreter.delay_timer()
+            && blocks.sound_timer() == interpreter.sound_timer()
+            && blocks.memory()[..] == interpreter.memory()[..]
+            && blocks.video[..] == interpreter.video[..];
+        assert!(same, "{}, frame {}: blocks gave {:?} at {:03X}, the interpreter {:?} at {:03X}",
+            name, frame, results[0], blocks.pc(), results[1], interpreter.pc());
+    }
+}
+
+// random instructions, with the jumps and calls kept inside the program and I pointed into it,
+// so stores land on code now and then
+fn random_program(rng: &mut StdRng) -> Vec<u8> {
+    let length: u16 = rng.gen_range(1, 128);
+    let end = 0x200 + 2 * length;
+
+    let mut program = vec![];
+    for _ in 0..length {
+        let mut opcode: u16 = rng.gen();
+        if let 0x1 | 0x2 | 0xA | 0xB = opcode >> 12 {
+            opcode = (opcode & 0xF000) | rng.gen_range(0x200, end);
+        }
+        program.extend_from_slice(&opcode.to_be_bytes());
+    }
+
+    return program;
+}
+
+#[test]
+fn random_programs_run_the_same_as_the_interpreter() {
+    let profiles = [Quirks::default(), Quirks::chip8(), Quirks::schip()];
+    for seed in 0..300 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let program = random_program(&mut rng);
+        let quirks = profiles[seed as usize % profiles.len()];
+
+        compare(&format!("seed {}", seed), &program, quirks, seed);
+    }
+}
+
+#[test]
+fn the_bench_roms_run_the_same_as_the_interpreter() {
+    for rom in ["arithmetic", "sprites", "memory"].iter() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("benches/roms").join(format!("{}.asm", rom));
+        let program = assembler::read_program(&path).unwrap();
+
+        compare(rom, &program, Quirks::default(), 0);
+    }
+}
+
+#[test]
+fn code_written_over_is_translated_again() {
+    // the store at the end of each pass writes V0 and V1 over the ADD at count, in the same
+    // block as it, so the amount added to V2 goes up by one each time round
+    let source = "
+        LD V0, #72      ; the first byte of ADD V2
+        LD V1, 0
+loop:   ADD V1, 1
+count:  ADD V2, 0
+        LD I, count
+        LD [I], V1
+        JP loop
+";
+    let program = assembler::assemble(source).unwrap();
+
+    compare("self modifying", &program, Quirks::default(), 0);
+    compare("self modifying", &program, Quirks::chip8(), 0);
+}
+
+#[test]
+fn writes_at_the_far_end_of_the_longest_block_are_caught() {
+    // 63 ADDs and then the one written over, which makes the first block as long as they get,
+    // with the store that changes its last instruction in the block after it. frames have to be
+    // long enough for the whole block to run from the start
+    let source = format!("
+long:   {}
+last:   ADD V2, 0
+        LD V0, #72      ; the first byte of ADD V2
+        ADD V1, 1
+        LD I, last
+        LD [I], V1
+        JP long
+", "ADD V3, 1\n        ".repeat(63));
+    let program = assembler::assemble(&source).unwrap();
+
+    compare_at_tick_rate("longest block", &program, Quirks::default(), 0, 1000);
+}
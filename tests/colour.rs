@@ -0,0 +1,111 @@
+#![allow(clippy::needless_return)]
+
+use chip8_rust::colour::{linear_to_srgb, srgb_to_linear, Colour};
+
+#[test]
+fn full_channels_normalise_to_one() {
+    assert_eq!(Colour::new(255, 255, 255).to_array(), [1.0, 1.0, 1.0, 1.0]);
+    assert_eq!(Colour::with_alpha(0, 0, 0, 0).to_array(), [0.0, 0.0, 0.0, 0.0]);
+    assert_eq!(Colour::new(255, 255, 255).to_linear_array(), [1.0, 1.0, 1.0, 1.0]);
+}
+
+#[test]
+fn hex_is_parsed_with_or_without_the_hash_and_alpha() {
+    assert_eq!(Colour::from_hex("#FFAA00"), Some(Colour::new(0xFF, 0xAA, 0x00)));
+    assert_eq!(Colour::from_hex("ffaa00"), Some(Colour::new(0xFF, 0xAA, 0x00)));
+    assert_eq!(Colour::from_hex("#10203040"), Some(Colour::with_alpha(0x10, 0x20, 0x30, 0x40)));
+    assert_eq!(Colour::parse("#102030"), Some(Colour::new(0x10, 0x20, 0x30)));
+}
+
+#[test]
+fn hex_goes_back_out_the_way_it_came_in() {
+    assert_eq!(Colour::new(0xFF, 0xAA, 0x00).to_hex(), "#FFAA00");
+    assert_eq!(Colour::with_alpha(0x10, 0x20, 0x30, 0x40).to_hex(), "#10203040");
+    assert_eq!(Colour::from_argb(0x80FFAA00), Colour::with_alpha(0xFF, 0xAA, 0x00, 0x80));
+    assert_eq!(Colour::with_alpha(0xFF, 0xAA, 0x00, 0x80).to_argb(), 0x80FFAA00);
+}
+
+#[test]
+fn css_names_are_parsed_in_any_case() {
+    assert_eq!(Colour::parse("cornflowerblue"), Some(Colour::new(100, 149, 237)));
+    assert_eq!(Colour::parse("AliceBlue"), Some(Colour::new(240, 248, 255)));
+    assert_eq!(Colour::parse("yellowgreen"), Some(Colour::new(154, 205, 50)));
+    assert_eq!(Colour::parse("transparent"), Some(Colour::with_alpha(0, 0, 0, 0)));
+}
+
+#[test]
+fn invalid_colours_are_rejected() {
+    for text in ["", "#", "#FFF", "#FFAA0", "#FFAA000", "#GGAA00", "FFAA00FF00", "#ÿÿÿ", "notacolour", "light blue"] {
+        assert_eq!(Colour::parse(text), None, "{:?}", text);
+    }
+}
+
+#[test]
+fn colours_are_read_from_config_as_text_or_rgb() {
+    #[derive(serde::Deserialize)]
+    struct Colours {
+        hex: Colour,
+        name: Colour,
+        rgb: Colour
+    }
+
+    let colours: Colours = toml::from_str("hex = \"#FFAA00\"\nname = \"red\"\nrgb = [1, 2, 3]").unwrap();
+    assert_eq!(colours.hex, Colour::new(0xFF, 0xAA, 0x00));
+    assert_eq!(colours.name, Colour::new(255, 0, 0));
+    assert_eq!(colours.rgb, Colour::new(1, 2, 3));
+
+    assert!(toml::from_str::<Colours>("hex = \"#FFAA\"\nname = \"red\"\nrgb = [1, 2, 3]").is_err());
+}
+
+#[test]
+fn every_channel_value_survives_a_trip_through_linear() {
+    for value in 0..=255 {
+        let colour = Colour::with_alpha(value, value, value, value);
+        assert_eq!(Colour::from_linear_array(colour.to_linear_array()), colour);
+    }
+}
+
+#[test]
+fn srgb_conversion_matches_known_values() {
+    // mid grey in srgb is a lot darker than half in linear light
+    assert!((srgb_to_linear(0.5) - 0.214).abs() < 0.001);
+    assert!((linear_to_srgb(0.214) - 0.5).abs() < 0.001);
+    assert_eq!(srgb_to_linear(0.0), 0.0);
+    assert!((srgb_to_linear(1.0) - 1.0).abs() < 1e-6);
+
+    // either side of where the curve switches from linear to the power law
+    for value in [0.0, 0.001, 0.04, 0.05, 0.2, 0.8, 1.0] {
+        assert!((linear_to_srgb(srgb_to_linear(value)) - value).abs() < 1e-5, "{}", value);
+    }
+}
+
+#[test]
+fn lerp_gives_the_ends_at_zero_and_one() {
+    let black = Colour::new(0, 0, 0);
+    let orange = Colour::new(0xFF, 0xAA, 0x00);
+
+    assert_eq!(black.lerp(orange, 0.0), black);
+    assert_eq!(black.lerp(orange, 1.0), orange);
+
+    // and clamps past them
+    assert_eq!(black.lerp(orange, -1.0), black);
+    assert_eq!(black.lerp(orange, 2.0), orange);
+
+    // halfway in linear light is brighter than halfway in srgb
+    assert!(black.lerp(Colour::new(255, 255, 255), 0.5).red() > 128);
+}
+
+#[test]
+fn blending_opaque_and_transparent_gives_the_ends() {
+    let background = Colour::new(0x10, 0x20, 0x30);
+    let foreground = Colour::new(0xFF, 0xAA, 0x00);
+
+    assert_eq!(foreground.blend_over(background), foreground);
+    assert_eq!(Colour::with_alpha(0xFF, 0xAA, 0x00, 0).blend_over(background), background);
+    assert_eq!(Colour::with_alpha(0, 0, 0, 0).blend_over(Colour::with_alpha(0, 0, 0, 0)), Colour::with_alpha(0, 0, 0, 0));
+
+    // half over opaque stays opaque, part way between the two
+    let half = Colour::with_alpha(255, 255, 255, 128).blend_over(Colour::new(0, 0, 0));
+    assert_eq!(half.alpha(), 255);
+    assert!(half.red() > 0 && half.red() < 255);
+}
@@ -0,0 +1,146 @@
+#![allow(clippy::needless_return)]
+
+// settings are layered from lowest to highest: built in defaults, [defaults] in the config file,
+// the rom database, [roms.<sha-1>] and the command line. each layer only changes what it sets
+
+use chip8_rust::colour::Colour;
+use chip8_rust::config::{Config, Overrides, QuirkOverrides};
+use chip8_rust::core::{Quirks, DEFAULT_TICK_RATE};
+use chip8_rust::palette::Palette;
+
+const HASH: &str = "92a5652d382a18e89c4881ec57041fc7d885ca80";
+const OTHER_HASH: &str = "ebb9deb484be6f9599690d2cc276670112a66636";
+
+const CONFIG: &str = r#"
+[defaults]
+scale = 12
+tick_rate = 20
+profile = "chip8"
+
+[roms.92a5652d382a18e89c4881ec57041fc7d885ca80]
+name = "Spin"
+tick_rate = 40
+
+[roms.92a5652d382a18e89c4881ec57041fc7d885ca80.quirks]
+jump = true
+"#;
+
+fn config() -> Config {
+    return toml::from_str(CONFIG).unwrap();
+}
+
+fn database() -> Overrides {
+    return Overrides {
+        name: Some("Spin from the database".to_string()),
+        tick_rate: Some(30),
+        scale: Some(6),
+        profile: Some("schip".to_string()),
+        ..Overrides::default()
+    };
+}
+
+#[test]
+fn nothing_configured_gives_the_built_in_defaults() {
+    let settings = Config::default().resolve(HASH, None, &Overrides::default()).unwrap();
+
+    assert_eq!(settings.quirks, Quirks::default());
+    assert_eq!(settings.tick_rate, DEFAULT_TICK_RATE);
+    assert_eq!(settings.title, None);
+}
+
+#[test]
+fn config_defaults_override_the_built_in_ones() {
+    let settings = config().resolve(OTHER_HASH, None, &Overrides::default()).unwrap();
+
+    assert_eq!(settings.quirks, Quirks::chip8());
+    assert_eq!(settings.tick_rate, 20);
+    assert_eq!(settings.scale, 12);
+}
+
+#[test]
+fn the_database_overrides_config_defaults() {
+    let settings = config().resolve(OTHER_HASH, Some(&database()), &Overrides::default()).unwrap();
+
+    assert_eq!(settings.quirks, Quirks::schip());
+    assert_eq!(settings.tick_rate, 30);
+    assert_eq!(settings.scale, 6);
+    assert_eq!(settings.title.as_deref(), Some("Spin from the database"));
+}
+
+#[test]
+fn the_roms_own_section_overrides_the_database() {
+    let settings = config().resolve(HASH, Some(&database()), &Overrides::default()).unwrap();
+
+    // the database's profile, with the one quirk the rom's section changes
+    assert_eq!(settings.quirks, Quirks { jump: true, ..Quirks::schip() });
+    assert_eq!(settings.tick_rate, 40);
+    assert_eq!(settings.title.as_deref(), Some("Spin"));
+
+    // and whatever the section doesn't set comes from below it
+    assert_eq!(settings.scale, 6);
+}
+
+#[test]
+fn the_roms_section_is_found_whatever_the_case_of_the_hash() {
+    let settings = config().resolve(&HASH.to_ascii_uppercase(), None, &Overrides::default()).unwrap();
+    assert_eq!(settings.tick_rate, 40);
+}
+
+#[test]
+fn the_command_line_overrides_everything() {
+    let cli = Overrides {
+        tick_rate: Some(100),
+        profile: Some("default".to_string()),
+        quirks: QuirkOverrides { clip: Some(false), ..QuirkOverrides::default() },
+        ..Overrides::default()
+    };
+    let settings = config().resolve(HASH, Some(&database()), &cli).unwrap();
+
+    // the profile replaces every quirk set below it, then the single quirk goes on top
+    assert_eq!(settings.quirks, Quirks { clip: false, ..Quirks::default() });
+    assert_eq!(settings.tick_rate, 100);
+    assert_eq!(settings.title.as_deref(), Some("Spin"));
+}
+
+#[test]
+fn profiles_can_be_given_by_their_other_names() {
+    for (name, quirks) in [("chip-8", Quirks::chip8()), ("superchip", Quirks::schip())] {
+        let cli = Overrides { profile: Some(name.to_string()), ..Overrides::default() };
+        let settings = Config::default().resolve(HASH, None, &cli).unwrap();
+        assert_eq!(settings.quirks, quirks);
+    }
+}
+
+#[test]
+fn an_unknown_profile_is_rejected() {
+    let cli = Overrides { profile: Some("xochip".to_string()), ..Overrides::default() };
+    let e = Config::default().resolve(HASH, None, &cli).err().unwrap();
+    assert_eq!(e, "unknown quirk profile: xochip");
+
+    // wherever it comes from
+    let config: Config = toml::from_str("[defaults]\nprofile = \"nope\"").unwrap();
+    assert!(config.resolve(HASH, None, &Overrides::default()).is_err());
+}
+
+#[test]
+fn a_palette_can_be_a_theme_or_a_list_of_colours() {
+    let palette = |toml: &str| {
+        let config: Config = toml::from_str(&format!("[defaults]\npalette = {}", toml)).unwrap();
+        config.resolve(HASH, None, &Overrides::default()).unwrap().palette
+    };
+
+    assert_eq!(palette("\"amber\""), Palette::theme("amber").unwrap());
+    assert_eq!(palette("\"#101010, #FFB000\""), Palette::parse("#101010, #FFB000").unwrap());
+
+    let listed = palette("[\"#101010\", [255, 176, 0], \"red\", \"#00000080\"]");
+    assert_eq!(listed.colour_count(), 4);
+    assert_eq!(listed.background(), Colour::new(0x10, 0x10, 0x10));
+    assert_eq!(listed.colour(1), Colour::new(255, 176, 0));
+    assert_eq!(listed.colour(2), Colour::new(255, 0, 0));
+    assert_eq!(listed.colour(3), Colour::with_alpha(0, 0, 0, 0x80));
+
+    // lists still need 2, 4 or 16 colours that all make sense
+    for toml in ["[\"#101010\", \"#FFB000\", \"#000000\"]", "[\"#101010\", \"nope\"]", "[]"] {
+        assert!(toml::from_str::<Config>(&format!("[defaults]\npalette = {}", toml)).is_err(), "{}", toml);
+    }
+}
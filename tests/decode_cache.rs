@@ -0,0 +1,84 @@
+#![allow(clippy::needless_return)]
+
+// the decode cache has to be invisible: a program that writes over its own instructions sees
+// the new ones, and anything at all runs the same with it on or off
+
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use chip8_rust::assembler;
+use chip8_rust::core::Chip8;
+
+// runs LD V1 once so it's cached, then writes over it from V0 and V1 with I at the label given,
+// and runs it again
+const SELF_MODIFYING: &str = "
+        LD V2, 0
+loop:   ADD V2, 1
+target: DB #61          ; LD V1, 1 to start with
+operand: DB #01
+        SE V2, 1
+        JP done
+        LD V0, #61
+        LD V1, #07
+        LD I, {}
+        LD [I], {}
+        JP loop
+done:   JP done
+";
+
+fn run_self_modifying(address: &str, last: &str, decode_cache: bool) -> u8 {
+    let source = SELF_MODIFYING.replacen("{}", address, 1).replacen("{}", last, 1);
+    let program = assembler::assemble(&source).unwrap();
+
+    let mut machine = Chip8::new();
+    machine.decode_cache = decode_cache;
+    machine.load_program(&program).unwrap();
+    for _ in 0..20 {
+        machine.step().unwrap();
+    }
+
+    return machine.registers()[1];
+}
+
+#[test]
+fn writing_over_an_instruction_replaces_it() {
+    // both bytes, 61 07
+    assert_eq!(run_self_modifying("target", "V1", true), 0x07);
+    assert_eq!(run_self_modifying("target", "V1", false), 0x07);
+}
+
+#[test]
+fn writing_over_the_second_byte_of_an_instruction_replaces_it() {
+    // just the 61 from V0, over the 01, making LD V1, #61
+    assert_eq!(run_self_modifying("operand", "V0", true), 0x61);
+    assert_eq!(run_self_modifying("operand", "V0", false), 0x61);
+}
+
+#[test]
+fn random_programs_run_the_same_with_and_without_the_cache() {
+    for seed in 0..200 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let program: Vec<u8> = (0..rng.gen_range(2, 512)).map(|_| rng.gen()).collect();
+
+        let mut machines = [Chip8::new(), Chip8::new()];
+        for (machine, decode_cache) in machines.iter_mut().zip([true, false].iter()) {
+            machine.decode_cache = *decode_cache;
+            machine.seed_random(seed);
+            machine.load_program(&program).unwrap();
+        }
+
+        for step in 0..1000 {
+            let results: Vec<_> = machines.iter_mut().map(|machine| machine.step()).collect();
+            let (cached, uncached) = (&machines[0], &machines[1]);
+
+            let same = results[0] == results[1]
+                && cached.pc() == uncached.pc()
+                && cached.index() == uncached.index()
+                && cached.registers() == uncached.registers()
+                && cached.stack_depth() == uncached.stack_depth()
+                && cached.memory()[..] == uncached.memory()[..]
+                && cached.video[..] == uncached.video[..];
+            assert!(same, "seed {}, step {}: the cached run went differently", seed, step);
+        }
+    }
+}
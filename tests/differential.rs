@@ -0,0 +1,203 @@
+#![allow(clippy::needless_return)]
+
+// runs random programs on the real chip-8 and on the reference model side by side, checking
+// they agree on everything after every instruction. each case is made from its own seed, so a
+// failure can be replayed by running just that case
+
+mod reference;
+
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+use chip8_rust::assembler;
+use chip8_rust::core::{Chip8, ExecutionError, Quirks, PIXEL_ON};
+use reference::{Reference, Stop, PROGRAM_START, WIDTH};
+
+const CASES: u64 = 500;
+const PROGRAM_LENGTH: usize = 48; // instructions, after the ones that set up the registers
+const STEPS: usize = 200;
+
+#[test]
+fn default_quirks_match_the_reference() {
+    run_cases("default", Quirks::default());
+}
+
+#[test]
+fn chip8_quirks_match_the_reference() {
+    run_cases("chip8", Quirks::chip8());
+}
+
+#[test]
+fn schip_quirks_match_the_reference() {
+    run_cases("schip", Quirks::schip());
+}
+
+fn run_cases(profile: &str, quirks: Quirks) {
+    for seed in 0..CASES {
+        if let Err(message) = run_case(seed, quirks) {
+            panic!("{} quirks, case {}: {}", profile, seed, message);
+        }
+    }
+}
+
+fn run_case(seed: u64, quirks: Quirks) -> Result<(), String> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let program = random_program(&mut rng);
+
+    let mut machine = Chip8::new();
+    machine.load_program(&program).unwrap();
+    machine.quirks = quirks;
+    let mut model = Reference::new(&program, quirks);
+
+    // some keys held down for the whole case, so the key instructions go both ways
+    for key in 0..16 {
+        let pressed = rng.gen_bool(0.25);
+        machine.keypad[key] = pressed as u8;
+        model.keys[key] = pressed;
+    }
+
+    for step in 0..STEPS {
+        let pc = model.pc;
+        let describe = match model.opcode() {
+            Some(opcode) => format!("step {} at {:03X}, {:04X} {}", step, pc, opcode, assembler::disassemble(opcode)),
+            None => format!("step {} at {:03X}", step, pc),
+        };
+
+        // work out what should happen first, since the real one may not cope with what's
+        // out of scope here
+        let expected = model.step();
+        if expected == Err(Stop::OutOfScope) {
+            return Ok(());
+        }
+
+        let actual = machine.step().map_err(|e| match e {
+            ExecutionError::UnknownOpcode { .. } => Stop::UnknownOpcode,
+            ExecutionError::StackOverflow { .. } => Stop::StackOverflow,
+            ExecutionError::StackUnderflow { .. } => Stop::StackUnderflow,
+            ExecutionError::MemoryOutOfBounds { .. } => Stop::MemoryOutOfBounds,
+        });
+
+        if actual != expected {
+            return Err(format!("{}: expected {:?}, got {:?}", describe, expected, actual));
+        }
+
+        // after an error the real one has moved pc on while the model hasn't, and there's
+        // nothing more to compare anyway
+        if expected.is_err() {
+            return Ok(());
+        }
+
+        compare(&machine, &model).map_err(|difference| format!("{}: {}", describe, difference))?;
+    }
+
+    Ok(())
+}
+
+fn compare(machine: &Chip8, model: &Reference) -> Result<(), String> {
+    if machine.pc() != model.pc {
+        return Err(format!("pc is {:03X}, expected {:03X}", machine.pc(), model.pc));
+    }
+
+    for (register, (actual, expected)) in machine.registers().iter().zip(model.v.iter()).enumerate() {
+        if actual != expected {
+            return Err(format!("V{:X} is {:02X}, expected {:02X}", register, actual, expected));
+        }
+    }
+
+    if machine.index() != model.i {
+        return Err(format!("I is {:03X}, expected {:03X}", machine.index(), model.i));
+    }
+
+    if machine.stack_depth() as usize != model.stack.len() {
+        return Err(format!("stack depth is {}, expected {}", machine.stack_depth(), model.stack.len()));
+    }
+
+    if machine.delay_timer() != model.delay_timer || machine.sound_timer() != model.sound_timer {
+        return Err(format!(
+            "timers are {} and {}, expected {} and {}",
+            machine.delay_timer(), machine.sound_timer(), model.delay_timer, model.sound_timer
+        ));
+    }
+
+    if let Some(address) = (0..model.memory.len()).find(|address| machine.memory()[*address] != model.memory[*address]) {
+        return Err(format!(
+            "memory at {:03X} is {:02X}, expected {:02X}",
+            address, machine.memory()[address], model.memory[address]
+        ));
+    }
+
+    for (i, expected) in model.video.iter().enumerate() {
+        if (machine.video[i] & PIXEL_ON != 0) != *expected {
+            return Err(format!("pixel {}, {} should be {}", i % WIDTH, i / WIDTH, if *expected { "on" } else { "off" }));
+        }
+    }
+
+    Ok(())
+}
+
+// starts by loading every register and I with something random, then carries on with random
+// instructions. jumps and calls stay inside the program so it doesn't just run off into
+// zeroes, and I points past it so stores don't land on it too often
+fn random_program(rng: &mut StdRng) -> Vec<u8> {
+    let mut opcodes: Vec<u16> = (0..16).map(|x| 0x6000 | (x << 8) | rng.gen_range(0, 256)).collect();
+    opcodes.push(0xA000 | rng.gen_range(0x300, 0xF00));
+
+    let end = PROGRAM_START + 2 * (opcodes.len() + PROGRAM_LENGTH) as u16;
+    for _ in 0..PROGRAM_LENGTH {
+        opcodes.push(random_instruction(rng, end));
+    }
+
+    return opcodes.iter().flat_map(|opcode| opcode.to_be_bytes().to_vec()).collect();
+}
+
+fn random_instruction(rng: &mut StdRng, end: u16) -> u16 {
+    let x: u16 = rng.gen_range(0, 16);
+    let y: u16 = rng.gen_range(0, 16);
+    let kk: u16 = rng.gen_range(0, 256);
+    let target = PROGRAM_START + 2 * rng.gen_range(0, (end - PROGRAM_START) / 2);
+
+    // the registers are what most of the bugs would be in, so they come up the most
+    let xy = (x << 8) | (y << 4);
+    let arithmetic = [0x0, 0x1, 0x2, 0x3, 0x4, 0x5, 0x6, 0x7, 0xE];
+    let timers_and_memory = [0x07, 0x0A, 0x15, 0x18, 0x1E, 0x29, 0x33, 0x55, 0x65];
+
+    return match rng.gen_range(0, 16) {
+        0 => [0x00E0, 0x00EE][rng.gen_range(0, 2)],
+        1 => 0x1000 | target,
+        2 => 0x2000 | target,
+        3 => 0x3000 | (x << 8) | kk,
+        4 => 0x4000 | (x << 8) | kk,
+        5 => 0x5000 | xy,
+        6 => 0x6000 | (x << 8) | kk,
+        7 => 0x7000 | (x << 8) | kk,
+        8..=10 => 0x8000 | xy | arithmetic[rng.gen_range(0, arithmetic.len())],
+        11 => 0x9000 | xy,
+        12 => 0xA000 | rng.gen_range(0x300, 0x1000), // right up to the end, so stores and draws can run off it
+        13 => 0xD000 | xy | rng.gen_range(0, 16),
+        14 => [0xE09E, 0xE0A1][rng.gen_range(0, 2)] | (x << 8),
+        _ => 0xF000 | (x << 8) | timers_and_memory[rng.gen_range(0, timers_and_memory.len())],
+    };
+}
+
+#[test]
+fn vf_is_the_flag_when_it_is_also_the_destination() {
+    // the cases that are easy to get backwards, spelled out rather than left to chance
+    let programs: [(&[u16], u8); 5] = [
+        (&[0x6FFF, 0x6101, 0x8F14], 1), // FF + 1 carries
+        (&[0x6F05, 0x6105, 0x8F15], 1), // 5 - 5 doesn't borrow
+        (&[0x6F05, 0x6106, 0x8F15], 0), // 5 - 6 does
+        (&[0x6F03, 0x8FF6], 1),         // 3 >> 1 shifts a 1 out
+        (&[0x6F40, 0x8FFE], 0)          // 40 << 1 doesn't
+    ];
+
+    for (opcodes, flag) in programs.iter() {
+        let program: Vec<u8> = opcodes.iter().flat_map(|opcode| opcode.to_be_bytes().to_vec()).collect();
+        let mut machine = Chip8::new();
+        machine.load_program(&program).unwrap();
+        for _ in 0..opcodes.len() {
+            machine.step().unwrap();
+        }
+
+        assert_eq!(machine.registers()[0xF], *flag, "after {:04X?}", opcodes);
+    }
+}
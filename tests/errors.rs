@@ -0,0 +1,74 @@
+#![allow(clippy::needless_return)]
+
+// bad programs stop with an error saying where and why, rather than panicking
+
+use chip8_rust::core::{Chip8, ExecutionError};
+
+fn loaded(program: &[u8]) -> Chip8 {
+    let mut machine = Chip8::new();
+    machine.load_program(program).unwrap();
+    return machine;
+}
+
+#[test]
+fn fetching_from_the_last_byte_of_memory_is_out_of_bounds() {
+    // JP #FFF, leaving only one byte of instruction
+    let mut machine = loaded(&[0x1F, 0xFF]);
+    machine.step().unwrap();
+    assert_eq!(machine.pc(), 0xFFF);
+
+    // the second byte is the one that isn't there
+    assert_eq!(machine.step(), Err(ExecutionError::MemoryOutOfBounds { pc: 0xFFF, address: 0x1000 }));
+    assert_eq!(machine.pc(), 0xFFF);
+}
+
+#[test]
+fn fetching_the_last_whole_instruction_is_fine() {
+    // JP #FFE, which with nothing there is 0x0000
+    let mut machine = loaded(&[0x1F, 0xFE]);
+    machine.step().unwrap();
+    assert_eq!(machine.step(), Err(ExecutionError::UnknownOpcode { pc: 0xFFE, opcode: 0x0000 }));
+}
+
+#[test]
+fn memory_accesses_past_the_end_are_out_of_bounds() {
+    // LD I, #FFF; LD [I], V1, which wants #FFF and #1000
+    let mut machine = loaded(&[0xAF, 0xFF, 0xF1, 0x55]);
+    machine.step().unwrap();
+    assert_eq!(machine.step(), Err(ExecutionError::MemoryOutOfBounds { pc: 0x202, address: 0x1000 }));
+
+    // starting past the end gives where it started
+    // LD I, #FFF; LD V1, 1; ADD I, V1; LD V0, [I]
+    let mut machine = loaded(&[0xAF, 0xFF, 0x61, 0x01, 0xF1, 0x1E, 0xF0, 0x65]);
+    for _ in 0..3 {
+        machine.step().unwrap();
+    }
+    assert_eq!(machine.index(), 0x1000);
+    assert_eq!(machine.step(), Err(ExecutionError::MemoryOutOfBounds { pc: 0x206, address: 0x1000 }));
+}
+
+#[test]
+fn the_stack_only_goes_16_deep() {
+    // CALL to itself
+    let mut machine = loaded(&[0x22, 0x00]);
+    for _ in 0..16 {
+        machine.step().unwrap();
+    }
+    assert_eq!(machine.step(), Err(ExecutionError::StackOverflow { pc: 0x200 }));
+
+    // RET with nothing to return to
+    let mut machine = loaded(&[0x00, 0xEE]);
+    assert_eq!(machine.step(), Err(ExecutionError::StackUnderflow { pc: 0x200 }));
+}
+
+#[test]
+fn errors_from_execute_before_pc_has_moved_past_anything_dont_panic() {
+    // JP #000, then run instructions as if they came from below it
+    let mut machine = loaded(&[0x10, 0x00]);
+    machine.step().unwrap();
+    assert_eq!(machine.pc(), 0);
+
+    // there's nowhere sensible for them to have come from, so the address wraps
+    assert_eq!(machine.execute(0x0000), Err(ExecutionError::UnknownOpcode { pc: 0xFFFE, opcode: 0x0000 }));
+    assert_eq!(machine.execute(0x00EE), Err(ExecutionError::StackUnderflow { pc: 0xFFFE }));
+}
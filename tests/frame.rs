@@ -0,0 +1,87 @@
+#![allow(clippy::needless_return)]
+
+// every frontend draws Frame::pixels, so this checks what they're handed rather than any one
+// of them. pixels used to be on when they were 0xFFFFFFFF, and anything still expecting that
+// would draw the screen as all background
+
+use std::fs::File;
+use std::path::PathBuf;
+
+use chip8_rust::colour::Colour;
+use chip8_rust::config::Settings;
+use chip8_rust::core::{Chip8, MACHINE_SCREEN_WIDTH, MACHINE_SCREEN_HEIGHT};
+use chip8_rust::palette::Palette;
+use chip8_rust::persistence::Persistence;
+use chip8_rust::platform::{Display, Frame, Input, InputEvent, Platform, Silence};
+
+// LD I, the font's 0; DRW V0, V0, 5; JP to itself
+const DRAW_ZERO: [u8; 6] = [0xA0, 0x50, 0xD0, 0x05, 0x12, 0x04];
+
+// keeps whatever it was last asked to show
+#[derive(Default)]
+struct Captured {
+    pixels: Vec<Colour>
+}
+
+impl Display for Captured {
+    fn present(&mut self, frame: &Frame) -> Result<(), String> {
+        self.pixels = frame.pixels.to_vec();
+        return Ok(());
+    }
+
+    fn show_message(&mut self, _message: &str) {}
+}
+
+struct NoInput;
+
+impl Input for NoInput {
+    fn poll(&mut self) -> Result<Vec<InputEvent>, String> {
+        return Ok(vec![]);
+    }
+}
+
+fn platform(persistence: Persistence) -> Platform<Captured, NoInput, Silence> {
+    let mut machine = Chip8::new();
+    machine.load_program(&DRAW_ZERO).unwrap();
+
+    let settings = Settings { palette: Palette::theme("amber").unwrap(), persistence, ..Settings::default() };
+    let mut platform = Platform::new(machine, &settings, Captured::default(), NoInput, Silence);
+    assert!(platform.step().unwrap());
+
+    return platform;
+}
+
+// the top row of the 0 is 0xF0 and the second 0x90
+fn assert_drew_a_zero(pixels: &[Colour], width: usize, scale: usize) {
+    let palette = Palette::theme("amber").unwrap();
+    let (on, off) = (palette.colour(1), palette.background());
+    let pixel = |x: usize, y: usize| pixels[y * scale * width + x * scale];
+
+    assert_eq!([pixel(0, 0), pixel(1, 0), pixel(2, 0), pixel(3, 0), pixel(4, 0)], [on, on, on, on, off]);
+    assert_eq!([pixel(0, 1), pixel(1, 1), pixel(2, 1), pixel(3, 1)], [on, off, off, on]);
+    assert_eq!(pixel(MACHINE_SCREEN_WIDTH - 1, MACHINE_SCREEN_HEIGHT - 1), off);
+}
+
+#[test]
+fn drawn_pixels_reach_the_display_in_the_foreground_colour() {
+    for persistence in Persistence::modes() {
+        let platform = platform(persistence);
+        let pixels = &platform.display.pixels;
+        assert_eq!(pixels.len(), MACHINE_SCREEN_WIDTH * MACHINE_SCREEN_HEIGHT);
+        assert_drew_a_zero(pixels, MACHINE_SCREEN_WIDTH, 1);
+    }
+}
+
+#[test]
+fn drawn_pixels_are_saved_in_screenshots() {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("frame.png");
+    platform(Persistence::Off).save_screenshot(&path).unwrap();
+
+    let (info, mut reader) = png::Decoder::new(File::open(&path).unwrap()).read_info().unwrap();
+    let mut data = vec![0; reader.output_buffer_size()];
+    reader.next_frame(&mut data).unwrap();
+    let pixels: Vec<Colour> = data.chunks(4).map(|rgba| Colour::with_alpha(rgba[0], rgba[1], rgba[2], rgba[3])).collect();
+
+    let width = info.width as usize;
+    assert_drew_a_zero(&pixels, width, width / MACHINE_SCREEN_WIDTH);
+}
@@ -0,0 +1,134 @@
+#![allow(clippy::needless_return)]
+
+use std::fs;
+use std::path::PathBuf;
+
+use chip8_rust::core::Quirks;
+use chip8_rust::menu::{Menu, MenuAction, MenuInput, MenuView, MenuValues, QUIRKS};
+use chip8_rust::romdb::RomDatabase;
+
+fn view(menu: &Menu) -> MenuView {
+    let values = MenuValues { palette: "classic", speed: 1.0, keymap: "qwerty", quirks: Quirks { jump: true, ..Quirks::default() } };
+    return menu.view(&values);
+}
+
+// presses each of the inputs in turn, returning what the last one did
+fn press(menu: &mut Menu, inputs: &[MenuInput]) -> Option<MenuAction> {
+    let database = RomDatabase::embedded();
+    let mut action = None;
+    for input in inputs {
+        action = menu.input(*input, &database);
+    }
+    return action;
+}
+
+fn quirks_page() -> Menu {
+    let mut menu = Menu::new(PathBuf::from("."));
+    // quirks is the sixth item on the main page
+    press(&mut menu, &[MenuInput::Down; 5]);
+    assert_eq!(view(&menu).items[5], "quirks");
+    assert_eq!(press(&mut menu, &[MenuInput::Select]), None);
+    return menu;
+}
+
+#[test]
+fn up_and_down_wrap_around() {
+    let mut menu = Menu::new(PathBuf::from("."));
+    let count = view(&menu).items.len();
+    assert_eq!(view(&menu).selected, 0);
+
+    press(&mut menu, &[MenuInput::Up]);
+    assert_eq!(view(&menu).selected, count - 1);
+    assert_eq!(view(&menu).items[count - 1], "quit");
+
+    press(&mut menu, &[MenuInput::Down]);
+    assert_eq!(view(&menu).selected, 0);
+
+    press(&mut menu, &[MenuInput::Down, MenuInput::Down]);
+    assert_eq!(view(&menu).selected, 2);
+}
+
+#[test]
+fn back_closes_the_main_page() {
+    let mut menu = Menu::new(PathBuf::from("."));
+    assert_eq!(press(&mut menu, &[MenuInput::Back]), Some(MenuAction::Close));
+    assert_eq!(press(&mut menu, &[MenuInput::Select]), Some(MenuAction::Close));
+}
+
+#[test]
+fn back_from_a_sub_page_returns_to_the_main_page() {
+    let mut menu = quirks_page();
+    assert_eq!(view(&menu).title, "quirks");
+
+    press(&mut menu, &[MenuInput::Down]);
+    assert_eq!(press(&mut menu, &[MenuInput::Back]), None);
+    assert_eq!(view(&menu).title, "chip-8");
+    assert_eq!(view(&menu).selected, 0);
+
+    let mut menu = Menu::new(PathBuf::from("."));
+    menu.show_roms(&RomDatabase::embedded());
+    assert_ne!(view(&menu).title, "chip-8");
+    assert_eq!(press(&mut menu, &[MenuInput::Back]), None);
+    assert_eq!(view(&menu).title, "chip-8");
+}
+
+#[test]
+fn the_quirks_page_toggles_the_selected_quirk() {
+    let mut menu = quirks_page();
+    let items = view(&menu).items;
+    assert_eq!(items.len(), QUIRKS.len() + 1);
+    assert_eq!(items[2], "jump  < on >");
+    assert_eq!(items[3], "vf_reset  < off >");
+
+    for (index, name) in QUIRKS.iter().enumerate() {
+        assert!(view(&menu).items[index].starts_with(name));
+        assert_eq!(press(&mut menu, &[MenuInput::Select]), Some(MenuAction::Quirk(index)));
+        assert_eq!(press(&mut menu, &[MenuInput::Left]), Some(MenuAction::Quirk(index)));
+        assert_eq!(press(&mut menu, &[MenuInput::Right]), Some(MenuAction::Quirk(index)));
+        press(&mut menu, &[MenuInput::Down]);
+    }
+
+    // the last item goes back
+    assert_eq!(press(&mut menu, &[MenuInput::Select]), None);
+    assert_eq!(view(&menu).title, "chip-8");
+}
+
+#[test]
+fn left_and_right_change_values_on_the_main_page() {
+    let mut menu = Menu::new(PathBuf::from("."));
+    press(&mut menu, &[MenuInput::Down; 6]);
+    assert_eq!(view(&menu).items[6], "palette  < classic >");
+    assert_eq!(press(&mut menu, &[MenuInput::Left]), Some(MenuAction::Palette(false)));
+    assert_eq!(press(&mut menu, &[MenuInput::Right]), Some(MenuAction::Palette(true)));
+    assert_eq!(press(&mut menu, &[MenuInput::Down, MenuInput::Right]), Some(MenuAction::Speed(true)));
+
+    // but don't do anything to the items without a value
+    press(&mut menu, &[MenuInput::Up; 5]);
+    assert_eq!(view(&menu).items[2], "reset");
+    assert_eq!(press(&mut menu, &[MenuInput::Right]), None);
+    assert_eq!(press(&mut menu, &[MenuInput::Select]), Some(MenuAction::Reset));
+    assert_eq!(press(&mut menu, &[MenuInput::Down, MenuInput::Select]), Some(MenuAction::SoftReset));
+}
+
+#[test]
+fn the_rom_browser_lists_directories_first_and_loads_roms() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("menu");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(dir.join("more")).unwrap();
+    fs::write(dir.join("pong.ch8"), [0x12, 0x00]).unwrap();
+    fs::write(dir.join("too big.ch8"), vec![0; 4096]).unwrap();
+    fs::write(dir.join(".hidden"), [0x12, 0x00]).unwrap();
+
+    let mut menu = Menu::new(dir.clone());
+    menu.show_roms(&RomDatabase::embedded());
+    assert_eq!(view(&menu).items, ["more/", "pong.ch8"]);
+
+    let rom = dir.canonicalize().unwrap().join("pong.ch8");
+    assert_eq!(press(&mut menu, &[MenuInput::Down, MenuInput::Select]), Some(MenuAction::Load(rom)));
+
+    // into the directory and back out of it
+    press(&mut menu, &[MenuInput::Up, MenuInput::Select]);
+    assert_eq!(view(&menu).items, ["no roms here"]);
+    press(&mut menu, &[MenuInput::Left]);
+    assert_eq!(view(&menu).items, ["more/", "pong.ch8"]);
+}
@@ -0,0 +1,100 @@
+#![allow(clippy::needless_return)]
+
+use chip8_rust::colour::Colour;
+use chip8_rust::core::{Chip8, MACHINE_SCREEN_WIDTH, PIXEL_ON};
+use chip8_rust::palette::{Palette, THEMES};
+
+fn colours(count: usize) -> Vec<Colour> {
+    return (0..count).map(|i| Colour::new(i as u8, 0, 0)).collect();
+}
+
+#[test]
+fn every_theme_exists_with_a_different_background_and_foreground() {
+    for name in THEMES.iter() {
+        let palette = Palette::theme(name).unwrap();
+        assert_ne!(palette.colour(0), palette.colour(1), "{}", name);
+        assert_eq!(Palette::parse(name), Ok(palette));
+    }
+
+    assert_eq!(Palette::theme("nope"), None);
+    assert_eq!(Palette::default(), Palette::theme("classic").unwrap());
+}
+
+#[test]
+fn themes_have_a_colour_for_each_xo_chip_plane() {
+    let amber = Palette::theme("amber").unwrap();
+    assert_eq!(amber.background(), Colour::new(0x1A, 0x10, 0x00));
+    assert_eq!(amber.colour(1), Colour::new(0xFF, 0xB0, 0x00));
+    assert_eq!(amber.colour(2), Colour::new(0xB3, 0x7B, 0x00));
+    assert_eq!(amber.colour(3), Colour::new(0x66, 0x46, 0x00));
+}
+
+#[test]
+fn two_colours_are_just_off_and_on() {
+    let palette = Palette::from_colours(colours(2)).unwrap();
+    assert_eq!(palette.colour(0), Colour::new(0, 0, 0));
+    assert_eq!(palette.colour(1), Colour::new(1, 0, 0));
+}
+
+#[test]
+fn four_colours_are_one_for_each_pair_of_planes() {
+    let palette = Palette::from_colours(colours(4)).unwrap();
+    for pixel in 0..4 {
+        assert_eq!(palette.colour(pixel), Colour::new(pixel as u8, 0, 0));
+    }
+}
+
+#[test]
+fn sixteen_colours_are_one_for_each_combination_of_four_planes() {
+    let palette = Palette::from_colours(colours(16)).unwrap();
+    for pixel in 0..16 {
+        assert_eq!(palette.colour(pixel), Colour::new(pixel as u8, 0, 0));
+    }
+}
+
+#[test]
+fn only_two_four_or_sixteen_colours_make_a_palette() {
+    for count in [0, 1, 3, 5, 8, 15, 17] {
+        assert!(Palette::from_colours(colours(count)).is_err(), "{}", count);
+    }
+}
+
+#[test]
+fn lists_of_colours_are_parsed_with_the_background_first() {
+    let palette = Palette::parse("#102030, #FFAA00").unwrap();
+    assert_eq!(palette.background(), Colour::new(0x10, 0x20, 0x30));
+    assert_eq!(palette.colour(1), Colour::new(0xFF, 0xAA, 0x00));
+
+    assert!(Palette::parse("#102030, nope").is_err());
+    assert!(Palette::parse("#102030, #FFAA00, #000000").is_err());
+}
+
+#[test]
+fn the_background_and_foreground_can_be_changed_on_their_own() {
+    let mut palette = Palette::theme("green").unwrap();
+    palette.set_background(Colour::new(1, 2, 3));
+    palette.set_foreground(Colour::new(4, 5, 6));
+
+    assert_eq!(palette.background(), Colour::new(1, 2, 3));
+    assert_eq!(palette.colour(1), Colour::new(4, 5, 6));
+    assert_eq!(palette.colour(2), Palette::theme("green").unwrap().colour(2));
+}
+
+// frontends colour the screen by handing each value in video to the palette, so whatever the
+// core draws has to come out as the foreground
+#[test]
+fn pixels_drawn_by_the_core_are_the_foreground() {
+    // LD I, font for 0; DRW V0, V0, 5; JP to itself
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("palette-draw.ch8");
+    std::fs::write(&path, [0xA0, 0x50, 0xD0, 0x05, 0x12, 0x04]).unwrap();
+
+    let mut machine = Chip8::new();
+    machine.load_rom(path.to_str().unwrap()).unwrap();
+    machine.cycle().unwrap();
+
+    let palette = Palette::theme("amber").unwrap();
+    assert_eq!(machine.video[0], PIXEL_ON);
+    assert_eq!(palette.colour(machine.video[0]), palette.colour(1));
+    assert_eq!(palette.colour(machine.video[4]), palette.background());
+    assert_eq!(palette.colour(machine.video[MACHINE_SCREEN_WIDTH]), palette.colour(1));
+}
@@ -0,0 +1,85 @@
+#![allow(clippy::needless_return)]
+
+use chip8_rust::colour::Colour;
+use chip8_rust::palette::Palette;
+use chip8_rust::persistence::{Persistence, PersistenceFilter};
+
+
+fn black() -> Colour {
+    return Colour::new(0, 0, 0);
+}
+
+fn white() -> Colour {
+    return Colour::new(255, 255, 255);
+}
+
+fn palette() -> Palette {
+    return Palette::parse("#000000, #FFFFFF").unwrap();
+}
+
+#[test]
+fn off_leaves_every_frame_as_it_is() {
+    let palette = palette();
+    let mut filter = PersistenceFilter::new(Persistence::Off);
+
+    for video in [[1, 0, 1, 0], [0, 1, 0, 0], [0, 0, 0, 0]] {
+        assert_eq!(filter.apply(&video, &palette), palette.colourise(&video));
+    }
+}
+
+#[test]
+fn or_lights_anything_lit_in_this_frame_or_the_last() {
+    let palette = palette();
+    let mut filter = PersistenceFilter::new(Persistence::Or);
+
+    assert_eq!(filter.apply(&[1, 0, 0, 0], &palette), [white(), black(), black(), black()]);
+    assert_eq!(filter.apply(&[0, 1, 0, 0], &palette), [white(), white(), black(), black()]);
+
+    // only the last frame, not every one before it
+    assert_eq!(filter.apply(&[0, 0, 1, 0], &palette), [black(), white(), white(), black()]);
+}
+
+#[test]
+fn decay_fades_a_pixel_out_over_several_frames() {
+    let palette = palette();
+    let mut filter = PersistenceFilter::new(Persistence::Decay(0.6));
+
+    assert_eq!(filter.apply(&[1], &palette), [white()]);
+
+    let mut previous = white();
+    let mut frames = 0;
+    while previous != black() {
+        let colour = filter.apply(&[0], &palette)[0];
+        assert!(colour.red() < previous.red(), "brightened from {:?} to {:?}", previous, colour);
+        previous = colour;
+
+        frames += 1;
+        assert!(frames < 100, "never faded out");
+    }
+    assert!(frames > 2);
+
+    // and stays off
+    assert_eq!(filter.apply(&[0], &palette), [black()]);
+}
+
+#[test]
+fn decay_shows_lit_pixels_straight_away() {
+    let palette = palette();
+    let mut filter = PersistenceFilter::new(Persistence::Decay(0.9));
+
+    filter.apply(&[1, 0], &palette);
+    filter.apply(&[0, 0], &palette);
+    let frame = filter.apply(&[1, 0], &palette);
+    assert_eq!(frame, [white(), black()]);
+}
+
+#[test]
+fn modes_are_parsed_by_name() {
+    assert_eq!(Persistence::parse("off"), Ok(Persistence::Off));
+    assert_eq!(Persistence::parse("or"), Ok(Persistence::Or));
+    assert_eq!(Persistence::parse("decay=0.25"), Ok(Persistence::Decay(0.25)));
+    assert!(matches!(Persistence::parse("decay"), Ok(Persistence::Decay(_))));
+
+    assert!(Persistence::parse("decay=1").is_err());
+    assert!(Persistence::parse("sometimes").is_err());
+}
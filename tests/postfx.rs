@@ -0,0 +1,84 @@
+#![allow(clippy::needless_return)]
+
+// the software renderer is what screenshots fall back on without a gpu, so it's checked here
+// in place of the shaders
+
+use chip8_rust::colour::Colour;
+use chip8_rust::postfx::{Effects, PRESETS};
+
+const WIDTH: usize = 4;
+const HEIGHT: usize = 2;
+
+// every texel different, so any mix up shows
+fn frame() -> Vec<Colour> {
+    return (0..WIDTH * HEIGHT).map(|i| Colour::new(i as u8 * 30, 255 - i as u8 * 30, 0x40)).collect();
+}
+
+#[test]
+fn no_effects_is_a_plain_nearest_neighbour_scale() {
+    let frame = frame();
+    let scale = 3;
+    let output = Effects::default().render(&frame, WIDTH, HEIGHT, WIDTH * scale, HEIGHT * scale);
+
+    for y in 0..HEIGHT * scale {
+        for x in 0..WIDTH * scale {
+            assert_eq!(output[y * WIDTH * scale + x], frame[(y / scale) * WIDTH + x / scale], "{}, {}", x, y);
+        }
+    }
+}
+
+#[test]
+fn scanlines_darken_the_gaps_and_leave_the_middle_of_each_row_alone() {
+    let white = vec![Colour::new(255, 255, 255); WIDTH * HEIGHT];
+    let effects = Effects { scanlines: 0.5, ..Effects::default() };
+
+    // an odd scale puts an output row right in the middle of each source row
+    let scale = 5;
+    let output = effects.render(&white, WIDTH, HEIGHT, WIDTH * scale, HEIGHT * scale);
+    let row = |y: usize| output[y * WIDTH * scale..][..WIDTH * scale].to_vec();
+
+    for source_row in 0..HEIGHT {
+        let top = source_row * scale;
+        let middle = top + scale / 2;
+        assert!(row(middle).iter().all(|colour| *colour == Colour::new(255, 255, 255)));
+
+        // getting darker towards the edges
+        for y in top..middle {
+            assert!(row(y)[0].red() < row(y + 1)[0].red(), "row {}", y);
+            assert!(row(y)[0].red() > 0);
+        }
+        assert!(row(top + scale - 1)[0].red() < 255);
+    }
+}
+
+#[test]
+fn the_output_is_always_the_size_asked_for() {
+    let frame = frame();
+    for name in PRESETS.iter() {
+        let effects = Effects::preset(name).unwrap();
+        for (width, height) in [(4, 2), (8, 4), (13, 7), (64, 32), (3, 1)] {
+            assert_eq!(effects.render(&frame, WIDTH, HEIGHT, width, height).len(), width * height, "{} at {}x{}", name, width, height);
+        }
+    }
+}
+
+#[test]
+fn curvature_blacks_out_the_corners() {
+    let white = vec![Colour::new(255, 255, 255); WIDTH * HEIGHT];
+    let output = Effects::preset("crt").unwrap().render(&white, WIDTH, HEIGHT, 40, 20);
+    assert_eq!(output[0], Colour::new(0, 0, 0));
+    assert_eq!(output[40 * 20 - 1], Colour::new(0, 0, 0));
+}
+
+#[test]
+fn effects_are_parsed_from_presets_or_lists() {
+    assert_eq!(Effects::parse("none"), Ok(Effects::default()));
+    assert_eq!(
+        Effects::parse("scanlines=0.25, sharp"),
+        Ok(Effects { scanlines: 0.25, sharp_bilinear: true, ..Effects::default() })
+    );
+
+    assert!(Effects::parse("scanlines=2").is_err());
+    assert!(Effects::parse("scanlines=lots").is_err());
+    assert!(Effects::parse("sparkles").is_err());
+}
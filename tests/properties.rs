@@ -0,0 +1,156 @@
+#![allow(clippy::needless_return)]
+
+// properties of the arithmetic instructions that should hold for every input, rather than the
+// handful of cases the differential tests happen to hit. every register pair is fair game,
+// including Vx or Vy being VF, and each property is checked under all three quirk profiles
+
+use proptest::prelude::*;
+
+use chip8_rust::core::{Chip8, Quirks};
+
+// which 8xyn instruction, and what it should leave in Vx and VF given Vx and Vy
+type Arithmetic = fn(u8, u8, Quirks) -> (u8, u8);
+
+fn quirks() -> impl Strategy<Value = Quirks> {
+    prop_oneof![Just(Quirks::default()), Just(Quirks::chip8()), Just(Quirks::schip())]
+}
+
+// runs the setup instructions and then the one being tested, handing back the machine and the
+// registers as they were just before it
+fn run(quirks: Quirks, setup: &[u16], opcode: u16) -> (Chip8, [u8; 16]) {
+    let program: Vec<u8> = setup.iter().chain(Some(&opcode)).flat_map(|opcode| opcode.to_be_bytes().to_vec()).collect();
+
+    let mut machine = Chip8::new();
+    machine.load_program(&program).unwrap();
+    machine.quirks = quirks;
+    for _ in setup {
+        machine.step().unwrap();
+    }
+
+    let before = *machine.registers();
+    machine.step().unwrap();
+
+    return (machine, before);
+}
+
+fn load(register: usize, value: u8) -> u16 {
+    return 0x6000 | (register as u16) << 8 | value as u16;
+}
+
+// the result goes in Vx and then the flag in VF, so when x is F the flag is what's left
+fn check_arithmetic(n: u16, expected: Arithmetic, quirks: Quirks, x: usize, y: usize, a: u8, b: u8) -> Result<(), TestCaseError> {
+    let opcode = 0x8000 | (x as u16) << 8 | (y as u16) << 4 | n;
+    let (machine, before) = run(quirks, &[load(x, a), load(y, b)], opcode);
+    let (result, flag) = expected(before[x], before[y], quirks);
+
+    let mut registers = before;
+    registers[x] = result;
+    registers[0xF] = flag;
+    prop_assert_eq!(machine.registers(), &registers, "{:04X} with V{:X}={:02X}, V{:X}={:02X}", opcode, x, before[x], y, before[y]);
+
+    Ok(())
+}
+
+fn add(vx: u8, vy: u8, _: Quirks) -> (u8, u8) {
+    return (vx.wrapping_add(vy), (vx as u16 + vy as u16 > 0xFF) as u8);
+}
+
+fn sub(vx: u8, vy: u8, _: Quirks) -> (u8, u8) {
+    return (vx.wrapping_sub(vy), (vx >= vy) as u8);
+}
+
+fn subn(vx: u8, vy: u8, _: Quirks) -> (u8, u8) {
+    return (vy.wrapping_sub(vx), (vy >= vx) as u8);
+}
+
+fn shr(vx: u8, vy: u8, quirks: Quirks) -> (u8, u8) {
+    let value = if quirks.shift { vx } else { vy };
+    return (value / 2, value % 2);
+}
+
+fn shl(vx: u8, vy: u8, quirks: Quirks) -> (u8, u8) {
+    let value = if quirks.shift { vx } else { vy };
+    return ((value as u16 * 2) as u8, (value >= 0x80) as u8);
+}
+
+proptest! {
+    #[test]
+    fn add_wraps_and_sets_carry(quirks in quirks(), x in 0..16usize, y in 0..16usize, a: u8, b: u8) {
+        check_arithmetic(0x4, add, quirks, x, y, a, b)?;
+    }
+
+    #[test]
+    fn sub_wraps_and_sets_not_borrow(quirks in quirks(), x in 0..16usize, y in 0..16usize, a: u8, b: u8) {
+        check_arithmetic(0x5, sub, quirks, x, y, a, b)?;
+    }
+
+    #[test]
+    fn shr_sets_the_bit_shifted_out(quirks in quirks(), x in 0..16usize, y in 0..16usize, a: u8, b: u8) {
+        check_arithmetic(0x6, shr, quirks, x, y, a, b)?;
+    }
+
+    #[test]
+    fn subn_wraps_and_sets_not_borrow(quirks in quirks(), x in 0..16usize, y in 0..16usize, a: u8, b: u8) {
+        check_arithmetic(0x7, subn, quirks, x, y, a, b)?;
+    }
+
+    #[test]
+    fn shl_sets_the_bit_shifted_out(quirks in quirks(), x in 0..16usize, y in 0..16usize, a: u8, b: u8) {
+        check_arithmetic(0xE, shl, quirks, x, y, a, b)?;
+    }
+
+    // the flag case on its own, so it isn't left to x and y both coming up F by chance
+    #[test]
+    fn vf_holds_the_flag_when_it_is_the_destination(quirks in quirks(), y in 0..15usize, a: u8, b: u8) {
+        let operations: [(u16, Arithmetic); 5] = [(0x4, add), (0x5, sub), (0x6, shr), (0x7, subn), (0xE, shl)];
+        for (n, expected) in operations.iter() {
+            check_arithmetic(*n, *expected, quirks, 0xF, y, a, b)?;
+        }
+    }
+
+    // unlike 8xy4 there's no carry, so VF is left alone even when it wraps
+    #[test]
+    fn add_immediate_wraps_without_touching_vf(quirks in quirks(), x in 0..16usize, a: u8, kk: u8, flag: u8) {
+        let (machine, before) = run(quirks, &[load(0xF, flag), load(x, a)], 0x7000 | (x as u16) << 8 | kk as u16);
+
+        let mut registers = before;
+        registers[x] = before[x].wrapping_add(kk);
+        prop_assert_eq!(machine.registers(), &registers);
+    }
+
+    #[test]
+    fn bcd_stores_the_decimal_digits(quirks in quirks(), x in 0..16usize, value: u8, address in 0x300..0xFFDu16) {
+        let (machine, before) = run(quirks, &[load(x, value), 0xA000 | address], 0xF033 | (x as u16) << 8);
+        let digits = &machine.memory()[address as usize..address as usize + 3];
+
+        prop_assert_eq!(digits[0] as u16 * 100 + digits[1] as u16 * 10 + digits[2] as u16, value as u16);
+        prop_assert!(digits.iter().all(|digit| *digit < 10));
+        prop_assert_eq!(machine.registers(), &before);
+        prop_assert_eq!(machine.index(), address);
+    }
+
+    // any of the three digits landing past the end of memory is an error, and nothing is written
+    #[test]
+    fn bcd_past_the_end_of_memory_is_an_error(quirks in quirks(), x in 0..16usize, value: u8, address in 0xFFE..=0xFFFu16) {
+        let program: Vec<u8> = [load(x, value), 0xA000 | address, 0xF033 | (x as u16) << 8].iter()
+            .flat_map(|opcode| opcode.to_be_bytes().to_vec())
+            .collect();
+        let mut machine = Chip8::new();
+        machine.load_program(&program).unwrap();
+        machine.quirks = quirks;
+        machine.step().unwrap();
+        machine.step().unwrap();
+        let memory = *machine.memory();
+
+        prop_assert!(machine.step().is_err());
+        prop_assert_eq!(&machine.memory()[..], &memory[..]);
+    }
+
+    #[test]
+    fn add_to_index_adds_vx_without_touching_vf(quirks in quirks(), x in 0..16usize, value: u8, address in 0..0x1000u16, flag: u8) {
+        let (machine, before) = run(quirks, &[load(0xF, flag), load(x, value), 0xA000 | address], 0xF01E | (x as u16) << 8);
+
+        prop_assert_eq!(machine.index(), address + before[x] as u16);
+        prop_assert_eq!(machine.registers(), &before);
+    }
+}
@@ -0,0 +1,146 @@
+#![allow(clippy::needless_return)]
+
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+use chip8_rust::core::{MACHINE_SCREEN_WIDTH, MACHINE_SCREEN_HEIGHT, PIXEL_ON};
+use chip8_rust::palette::Palette;
+use chip8_rust::recording::{Recorder, RecordingFormat};
+use chip8_rust::screenshot;
+
+const SAMPLES_PER_FRAME: u32 = 44100 / 60;
+
+fn path(name: &str) -> PathBuf {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("recording").join(name);
+    let _ = fs::remove_dir_all(&path);
+    let _ = fs::remove_file(&path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    return path;
+}
+
+// a screen with just one pixel lit
+fn video(pixel: usize) -> Vec<u32> {
+    let mut video = vec![0; MACHINE_SCREEN_WIDTH * MACHINE_SCREEN_HEIGHT];
+    video[pixel] = PIXEL_ON;
+    return video;
+}
+
+// three frames of one screen, one of the next and two of the last
+fn record(path: &Path, format: RecordingFormat, tone: bool) {
+    let palette = Palette::default();
+    let mut recorder = Recorder::start(path, format, 2, &palette).unwrap();
+    for pixel in [0, 0, 0, 1, 2, 2] {
+        recorder.record(&video(pixel), &palette, tone).unwrap();
+    }
+    recorder.finish().unwrap();
+}
+
+fn gif_delays(path: &Path) -> Vec<u16> {
+    let mut options = gif::DecodeOptions::new();
+    options.set_color_output(gif::ColorOutput::Indexed);
+    let mut decoder = options.read_info(File::open(path).unwrap()).unwrap();
+
+    let mut delays = vec![];
+    while let Some(frame) = decoder.read_next_frame().unwrap() {
+        assert_eq!((frame.width as usize, frame.height as usize), (MACHINE_SCREEN_WIDTH * 2, MACHINE_SCREEN_HEIGHT * 2));
+        delays.push(frame.delay);
+    }
+    return delays;
+}
+
+#[test]
+fn the_format_comes_from_the_path() {
+    assert_eq!(RecordingFormat::from_path(&PathBuf::from("pong.GIF")), RecordingFormat::Gif);
+    assert_eq!(RecordingFormat::from_path(&PathBuf::from("pong")), RecordingFormat::Frames);
+    assert_eq!(RecordingFormat::parse("frames"), Ok(RecordingFormat::Frames));
+    assert!(RecordingFormat::parse("mp4").is_err());
+}
+
+#[test]
+fn repeated_frames_are_held_rather_than_written_again() {
+    let path = path("held.gif");
+    record(&path, RecordingFormat::Gif, false);
+    assert_eq!(gif_delays(&path).len(), 3);
+}
+
+#[test]
+fn gif_delays_follow_the_60hz_frame_clock() {
+    let path = path("delays.gif");
+    record(&path, RecordingFormat::Gif, false);
+
+    // 3, 1 and 2 frames are 5, 1.67 and 3.33 hundredths, with the ends rounded so the total
+    // still comes out at 6 frames
+    let delays = gif_delays(&path);
+    assert_eq!(delays, [5, 2, 3]);
+    assert_eq!(delays.iter().sum::<u16>(), 10);
+}
+
+#[test]
+fn frames_are_written_once_each_with_their_durations() {
+    let path = path("frames");
+    record(&path, RecordingFormat::Frames, false);
+
+    let mut pngs: Vec<String> = fs::read_dir(&path).unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .filter(|name| name.ends_with(".png"))
+        .collect();
+    pngs.sort();
+    assert_eq!(pngs, ["frame-000001.png", "frame-000002.png", "frame-000003.png"]);
+
+    let concat = fs::read_to_string(path.join("frames.ffconcat")).unwrap();
+    assert!(concat.contains("file 'frame-000001.png'\nduration 0.050000\n"), "{}", concat);
+    assert!(concat.contains("file 'frame-000002.png'\nduration 0.016667\n"), "{}", concat);
+    assert!(concat.contains("file 'frame-000003.png'\nduration 0.033333\n"), "{}", concat);
+}
+
+#[test]
+fn the_wav_has_a_frames_worth_of_samples_for_every_frame() {
+    let path = path("audio");
+    record(&path, RecordingFormat::Frames, true);
+
+    let mut reader = hound::WavReader::open(path.join("audio.wav")).unwrap();
+    assert_eq!(reader.spec().sample_rate, 44100);
+    assert_eq!(reader.len(), SAMPLES_PER_FRAME * 6);
+    assert!(reader.samples::<i16>().any(|sample| sample.unwrap() != 0));
+
+    // and silence while the buzzer's off, repeated frames included
+    let path = self::path("silence");
+    record(&path, RecordingFormat::Frames, false);
+    let mut reader = hound::WavReader::open(path.join("audio.wav")).unwrap();
+    assert_eq!(reader.len(), SAMPLES_PER_FRAME * 6);
+    assert!(reader.samples::<i16>().all(|sample| sample.unwrap() == 0));
+}
+
+#[test]
+fn recordings_made_one_after_another_get_their_own_numbers() {
+    let dir = path("numbered");
+    let palette = Palette::default();
+    File::create(screenshot::next_path(&dir, "Pong", "png").unwrap()).unwrap();
+
+    for (number, frames) in [(1, 4), (2, 7)] {
+        let path = screenshot::next_path(&dir, "Pong", "").unwrap();
+        assert_eq!(path, dir.join(format!("pong-{:03}", number)));
+
+        // a different screen every frame, so each one gets written
+        let mut recorder = Recorder::start(&path, RecordingFormat::Frames, 1, &palette).unwrap();
+        for pixel in 0..frames {
+            recorder.record(&video(pixel), &palette, false).unwrap();
+        }
+        recorder.finish().unwrap();
+
+        let mut names: Vec<String> = fs::read_dir(&path).unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        let mut expected: Vec<String> = (1..=frames).map(|frame| format!("frame-{:06}.png", frame)).collect();
+        expected.extend(["audio.wav".to_string(), "frames.ffconcat".to_string()]);
+        expected.sort();
+        assert_eq!(names, expected);
+
+        let reader = hound::WavReader::open(path.join("audio.wav")).unwrap();
+        assert_eq!(reader.len(), SAMPLES_PER_FRAME * frames as u32);
+    }
+
+    // the screenshot that was already there is left alone
+    assert!(dir.join("pong-001.png").is_file());
+}
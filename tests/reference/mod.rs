@@ -0,0 +1,224 @@
+use chip8_rust::core::Quirks;
+
+// a chip-8 written to be obviously right rather than fast or nice to use, straight from the
+// descriptions of each instruction, for checking the real one against. it only covers what
+// can be pinned down exactly, so RND is reported as OutOfScope rather than guessed at
+
+pub const PROGRAM_START: u16 = 0x200;
+pub const FONT_START: u16 = 0x50;
+pub const WIDTH: usize = 64;
+pub const HEIGHT: usize = 32;
+
+const FONT: [u8; 80] = [
+    0xF0, 0x90, 0x90, 0x90, 0xF0, 0x20, 0x60, 0x20, 0x20, 0x70, // 0 1
+    0xF0, 0x10, 0xF0, 0x80, 0xF0, 0xF0, 0x10, 0xF0, 0x10, 0xF0, // 2 3
+    0x90, 0x90, 0xF0, 0x10, 0x10, 0xF0, 0x80, 0xF0, 0x10, 0xF0, // 4 5
+    0xF0, 0x80, 0xF0, 0x90, 0xF0, 0xF0, 0x10, 0x20, 0x40, 0x40, // 6 7
+    0xF0, 0x90, 0xF0, 0x90, 0xF0, 0xF0, 0x90, 0xF0, 0x10, 0xF0, // 8 9
+    0xF0, 0x90, 0xF0, 0x90, 0x90, 0xE0, 0x90, 0xE0, 0x90, 0xE0, // A B
+    0xF0, 0x80, 0x80, 0x80, 0xF0, 0xE0, 0x90, 0x90, 0x90, 0xE0, // C D
+    0xF0, 0x80, 0xF0, 0x80, 0xF0, 0xF0, 0x80, 0xF0, 0x80, 0x80  // E F
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Stop {
+    UnknownOpcode,
+    StackOverflow,
+    StackUnderflow,
+    MemoryOutOfBounds,
+    OutOfScope // nothing to compare against, so the test stops here
+}
+
+pub struct Reference {
+    pub v: [u8; 16],
+    pub i: u16,
+    pub pc: u16,
+    pub stack: Vec<u16>,
+    pub delay_timer: u8,
+    pub sound_timer: u8,
+    pub memory: [u8; 4096],
+    pub video: [bool; WIDTH * HEIGHT],
+    pub keys: [bool; 16],
+    pub quirks: Quirks
+}
+
+impl Reference {
+    pub fn new(program: &[u8], quirks: Quirks) -> Self {
+        let mut memory = [0; 4096];
+        memory[FONT_START as usize..FONT_START as usize + FONT.len()].copy_from_slice(&FONT);
+        memory[PROGRAM_START as usize..PROGRAM_START as usize + program.len()].copy_from_slice(program);
+
+        Reference {
+            v: [0; 16],
+            i: 0,
+            pc: PROGRAM_START,
+            stack: vec![],
+            delay_timer: 0,
+            sound_timer: 0,
+            memory,
+            video: [false; WIDTH * HEIGHT],
+            keys: [false; 16],
+            quirks
+        }
+    }
+
+    // the instruction at pc, or None if it runs off the end of memory
+    pub fn opcode(&self) -> Option<u16> {
+        if self.pc as usize + 1 >= self.memory.len() {
+            return None;
+        }
+
+        return Some(((self.memory[self.pc as usize] as u16) << 8) | self.memory[self.pc as usize + 1] as u16);
+    }
+
+    pub fn step(&mut self) -> Result<(), Stop> {
+        let opcode = self.opcode().ok_or(Stop::MemoryOutOfBounds)?;
+        let x = ((opcode >> 8) & 0xF) as usize;
+        let y = ((opcode >> 4) & 0xF) as usize;
+        let n = opcode & 0xF;
+        let kk = (opcode & 0xFF) as u8;
+        let nnn = opcode & 0xFFF;
+
+        // everything is worked out before pc moves on, so nothing changes if we bail out
+        let mut next = self.pc + 2;
+        let skip = self.pc + 4;
+
+        match opcode >> 12 {
+            0x0 if opcode == 0x00E0 => self.video = [false; WIDTH * HEIGHT],
+            0x0 if opcode == 0x00EE => next = self.stack.pop().ok_or(Stop::StackUnderflow)?,
+            0x1 => next = nnn,
+            0x2 => {
+                if self.stack.len() == 16 {
+                    return Err(Stop::StackOverflow);
+                }
+                self.stack.push(next);
+                next = nnn;
+            },
+            0x3 if self.v[x] == kk => next = skip,
+            0x3 => (),
+            0x4 if self.v[x] != kk => next = skip,
+            0x4 => (),
+            0x5 if n == 0 && self.v[x] == self.v[y] => next = skip,
+            0x5 if n == 0 => (),
+            0x6 => self.v[x] = kk,
+            0x7 => self.v[x] = self.v[x].wrapping_add(kk),
+            0x8 => self.arithmetic(x, y, n)?,
+            0x9 if n == 0 && self.v[x] != self.v[y] => next = skip,
+            0x9 if n == 0 => (),
+            0xA => self.i = nnn,
+            0xB if self.quirks.jump => next = nnn + self.v[x] as u16,
+            0xB => next = nnn + self.v[0] as u16,
+            0xC => return Err(Stop::OutOfScope),
+            0xD => self.draw(x, y, n)?,
+            0xE if kk == 0x9E || kk == 0xA1 => {
+                let key = self.keys[self.v[x] as usize & 0xF];
+                if key == (kk == 0x9E) {
+                    next = skip;
+                }
+            },
+            0xF => match kk {
+                0x07 => self.v[x] = self.delay_timer,
+                0x0A => match self.keys.iter().position(|pressed| *pressed) {
+                    Some(key) => self.v[x] = key as u8,
+                    None => next = self.pc, // wait here until there's a key
+                },
+                0x15 => self.delay_timer = self.v[x],
+                0x18 => self.sound_timer = self.v[x],
+                0x1E => self.i = self.i.wrapping_add(self.v[x] as u16),
+                0x29 => self.i = FONT_START + 5 * (self.v[x] & 0xF) as u16,
+                0x33 => {
+                    let bcd = [self.v[x] / 100, self.v[x] / 10 % 10, self.v[x] % 10];
+                    self.write(self.i, &bcd)?;
+                },
+                0x55 => {
+                    let registers = self.v;
+                    self.write(self.i, &registers[..=x])?;
+                    if self.quirks.load_store {
+                        self.i = self.i.wrapping_add(x as u16 + 1);
+                    }
+                },
+                0x65 => {
+                    let start = self.i as usize;
+                    let values = self.memory.get(start..=start + x).ok_or(Stop::MemoryOutOfBounds)?;
+                    self.v[..=x].copy_from_slice(values);
+                    if self.quirks.load_store {
+                        self.i = self.i.wrapping_add(x as u16 + 1);
+                    }
+                },
+                _ => return Err(Stop::UnknownOpcode),
+            },
+            _ => return Err(Stop::UnknownOpcode),
+        }
+
+        self.pc = next;
+
+        Ok(())
+    }
+
+    fn arithmetic(&mut self, x: usize, y: usize, n: u16) -> Result<(), Stop> {
+        let (vx, vy) = (self.v[x], self.v[y]);
+
+        // the flag goes in last, so it wins when x is F
+        let (result, flag) = match n {
+            0x0 => (vy, None),
+            0x1 => (vx | vy, if self.quirks.vf_reset { Some(0) } else { None }),
+            0x2 => (vx & vy, if self.quirks.vf_reset { Some(0) } else { None }),
+            0x3 => (vx ^ vy, if self.quirks.vf_reset { Some(0) } else { None }),
+            0x4 => (vx.wrapping_add(vy), Some((vx as u16 + vy as u16 > 0xFF) as u8)),
+            0x5 => (vx.wrapping_sub(vy), Some((vx >= vy) as u8)),
+            0x7 => (vy.wrapping_sub(vx), Some((vy >= vx) as u8)),
+            0x6 | 0xE => {
+                let value = if self.quirks.shift { vx } else { vy };
+                if n == 0x6 {
+                    (value >> 1, Some(value & 1))
+                } else {
+                    (value << 1, Some(value >> 7))
+                }
+            },
+            _ => return Err(Stop::UnknownOpcode),
+        };
+
+        self.v[x] = result;
+        if let Some(flag) = flag {
+            self.v[0xF] = flag;
+        }
+
+        Ok(())
+    }
+
+    fn draw(&mut self, x: usize, y: usize, n: u16) -> Result<(), Stop> {
+        let sprite = self.memory.get(self.i as usize..self.i as usize + n as usize).ok_or(Stop::MemoryOutOfBounds)?.to_vec();
+        let left = self.v[x] as usize % WIDTH;
+        let top = self.v[y] as usize % HEIGHT;
+
+        let mut collision = false;
+        for (row, bits) in sprite.iter().enumerate() {
+            for column in 0..8 {
+                if bits & (0x80 >> column) == 0 {
+                    continue;
+                }
+
+                let (px, py) = (left + column, top + row);
+                if self.quirks.clip && (px >= WIDTH || py >= HEIGHT) {
+                    continue;
+                }
+
+                let pixel = &mut self.video[(py % HEIGHT) * WIDTH + px % WIDTH];
+                collision |= *pixel;
+                *pixel = !*pixel;
+            }
+        }
+
+        self.v[0xF] = collision as u8;
+
+        Ok(())
+    }
+
+    fn write(&mut self, address: u16, values: &[u8]) -> Result<(), Stop> {
+        let start = address as usize;
+        let memory = self.memory.get_mut(start..start + values.len()).ok_or(Stop::MemoryOutOfBounds)?;
+        memory.copy_from_slice(values);
+
+        Ok(())
+    }
+}
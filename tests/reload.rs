@@ -0,0 +1,66 @@
+#![allow(clippy::needless_return)]
+
+use std::fs::{self, File};
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use chip8_rust::config::Settings;
+use chip8_rust::core::{Chip8, Quirks};
+use chip8_rust::headless::Headless;
+use chip8_rust::platform::Platform;
+use chip8_rust::watcher::WatchMode;
+
+const SEED: u64 = 1234;
+
+// JP to itself
+const WAITING: [u8; 2] = [0x12, 0x00];
+// RND V0, #FF then JP to itself
+const RANDOM: [u8; 4] = [0xC0, 0xFF, 0x12, 0x02];
+
+fn configured_machine() -> Chip8 {
+    let mut machine = Chip8::new();
+    machine.seed_random(SEED);
+    machine.quirks = Quirks::chip8();
+    machine.tick_rate = 3;
+    machine.decode_cache = false;
+    machine.translate_blocks = true;
+    return machine;
+}
+
+#[test]
+fn reloading_keeps_the_seed_and_engine_settings() {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("reload.ch8");
+    fs::write(&path, WAITING).unwrap();
+
+    let mut machine = configured_machine();
+    machine.load_rom(&path).unwrap();
+
+    let settings = Settings { watch: WatchMode::Reset, ..Settings::default() };
+    let mut platform = Platform::from_frontend(machine, &settings, Headless { frames: 10 }).unwrap();
+    platform.rom_path = Some(path.clone());
+
+    // the first frame starts watching the rom
+    assert!(platform.step().unwrap());
+
+    // pushed into the future so it can't land on the same modification time, then waiting out
+    // the watcher's polling interval
+    fs::write(&path, RANDOM).unwrap();
+    File::options().write(true).open(&path).unwrap()
+        .set_modified(SystemTime::now() + Duration::from_secs(10)).unwrap();
+    thread::sleep(Duration::from_millis(600));
+    assert!(platform.step().unwrap());
+
+    // the same as if the new version had been loaded into a machine set up the same way
+    let mut expected = configured_machine();
+    expected.load_program(&RANDOM).unwrap();
+    expected.cycle().unwrap();
+
+    let machine = &platform.machine;
+    assert_eq!(machine.pc(), 0x202);
+    assert_eq!(machine.registers(), expected.registers());
+    assert_eq!(machine.quirks, Quirks::chip8());
+    assert_eq!(machine.tick_rate, 3);
+    assert!(!machine.decode_cache);
+    assert!(machine.translate_blocks);
+}
@@ -0,0 +1,125 @@
+#![allow(clippy::needless_return)]
+
+use chip8_rust::core::{Chip8, Quirks, MACHINE_SCREEN_WIDTH, PIXEL_ON};
+
+// draws the font's 0 in the top left, then writes over both the font and its own first
+// instruction, which becomes JP 0x250
+const SELF_MODIFYING: [u8; 12] = [
+    0xA0, 0x50, // LD I, 0x050, the font's 0
+    0xD1, 0x15, // DRW V1, V1, 5
+    0x60, 0x12, // LD V0, 0x12
+    0xF0, 0x55, // LD [I], V0
+    0xA2, 0x00, // LD I, 0x200
+    0xF0, 0x55  // LD [I], V0
+];
+
+fn machine(program: &[u8]) -> Chip8 {
+    let mut machine = Chip8::new();
+    machine.tick_rate = 1;
+    machine.load_program(program).unwrap();
+    return machine;
+}
+
+fn run(machine: &mut Chip8, instructions: usize) {
+    for _ in 0..instructions {
+        machine.cycle().unwrap();
+    }
+}
+
+// the top row of the font's 0 is 0xF0, so the first four pixels
+fn drew_a_zero(machine: &Chip8) -> bool {
+    let row = &machine.video[..8];
+    return row[..4].iter().all(|pixel| *pixel == PIXEL_ON) && row[4..].iter().all(|pixel| *pixel == 0);
+}
+
+#[test]
+fn reset_restores_the_rom_and_the_font() {
+    let mut machine = machine(&SELF_MODIFYING);
+    run(&mut machine, 6);
+    assert!(drew_a_zero(&machine));
+
+    machine.reset();
+    assert!(machine.video.iter().all(|pixel| *pixel == 0));
+    assert_eq!(machine.pc(), 0x200);
+    assert_eq!(machine.registers(), &[0; 16]);
+
+    // LD I, 0x050 is back in place of the jump, and the 0 it draws is the original one rather
+    // than starting with 0x12
+    run(&mut machine, 2);
+    assert_eq!(machine.pc(), 0x204);
+    assert!(drew_a_zero(&machine));
+}
+
+#[test]
+fn reset_keeps_the_settings() {
+    let mut machine = machine(&SELF_MODIFYING);
+    machine.quirks = Quirks::chip8();
+    machine.tick_rate = 7;
+
+    machine.reset();
+    assert_eq!(machine.quirks, Quirks::chip8());
+    assert_eq!(machine.tick_rate, 7);
+}
+
+#[test]
+fn soft_reset_keeps_memory_and_the_screen() {
+    let mut machine = machine(&SELF_MODIFYING);
+    run(&mut machine, 6);
+
+    machine.soft_reset();
+    assert_eq!(machine.pc(), 0x200);
+    assert!(drew_a_zero(&machine));
+
+    // the first instruction is still the jump the program wrote
+    run(&mut machine, 1);
+    assert_eq!(machine.pc(), 0x250);
+}
+
+#[test]
+fn soft_reset_clears_the_registers_stack_and_timers() {
+    let mut machine = machine(&[
+        0x61, 0x05, // LD V1, 5
+        0xF1, 0x15, // LD DT, V1
+        0xF1, 0x18, // LD ST, V1
+        0xA1, 0x23, // LD I, 0x123
+        0x23, 0x00  // CALL 0x300
+    ]);
+    run(&mut machine, 5);
+    assert_eq!(machine.registers()[1], 5);
+    assert!(machine.delay_timer() > 0);
+    assert!(machine.sound_timer() > 0);
+    assert_eq!(machine.index(), 0x123);
+    assert_eq!(machine.stack_depth(), 1);
+    assert_eq!(machine.pc(), 0x300);
+
+    machine.soft_reset();
+    assert_eq!(machine.registers(), &[0; 16]);
+    assert_eq!(machine.delay_timer(), 0);
+    assert_eq!(machine.sound_timer(), 0);
+    assert_eq!(machine.index(), 0);
+    assert_eq!(machine.stack_depth(), 0);
+    assert_eq!(machine.pc(), 0x200);
+
+    // and it runs from the top again
+    run(&mut machine, 1);
+    assert_eq!(machine.registers()[1], 5);
+    assert_eq!(machine.video[MACHINE_SCREEN_WIDTH], 0);
+}
+
+#[test]
+fn a_seeded_machine_picks_the_same_random_numbers_after_a_reset() {
+    // RND V0, #FF; RND V1, #FF; RND V2, #FF; JP to itself
+    let mut machine = machine(&[0xC0, 0xFF, 0xC1, 0xFF, 0xC2, 0xFF, 0x12, 0x06]);
+    machine.seed_random(99);
+    run(&mut machine, 4);
+    let first = *machine.registers();
+
+    machine.reset();
+    run(&mut machine, 4);
+    assert_eq!(machine.registers(), &first);
+
+    // a soft reset is only starting the program over, so RND carries on where it was
+    machine.soft_reset();
+    run(&mut machine, 4);
+    assert_ne!(machine.registers(), &first);
+}
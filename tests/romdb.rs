@@ -0,0 +1,98 @@
+#![allow(clippy::needless_return)]
+
+// looking roms up in a database laid out like the community chip-8-database. tests/data/database
+// has programs.json and sha1-hashes.json for a few tiny roms, and platforms.json comes from the
+// built in copy
+
+use std::path::Path;
+
+use chip8_rust::colour::Colour;
+use chip8_rust::config::{Config, Overrides, Settings};
+use chip8_rust::core::Quirks;
+use chip8_rust::romdb::RomDatabase;
+
+const SPIN: [u8; 2] = [0x12, 0x00]; // JP #200
+const CLEAR: [u8; 4] = [0x00, 0xE0, 0x12, 0x02]; // CLS, then JP #202
+const STORE: [u8; 4] = [0xF1, 0x55, 0x12, 0x02]; // LD [I], V1, then JP #202
+const LOAD: [u8; 4] = [0xF1, 0x65, 0x12, 0x02]; // LD V1, [I], then JP #202
+
+fn database() -> RomDatabase {
+    return RomDatabase::load(Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/database"))).unwrap();
+}
+
+// the same as Chip8::rom_hash would give once the rom was loaded
+fn hash(program: &[u8]) -> String {
+    return sha1_smol::Sha1::from(program).digest().to_string();
+}
+
+// what the rom would run with, going by the database and nothing else
+fn settings(database: &RomDatabase, hash: &str) -> Settings {
+    let info = database.lookup(hash).unwrap();
+    return Config::default().resolve(hash, Some(&info.overrides), &Overrides::default()).unwrap();
+}
+
+#[test]
+fn a_known_rom_gets_its_title_and_platform() {
+    let database = database();
+    let info = database.lookup(&hash(&SPIN)).unwrap();
+
+    assert_eq!(info.title, "Spin");
+    assert_eq!(info.platform.map(|platform| platform.id.as_str()), Some("superchip"));
+    assert_eq!(info.keys, vec![("left", 4), ("right", 6)]);
+}
+
+#[test]
+fn a_known_rom_gets_its_platforms_quirks_and_tick_rate() {
+    let settings = settings(&database(), &hash(&SPIN));
+
+    assert_eq!(settings.quirks, Quirks::schip());
+    assert_eq!(settings.tick_rate, 30);
+    assert_eq!(settings.title.as_deref(), Some("Spin"));
+    assert_eq!(settings.palette.background(), Colour::new(0x10, 0x10, 0x10));
+    assert_eq!(settings.palette.colour(1), Colour::new(0xFF, 0xAA, 0x00));
+}
+
+#[test]
+fn a_roms_own_quirks_and_tick_rate_win_over_its_platforms() {
+    let settings = settings(&database(), &hash(&CLEAR));
+
+    // the cosmac vip's quirks, apart from shift and wrap which the rom sets itself
+    let expected = Quirks { shift: true, clip: false, ..Quirks::chip8() };
+    assert_eq!(settings.quirks, expected);
+    assert_eq!(settings.tick_rate, 20);
+}
+
+// superchip leaves I where it is after fx55/fx65, going by memoryLeaveIUnchanged
+#[test]
+fn memory_increment_by_x_only_moves_i_when_it_is_set() {
+    let database = database();
+    assert!(!settings(&database, &hash(&SPIN)).quirks.load_store);
+
+    // false only says it isn't by x, which says nothing about whether I moves
+    assert_eq!(settings(&database, &hash(&STORE)).quirks, Quirks::schip());
+
+    // but true means it moves, near enough the same as by x + 1
+    assert_eq!(settings(&database, &hash(&LOAD)).quirks, Quirks { load_store: true, ..Quirks::schip() });
+}
+
+#[test]
+fn hashes_are_looked_up_case_insensitively() {
+    let database = database();
+    let info = database.lookup(&hash(&SPIN).to_ascii_uppercase()).unwrap();
+    assert_eq!(info.title, "Spin");
+}
+
+#[test]
+fn an_unknown_rom_isnt_found() {
+    assert!(database().lookup(&hash(&[0x00, 0xE0])).is_none());
+}
+
+#[test]
+fn every_hash_in_the_built_in_database_points_at_a_program_with_that_rom() {
+    // lookup only comes back empty if the hash points at the wrong program, or past the end
+    let database = RomDatabase::embedded();
+    let hashes: std::collections::HashMap<String, usize> = serde_json::from_str(include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/database/sha1-hashes.json"))).unwrap();
+    for hash in hashes.keys() {
+        assert!(database.lookup(hash).is_some(), "{} isn't in the program it points at", hash);
+    }
+}
@@ -0,0 +1,101 @@
+#![allow(clippy::needless_return)]
+
+use std::fs::{self, File};
+use std::path::PathBuf;
+
+use chip8_rust::colour::Colour;
+use chip8_rust::core::{MACHINE_SCREEN_WIDTH, MACHINE_SCREEN_HEIGHT};
+use chip8_rust::postfx::Effects;
+use chip8_rust::screenshot;
+
+// a fresh directory for each test, since they run in parallel
+fn directory(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("screenshot").join(name);
+    let _ = fs::remove_dir_all(&dir);
+    return dir;
+}
+
+fn frame() -> Vec<Colour> {
+    let mut frame = vec![Colour::new(0, 0, 0); MACHINE_SCREEN_WIDTH * MACHINE_SCREEN_HEIGHT];
+    frame[0] = Colour::new(255, 255, 255);
+    return frame;
+}
+
+#[test]
+fn rom_names_are_made_safe_for_file_names() {
+    for (name, safe) in [
+        ("Space Invaders [David Winter].ch8", "space-invaders--david-winter--ch8"),
+        ("pong_2", "pong_2"),
+        ("../../etc", "etc"),
+        ("???", "chip8"),
+        ("", "chip8"),
+    ] {
+        assert_eq!(screenshot::safe_name(name), safe);
+    }
+
+    let dir = directory("names");
+    assert_eq!(screenshot::next_path(&dir, "Pong (1 player)", "png").unwrap(), dir.join("pong--1-player-001.png"));
+}
+
+#[test]
+fn screenshots_take_the_next_free_number() {
+    let dir = directory("numbers");
+
+    let first = screenshot::next_path(&dir, "pong", "png").unwrap();
+    assert_eq!(first, dir.join("pong-001.png"));
+    File::create(&first).unwrap();
+    assert_eq!(screenshot::next_path(&dir, "pong", "png").unwrap(), dir.join("pong-002.png"));
+
+    // a gap left by a deleted screenshot gets filled first
+    File::create(dir.join("pong-003.png")).unwrap();
+    assert_eq!(screenshot::next_path(&dir, "pong", "png").unwrap(), dir.join("pong-002.png"));
+
+    // and other roms are numbered on their own
+    assert_eq!(screenshot::next_path(&dir, "tetris", "png").unwrap(), dir.join("tetris-001.png"));
+}
+
+#[test]
+fn each_extension_is_numbered_on_its_own() {
+    let dir = directory("extensions");
+    File::create(screenshot::next_path(&dir, "pong", "png").unwrap()).unwrap();
+    File::create(screenshot::next_path(&dir, "pong", "png").unwrap()).unwrap();
+
+    // a recording isn't numbered after the screenshots, or kept from overwriting another one
+    // just because there's no screenshot with its number
+    assert_eq!(screenshot::next_path(&dir, "pong", "gif").unwrap(), dir.join("pong-001.gif"));
+    File::create(dir.join("pong-001.gif")).unwrap();
+    assert_eq!(screenshot::next_path(&dir, "pong", "gif").unwrap(), dir.join("pong-002.gif"));
+
+    // no extension at all is for frame directories
+    fs::create_dir(dir.join("pong-001")).unwrap();
+    assert_eq!(screenshot::next_path(&dir, "pong", "").unwrap(), dir.join("pong-002"));
+}
+
+#[test]
+fn screenshots_are_saved_at_the_scale_asked_for() {
+    let dir = directory("scale");
+    fs::create_dir_all(&dir).unwrap();
+
+    for (scale, effects) in [(1, None), (4, None), (3, Effects::preset("crt"))] {
+        let (pixels, width, height) = screenshot::render(&frame(), scale, effects);
+        assert_eq!((width, height), (MACHINE_SCREEN_WIDTH * scale as usize, MACHINE_SCREEN_HEIGHT * scale as usize));
+        assert_eq!(pixels.len(), width * height);
+
+        let path = dir.join(format!("scale-{}.png", scale));
+        screenshot::write_png(&path, &pixels, width, height).unwrap();
+
+        let (info, _) = png::Decoder::new(File::open(&path).unwrap()).read_info().unwrap();
+        assert_eq!((info.width as usize, info.height as usize), (width, height));
+    }
+}
+
+#[test]
+fn without_effects_each_pixel_becomes_a_square() {
+    let (pixels, width, _) = screenshot::render(&frame(), 2, None);
+    assert_eq!(pixels[0], Colour::new(255, 255, 255));
+    assert_eq!(pixels[1], Colour::new(255, 255, 255));
+    assert_eq!(pixels[width], Colour::new(255, 255, 255));
+    assert_eq!(pixels[width + 1], Colour::new(255, 255, 255));
+    assert_eq!(pixels[2], Colour::new(0, 0, 0));
+    assert_eq!(pixels[width * 2], Colour::new(0, 0, 0));
+}
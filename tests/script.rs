@@ -0,0 +1,72 @@
+#![allow(clippy::needless_return)]
+
+// input scripts are written by hand, so bad ones need to be turned away with the line that's
+// wrong rather than quietly pressing the wrong keys
+
+use chip8_rust::script::Script;
+
+fn keys(keys: &[u8]) -> u16 {
+    return keys.iter().fold(0, |held, key| held | 1 << key);
+}
+
+#[test]
+fn keys_are_held_from_their_frame_until_the_next_change() {
+    let script = Script::parse("10 5\n20 -\n30 A\n").unwrap();
+
+    assert_eq!(script.keys_at(0), 0);
+    assert_eq!(script.keys_at(9), 0);
+    assert_eq!(script.keys_at(10), keys(&[5]));
+    assert_eq!(script.keys_at(19), keys(&[5]));
+    assert_eq!(script.keys_at(20), 0);
+    assert_eq!(script.keys_at(30), keys(&[0xA]));
+    assert_eq!(script.keys_at(1000), keys(&[0xA]));
+}
+
+#[test]
+fn a_dash_holds_nothing() {
+    let script = Script::parse("0 F\n5 -").unwrap();
+    assert_eq!(script.changes, vec![(0, keys(&[0xF])), (5, 0)]);
+}
+
+#[test]
+fn several_keys_can_be_held_at_once_in_either_case() {
+    let script = Script::parse("0 46\n1 0aF\n2 55").unwrap();
+    assert_eq!(script.changes, vec![(0, keys(&[4, 6])), (1, keys(&[0, 0xA, 0xF])), (2, keys(&[5]))]);
+}
+
+#[test]
+fn comments_and_blank_lines_are_skipped() {
+    let script = Script::parse("# start\n\n  10 5   # jump\n\t\n").unwrap();
+    assert_eq!(script.changes, vec![(10, keys(&[5]))]);
+}
+
+#[test]
+fn frames_can_repeat_but_not_go_backwards() {
+    // the same frame twice is fine, the later line wins
+    let script = Script::parse("10 1\n10 2").unwrap();
+    assert_eq!(script.keys_at(10), keys(&[2]));
+
+    let e = Script::parse("10 1\n20 2\n\n15 3").unwrap_err();
+    assert!(e.contains("line 4"), "{}", e);
+}
+
+#[test]
+fn invalid_lines_are_rejected_with_their_line_number() {
+    for (text, line) in [
+        ("10", 1),            // no keys
+        ("x 5", 1),           // frame isn't a number
+        ("-1 5", 1),          // or is negative
+        ("0 5\n10 G", 2),     // key isn't hex
+        ("0 5\n1 -5", 2),     // dash mixed with keys
+        ("0 5\n1 2\n2 3 4", 3) // too much on the line
+    ] {
+        let e = Script::parse(text).unwrap_err();
+        assert!(e.contains(&format!("line {} ", line)), "{:?} gave {}", text, e);
+    }
+}
+
+#[test]
+fn an_empty_script_holds_nothing() {
+    let script = Script::parse("").unwrap();
+    assert_eq!(script.keys_at(0), 0);
+}
@@ -0,0 +1,84 @@
+#![allow(clippy::needless_return)]
+
+// traces have to read back as exactly what was run, in either format, or trace-diff would find
+// differences that aren't there
+
+use std::path::PathBuf;
+
+use chip8_rust::assembler;
+use chip8_rust::core::Chip8;
+use chip8_rust::trace::{self, Change, Entry, TraceFormat, TraceOptions, Tracer};
+
+// touches every kind of change: a register, several at once with VF, I, and nothing at all
+const PROGRAM: &str = "
+        LD V0, #FF
+        LD V1, 2
+        ADD V0, V1      ; V0 and VF
+        LD I, #234
+        ADD I, V1
+        LD F, V1
+        LD [I], V1      ; memory only
+        CLS
+loop:   JP loop
+";
+const STEPS: usize = 12;
+
+fn round_trip(format: TraceFormat, name: &str) -> Vec<Entry> {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let options = TraceOptions { format, ..TraceOptions::default() };
+
+    let mut machine = Chip8::new();
+    machine.load_program(&assembler::assemble(PROGRAM).unwrap()).unwrap();
+    let mut tracer = Tracer::create(&path, options).unwrap();
+    for _ in 0..STEPS {
+        tracer.step(&mut machine).unwrap();
+    }
+    tracer.finish().unwrap();
+
+    let entries = trace::read_trace(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    return entries;
+}
+
+fn expected() -> Vec<Entry> {
+    let entry = |cycle: u64, pc: u16, opcode: u16, changes: Vec<Change>| Entry { cycle, pc, opcode, changes };
+
+    let mut expected = vec![
+        entry(0, 0x200, 0x60FF, vec![Change::Register(0, 0xFF)]),
+        entry(1, 0x202, 0x6102, vec![Change::Register(1, 2)]),
+        entry(2, 0x204, 0x8014, vec![Change::Register(0, 0x01), Change::Register(0xF, 1)]),
+        entry(3, 0x206, 0xA234, vec![Change::Index(0x234)]),
+        entry(4, 0x208, 0xF11E, vec![Change::Index(0x236)]),
+        entry(5, 0x20A, 0xF129, vec![Change::Index(0x5A)]),
+        entry(6, 0x20C, 0xF155, vec![]),
+        entry(7, 0x20E, 0x00E0, vec![]),
+    ];
+    for cycle in expected.len() as u64..STEPS as u64 {
+        expected.push(entry(cycle, 0x210, 0x1210, vec![]));
+    }
+
+    return expected;
+}
+
+#[test]
+fn text_traces_read_back_the_same() {
+    assert_eq!(round_trip(TraceFormat::Text, "round-trip.txt"), expected());
+}
+
+#[test]
+fn binary_traces_read_back_the_same() {
+    assert_eq!(round_trip(TraceFormat::Binary, "round-trip.bin"), expected());
+}
+
+#[test]
+fn anything_else_is_an_error() {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("not-a-trace.txt");
+    std::fs::write(&path, "0\t200\t00E0\tCLS\t\nnope\n").unwrap();
+    assert!(trace::read_trace(&path).unwrap_err().ends_with("line 2 isn't a trace entry"));
+
+    let mut truncated = trace::TRACE_MAGIC.to_vec();
+    truncated.extend_from_slice(&[0, 0, 0]);
+    std::fs::write(&path, truncated).unwrap();
+    assert!(trace::read_trace(&path).unwrap_err().ends_with("the trace ends part way through an entry"));
+}
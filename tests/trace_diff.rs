@@ -0,0 +1,54 @@
+#![allow(clippy::needless_return)]
+
+// runs the trace-diff binary itself, the way it would be from a shell
+
+use std::path::PathBuf;
+use std::process::Command;
+
+// LD V1, 3; SHR V0, V1; JP to itself. with the shift quirk V0 is shifted in place and stays 0,
+// without it V0 gets V1 shifted, which is 1, with the 1 shifted out going in VF
+const SHIFT: [u8; 6] = [0x61, 0x03, 0x80, 0x16, 0x12, 0x04];
+
+// exit code and stdout
+fn trace_diff(args: &[&str]) -> (i32, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_trace-diff")).args(args).output().unwrap();
+    return (output.status.code().unwrap(), String::from_utf8(output.stdout).unwrap());
+}
+
+fn rom() -> String {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("trace-diff-shift.ch8");
+    std::fs::write(&path, SHIFT).unwrap();
+    return path.to_str().unwrap().to_string();
+}
+
+#[test]
+fn a_quirk_that_changes_the_result_is_found_at_the_instruction_it_matters() {
+    let rom = rom();
+    let (code, output) = trace_diff(&["--rom", &rom, "--a", "shift=false", "--b", "shift=on", "--frames", "1"]);
+    assert_eq!(code, 1, "{}", output);
+
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines[0], "first difference after 1 instructions (frame 0):");
+    assert!(lines[1].starts_with("a: 1\t202\t8016\tSHR V0, V1"), "{}", output);
+    assert!(lines[2].starts_with("b: 1\t202\t8016\tSHR V0, V1"), "{}", output);
+    assert_eq!(lines[4], "  V0: 01 / 00");
+    assert_eq!(lines[5], "  VF: 01 / 00");
+    assert_eq!(lines.len(), 6 + 3, "{}", output); // and the first instruction as context
+    assert!(lines[8].contains("LD V1, #03"), "{}", output);
+}
+
+#[test]
+fn runs_that_agree_say_so() {
+    let rom = rom();
+    let (code, output) = trace_diff(&["--rom", &rom, "--a", "shift=1", "--b", "profile=schip", "--frames", "2"]);
+    assert_eq!(code, 0, "{}", output);
+    assert!(output.starts_with("no differences in "), "{}", output);
+}
+
+#[test]
+fn quirks_are_switched_on_and_off_the_same_way_as_on_the_command_line() {
+    let rom = rom();
+    let (code, output) = trace_diff(&["--rom", &rom, "--a", "shift=yes", "--b", "shift=off"]);
+    assert_eq!(code, 2);
+    assert!(output.starts_with("error: shift expects true or false, got yes"), "{}", output);
+}